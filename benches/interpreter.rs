@@ -0,0 +1,102 @@
+#![allow(clippy::needless_return)]
+
+// ----- Imports ----- //
+
+use std::time::{Duration, Instant};
+
+use chip_8::assembler;
+use chip_8::config::Settings;
+use chip_8::keyboard::Keymap;
+use chip_8::machine::{Backend, Machine};
+use chip_8::platform::Platform;
+use chip_8::rom::RomImage;
+use chip_8::speed::DEFAULT_FAST_FORWARD;
+use chip_8::timing::Timing;
+
+// ----- Consts ----- //
+
+const INSTRUCTIONS_PER_FRAME: u32 = 10_000;
+const FRAMES: u64 = 300;
+
+/// Programs to run, spinning forever without waiting on anything.
+const PROGRAMS: [(&str, &str); 3] = [
+    ("arithmetic", "
+        loop: ADDI(v0, 03)
+              SET(v1, v0)
+              XOR(v1, v2)
+              ADD(v2, v1)
+              SHR(v3, v2)
+              SUB(v4, v0)
+              SEQ(v0, 00)
+              ADDI(v5, 01)
+              JUMP(loop)
+    "),
+    ("memory", "
+        loop: ADDI(v0, 01)
+              SETN(300)
+              BCD(v0)
+              LDM(v2)
+              ADDN(v1)
+              STM(v5)
+              JUMP(loop)
+    "),
+    // Rewrites one of its own instructions on every iteration, so the decoded
+    // instruction is thrown away every time.
+    ("self-modifying", "
+               SETI(v0, 61)
+        loop:  ADDI(v1, 01)
+               SETN(patch)
+               STM(v1)
+        patch: SETI(v1, 00)
+               ADD(v2, v1)
+               JUMP(loop)
+    "),
+];
+
+// ----- Functions ----- //
+
+/// Run the `program` for a while, measuring how many instructions it runs each
+/// second.
+fn measure(program: &[u8], decode_cache: bool, backend: Backend) -> f64 {
+    let platform = Platform::Chip8;
+    let settings = Settings {
+        platform,
+        quirks: platform.quirks(),
+        layout: platform.layout(),
+        keymap: Keymap::default(),
+        speed: INSTRUCTIONS_PER_FRAME,
+        fast_forward: DEFAULT_FAST_FORWARD,
+        slow_motion: 1,
+        timing: Timing::Fixed,
+        generator: platform.generator(),
+        seed: 1,
+        console: None,
+    };
+    let mut machine = Machine::new(&RomImage::from(program.to_vec()), &settings);
+    machine.set_decode_cache(decode_cache);
+    machine.set_backend(backend);
+
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        if machine.run_frame().is_err() {
+            panic!("The benchmark program failed");
+        }
+    }
+    let elapsed = start.elapsed().max(Duration::from_nanos(1));
+
+    return machine.instructions() as f64 / elapsed.as_secs_f64();
+}
+
+fn main() {
+    println!("{:<16} {:>16} {:>16} {:>8} {:>16} {:>8}",
+             "program", "uncached (ips)", "cached (ips)", "gain", "recompiled (ips)", "gain");
+
+    for (name, source) in PROGRAMS {
+        let program = assembler::assemble(source, None).expect("Benchmark programs assemble");
+        let uncached = measure(&program, false, Backend::Interpreter);
+        let cached = measure(&program, true, Backend::Interpreter);
+        let recompiled = measure(&program, true, Backend::Recompiler);
+        println!("{:<16} {:>16.0} {:>16.0} {:>7.2}x {:>16.0} {:>7.2}x",
+                 name, uncached, cached, cached / uncached, recompiled, recompiled / uncached);
+    }
+}
@@ -0,0 +1,613 @@
+// ----- Modules ----- //
+
+mod expression;
+mod source;
+
+// ----- Imports ----- //
+
+use std::collections::HashMap;
+use std::fs;
+
+use crate::cpu::extensions::Extensions;
+use crate::cpu::instructions;
+use crate::cpu::instructions::{Definition, Field};
+use crate::debug::{Check, Condition, DebugInfo, Location};
+use crate::platform::Platform;
+
+// ----- Consts ----- //
+
+/// Programs are loaded at this address.
+const ORIGIN: usize = 0x200;
+
+/// How deep constants can refer to other constants, to catch those that refer
+/// to themselves.
+const MAX_DEPTH: usize = 32;
+
+// ----- Structs ----- //
+
+/// An assembled program, along with what debuggers need to know about it.
+pub struct Program {
+    /// The program, ready to be loaded at 0x200.
+    pub data: Vec<u8>,
+    /// Labels, source lines, and assertions and breakpoints from the
+    /// `:assert` and `:breakpoint` directives.
+    pub debug: DebugInfo,
+}
+
+/// A single line of source, once parsed.
+struct Line<'a> {
+    location: &'a Location,
+    label: Option<&'a str>,
+    statement: Option<Statement<'a>>,
+}
+
+enum Statement<'a> {
+    Instruction { definition: &'a Definition, operands: Vec<&'a str> },
+    /// Raw data, from `BYTE(..)` or `INVALID(..)`.
+    Data(Vec<u8>),
+    /// Expressions, stored in `width` bytes each, from `:byte` or `:word`.
+    Values { width: usize, values: Vec<&'a str> },
+    /// Padding up to the next multiple of the given size, from `:align`.
+    Align(usize),
+    /// A named expression, from `:const`.
+    Const { name: &'a str, value: &'a str },
+    /// Another name for a variable register, from `:alias`.
+    Alias { name: &'a str, register: u16 },
+    /// A debugging annotation for the next instruction.
+    Check(Check),
+}
+
+/// What the names in operands refer to.
+#[derive(Default)]
+struct Symbols<'a> {
+    labels: HashMap<&'a str, usize>,
+    constants: HashMap<&'a str, &'a str>,  // Expressions, evaluated where used
+    aliases: HashMap<&'a str, u16>,
+}
+
+impl Statement<'_> {
+    /// Get the size of the statement, once assembled at `address`.
+    fn size(&self, address: usize) -> usize {
+        match self {
+            Statement::Instruction { .. } => { 2 }
+            Statement::Data(data) => { data.len() }
+            Statement::Values { width, values } => { width * values.len() }
+            Statement::Align(size) => { (size - address % size) % size }
+            Statement::Const { .. } | Statement::Alias { .. } | Statement::Check(_) => { 0 }
+        }
+    }
+}
+
+impl<'a> Symbols<'a> {
+    /// Check that `name` isn't taken yet.
+    fn check(&self, name: &str) -> Result<(), String> {
+        if self.labels.contains_key(name) || self.constants.contains_key(name) || self.aliases.contains_key(name) {
+            return Err(format!("'{}' is defined twice", name));
+        }
+        return Ok(());
+    }
+
+    /// Get the value of the label or constant `name`, if there is one.
+    fn value(&self, name: &str, depth: usize) -> Result<Option<i64>, String> {
+        if let Some(&address) = self.labels.get(name) {
+            return Ok(Some(address as i64));
+        }
+        let Some(value) = self.constants.get(name) else {
+            return Ok(None);
+        };
+        if depth == MAX_DEPTH {
+            return Err(format!("Constant '{}' refers to itself", name));
+        }
+        return expression::evaluate(value, &|name| self.value(name, depth + 1)).map(Some);
+    }
+
+    fn evaluate(&self, text: &str) -> Result<i64, String> {
+        return expression::evaluate(text, &|name| self.value(name, 0));
+    }
+
+    /// Get the variable register `text` refers to, by name or by alias.
+    fn register(&self, text: &str) -> Option<u16> {
+        return self.aliases.get(text).copied().or_else(|| Field::X.parse(text));
+    }
+}
+
+// ----- Functions ----- //
+
+/// Assemble the program in `source`, in the syntax the disassembler writes:
+/// one instruction per line, as in `SETI(v0, 2a)`, with numbers in hexadecimal.
+/// Lines may start with a `label:`, and anything after a `;` is a comment.
+/// `BYTE(..)` inserts raw bytes, and `INVALID(..)` a raw 16-bit word.
+/// Instructions that don't exist on the `platform` are rejected, if one is
+/// given.
+///
+/// Operands other than registers are expressions: numbers, in hexadecimal or
+/// in binary with a `%` prefix, labels and constants, combined with
+/// `+ - * / % & | ^ << >> ~` and parentheses, as in `SETI(v0, (ff - size) & f0)`.
+/// Directives add to that:
+///
+/// - `:const NAME EXPRESSION` names a value, and `:alias NAME vX` a register.
+/// - `:byte` and `:word`, followed by expressions, insert them as 8-bit or
+///   big-endian 16-bit values, and `:align N` pads the program with zeros up
+///   to the next multiple of `N` bytes.
+/// - `:macro NAME PARAMETERS..` starts a macro, which runs until `:end`. It is
+///   used like an instruction, as in `NAME(v1, 10)`, its parameters being
+///   replaced with the arguments. Labels defined in its body are local to each
+///   use: the nth macro use names `loop` as `loop_n`. Macros can't include
+///   files or define other macros.
+/// - `:include "FILE"` inserts another source file, relative to the one
+///   including it.
+/// - `:assert` and a condition, as in `:assert v6 < 64`, or `:breakpoint`
+///   annotate the next instruction for debuggers. They don't change the
+///   program, and are only written to its debug info.
+///
+/// returns The program, ready to be loaded at 0x200.
+pub fn assemble(source: &str, platform: Option<Platform>) -> Result<Vec<u8>, String> {
+    return assemble_with(source, platform, &Extensions::new());
+}
+
+/// Assemble the program in `source`, as `assemble` does, also accepting the
+/// custom instructions in `extensions`.
+pub fn assemble_with(source: &str, platform: Option<Platform>, extensions: &Extensions) -> Result<Vec<u8>, String> {
+    return assemble_program(source, "", platform, extensions).map(|program| program.data);
+}
+
+/// Assemble the program in `source`, as `assemble_with` does, keeping its
+/// debug info. Source lines are given as lines of `file`, which files it
+/// includes are relative to.
+pub fn assemble_program(source: &str, file: &str, platform: Option<Platform>, extensions: &Extensions)
+                        -> Result<Program, String> {
+    let source = source::expand(source, file)?;
+    let mut lines = Vec::new();
+    for line in source.iter() {
+        let (label, statement) = parse_line(&line.text, extensions)
+            .map_err(|message| format!("{}: {}", place(&line.location), message))?;
+        lines.push(Line { location: &line.location, label, statement });
+    }
+
+    // Work out every label, constant and alias before encoding, so that they
+    // can be used before they are defined.
+    let mut symbols = Symbols::default();
+    let mut address = ORIGIN;
+    for line in lines.iter() {
+        let error = |message: String| format!("{}: {}", place(line.location), message);
+        if let Some(label) = line.label {
+            symbols.check(label).map_err(error)?;
+            symbols.labels.insert(label, address);
+        }
+        match line.statement {
+            Some(Statement::Const { name, value }) => {
+                symbols.check(name).map_err(error)?;
+                symbols.constants.insert(name, value);
+            }
+            Some(Statement::Alias { name, register }) => {
+                symbols.check(name).map_err(error)?;
+                symbols.aliases.insert(name, register);
+            }
+            _ => {}
+        }
+        address += line.statement.as_ref().map_or(0, |statement| statement.size(address));
+    }
+
+    let mut result = Vec::new();
+    let mut debug = DebugInfo::new();
+    for line in lines.iter() {
+        let error = |message: String| format!("{}: {}", place(line.location), message);
+        let address = ORIGIN + result.len();
+        if let Some(label) = line.label {
+            debug.add_label(address, label);
+        }
+        if matches!(line.statement, Some(Statement::Instruction { .. } | Statement::Data(_) | Statement::Values { .. })) {
+            debug.add_line(address, line.location.clone());
+        }
+
+        match &line.statement {
+            Some(Statement::Instruction { definition, operands }) => {
+                if let Some(platform) = platform {
+                    if !definition.is_available(platform) {
+                        return Err(error(format!("{} isn't available on {}", definition.name, platform)));
+                    }
+                }
+                let opcode = encode(definition, operands, &symbols).map_err(error)?;
+                result.extend_from_slice(&opcode.to_be_bytes());
+            }
+            Some(Statement::Data(data)) => { result.extend_from_slice(data); }
+            Some(Statement::Values { width, values }) => {
+                for text in values.iter() {
+                    let value = symbols.evaluate(text).map_err(error)?;
+                    let value = fit(value, u16::MAX >> (8 * (2 - width)))
+                        .ok_or_else(|| error(format!("'{}' doesn't fit in {} bytes", text, width)))?;
+                    result.extend_from_slice(&value.to_be_bytes()[2 - width..]);
+                }
+            }
+            Some(statement @ Statement::Align(_)) => { result.resize(result.len() + statement.size(address), 0); }
+            Some(Statement::Check(check)) => { debug.add(address, check.clone()); }
+            Some(Statement::Const { .. } | Statement::Alias { .. }) | None => {}
+        }
+    }
+
+    return Ok(Program { data: result, debug });
+}
+
+/// Describe where a line of source is, for error messages.
+fn place(location: &Location) -> String {
+    if location.file.is_empty() {
+        return format!("Line {}", location.line);
+    }
+    return location.to_string();
+}
+
+fn is_name(text: &str) -> bool {
+    return !text.is_empty() && text.chars().all(|c| c.is_alphanumeric() || c == '_');
+}
+
+/// Split the `label:` at the start of the line, if it has one, from the rest.
+fn split_label(text: &str) -> Result<(Option<&str>, &str), String> {
+    if text.starts_with(':') {
+        return Ok((None, text));
+    }
+    let Some((name, rest)) = text.split_once(':') else {
+        return Ok((None, text));
+    };
+
+    let name = name.trim();
+    if !is_name(name) {
+        return Err(format!("Invalid label '{}'", name));
+    }
+    return Ok((Some(name), rest.trim()));
+}
+
+/// Split a statement into its name and operands. Operands may be given in
+/// parentheses, as the disassembler writes them, or simply after the name.
+fn split_statement(text: &str) -> Result<(&str, Vec<&str>), String> {
+    let (name, operands) = match text.find(['(', ' ', '\t']) {
+        Some(index) if text[index..].starts_with('(') => {
+            (&text[..index], text[index + 1..].strip_suffix(')').ok_or("Missing ')'")?)
+        }
+        Some(index) => { (&text[..index], &text[index..]) }
+        None => { (text, "") }
+    };
+    let operands = if operands.trim().is_empty() {
+        Vec::new()
+    } else {
+        operands.split(',').map(str::trim).collect()
+    };
+    return Ok((name, operands));
+}
+
+/// Fit `value` in a field holding up to `max`, negative values included, as
+/// two's complement.
+fn fit(value: i64, max: u16) -> Option<u16> {
+    let max = max as i64;
+    if value > max || value < -(max + 1) {
+        return None;
+    }
+    return Some((value & max) as u16);
+}
+
+fn parse_line<'a>(text: &'a str, extensions: &'a Extensions) -> Result<(Option<&'a str>, Option<Statement<'a>>), String> {
+    let text = text.split(';').next().unwrap_or("").trim();
+    let (label, text) = split_label(text)?;
+    if text.is_empty() {
+        return Ok((label, None));
+    }
+    if let Some(directive) = text.strip_prefix(':') {
+        return Ok((label, Some(parse_directive(directive)?)));
+    }
+
+    let (name, operands) = split_statement(text)?;
+    let name = name.to_uppercase();
+    let statement = match name.as_str() {
+        "BYTE" => {
+            let data = operands.iter()
+                .map(|operand| Field::Byte.parse(operand).map(|value| value as u8))
+                .collect::<Option<Vec<u8>>>()
+                .ok_or("Invalid byte")?;
+            Statement::Data(data)
+        }
+        "INVALID" => {
+            let word = match operands.as_slice() {
+                [word] => { u16::from_str_radix(word.trim_start_matches("0x"), 16).ok() }
+                _ => { None }
+            };
+            Statement::Data(word.ok_or("Invalid word")?.to_be_bytes().to_vec())
+        }
+        _ => {
+            let definition = instructions::find(&name)
+                .or_else(|| extensions.find(&name).map(|extension| extension.definition()))
+                .ok_or(format!("Unknown instruction '{}'", name))?;
+            if operands.len() != definition.fields.len() {
+                return Err(format!("{} takes {} operands", definition.name, definition.fields.len()));
+            }
+            Statement::Instruction { definition, operands }
+        }
+    };
+
+    return Ok((label, Some(statement)));
+}
+
+/// Parse the `directive`, given without its leading `:`.
+fn parse_directive(directive: &str) -> Result<Statement<'_>, String> {
+    let (name, rest) = directive.split_once(char::is_whitespace).unwrap_or((directive, ""));
+    let rest = rest.trim();
+    let statement = match name {
+        "assert" => {
+            Statement::Check(Check::Assert(Condition::parse(rest).ok_or(format!("Invalid condition '{}'", rest))?))
+        }
+        "breakpoint" if rest.is_empty() => { Statement::Check(Check::Break) }
+        "const" | "alias" => {
+            let (symbol, value) = rest.split_once(char::is_whitespace).ok_or(format!("Invalid :{}", name))?;
+            if !is_name(symbol) {
+                return Err(format!("Invalid name '{}'", symbol));
+            }
+            let value = value.trim();
+            if name == "const" {
+                Statement::Const { name: symbol, value }
+            } else {
+                let register = Field::X.parse(value).ok_or(format!("Invalid register '{}'", value))?;
+                Statement::Alias { name: symbol, register }
+            }
+        }
+        "byte" | "word" if !rest.is_empty() => {
+            let width = if name == "byte" { 1 } else { 2 };
+            Statement::Values { width, values: rest.split(',').map(str::trim).collect() }
+        }
+        "align" => {
+            let size = usize::from_str_radix(rest.trim_start_matches("0x"), 16).ok()
+                .filter(|&size| size > 0)
+                .ok_or(format!("Invalid alignment '{}'", rest))?;
+            Statement::Align(size)
+        }
+        _ => { return Err(format!("Unknown directive ':{}'", directive)); }
+    };
+    return Ok(statement);
+}
+
+/// Encode the instruction, evaluating its operands with the `symbols`.
+fn encode(definition: &Definition, operands: &[&str], symbols: &Symbols) -> Result<u16, String> {
+    let mut values = Vec::new();
+    for (field, operand) in definition.fields.iter().zip(operands) {
+        let value = match field {
+            Field::X | Field::Y => { symbols.register(operand) }
+            _ => { fit(symbols.evaluate(operand)?, field.max()) }
+        };
+        values.push(value.ok_or(format!("Invalid operand '{}' for {}", operand, definition.name))?);
+    }
+    return Ok(definition.encode(&values));
+}
+
+/// Entry point of the `asm` tool, given the arguments after its name: the
+/// source file, the file to write the program to, and optionally
+/// `--platform NAME`. Debug info, if any, is written next to the program, with
+/// `.debug` added to its name.
+///
+/// returns The exit code.
+pub fn command(args: &[String]) -> i32 {
+    let (paths, platform) = match parse_args(args) {
+        Ok(parsed) => { parsed }
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    };
+    let [source_path, output_path] = paths.as_slice() else {
+        eprintln!("Usage: asm SOURCE OUTPUT [--platform NAME]");
+        return 1;
+    };
+
+    let source = match fs::read_to_string(source_path) {
+        Ok(source) => { source }
+        Err(error) => {
+            eprintln!("Failed to read {}: {}", source_path, error);
+            return 1;
+        }
+    };
+    let program = match assemble_program(&source, source_path, platform, &Extensions::new()) {
+        Ok(program) => { program }
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    };
+    if let Err(error) = fs::write(output_path, program.data) {
+        eprintln!("Failed to write {}: {}", output_path, error);
+        return 1;
+    }
+
+    // The debug info goes next to the program, for `--debug`.
+    if !program.debug.is_empty() {
+        let debug_path = format!("{}.debug", output_path);
+        if let Err(error) = fs::write(&debug_path, program.debug.format()) {
+            eprintln!("Failed to write {}: {}", debug_path, error);
+            return 1;
+        }
+    }
+    return 0;
+}
+
+/// Split the arguments of a tool into its paths and the `--platform` option.
+pub fn parse_args(args: &[String]) -> Result<(Vec<&String>, Option<Platform>), String> {
+    let mut paths = Vec::new();
+    let mut platform = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--platform" {
+            let name = args.next().ok_or("Missing value for --platform")?;
+            platform = Some(Platform::from_name(name).ok_or(format!("Unknown platform '{}'", name))?);
+        } else {
+            paths.push(arg);
+        }
+    }
+    return Ok((paths, platform));
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use crate::cpu::instructions::Instruction;
+    use crate::disassembler::{disassemble, disassemble_with};
+
+    #[test]
+    fn disassembled_roms_assemble_back() {
+        let directory = concat!(env!("CARGO_MANIFEST_DIR"), "/test_roms");
+        for entry in fs::read_dir(directory).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|extension| extension == "ch8") {
+                let rom = fs::read(&path).unwrap();
+                let source = disassemble(&rom, None);
+                assert_eq!(assemble(&source, None).unwrap(), rom, "{}", path.display());
+            }
+        }
+    }
+
+    #[test]
+    fn labels_resolve_to_addresses() {
+        let source = "start: CLS()\n  JUMP(end)\nend: JUMP end ; halt\n";
+        assert_eq!(assemble(source, None).unwrap(), vec![0x00, 0xE0, 0x12, 0x04, 0x12, 0x04]);
+    }
+
+    #[test]
+    fn unavailable_instructions_are_rejected() {
+        assert!(assemble("SYS(123)", Some(Platform::SuperChip)).is_err());
+        assert!(assemble("SYS(123)", Some(Platform::CosmacVip)).is_ok());
+    }
+
+    #[test]
+    fn extensions_assemble_and_disassemble() {
+        let mut extensions = Extensions::new();
+        assert!(extensions.register("NOP", 0x8000, 0xF00F, &[], |_, _| Ok(())).is_err());
+        assert!(extensions.register("CLS", 0x9001, 0xF00F, &[], |_, _| Ok(())).is_err());
+        extensions.register("SWAP", 0x9001, 0xF00F, &[Field::X, Field::Y], |_, _| Ok(())).unwrap();
+        assert!(extensions.register("SWAP2", 0x9101, 0xFF0F, &[Field::Y], |_, _| Ok(())).is_err());
+
+        let program = assemble_with("SWAP(v3, va)", None, &extensions).unwrap();
+        assert_eq!(program, vec![0x93, 0xA1]);
+        let source = disassemble_with(&program, None, &extensions, &DebugInfo::new());
+        assert_eq!(assemble_with(&source, None, &extensions).unwrap(), program);
+        assert!(assemble("SWAP(v3, va)", None).is_err());
+    }
+
+    #[test]
+    fn debug_directives_leave_the_program_alone() {
+        let source = "SETI(v6, 00)\nloop: ADDI(v6, 10)\n:assert v6 < 60\n:breakpoint\nJUMP(loop)\n";
+        let program = assemble_program(source, "test.asm", None, &Extensions::new()).unwrap();
+        assert_eq!(program.data, vec![0x66, 0x00, 0x76, 0x10, 0x12, 0x02]);
+        assert_eq!(program.debug.checks(0x204).len(), 2);
+        assert_eq!(program.debug.locate(0x204), "204 loop+2 (test.asm:5)");
+        assert!(assemble(":assert v6 <", None).is_err());
+    }
+
+    #[test]
+    fn labels_survive_disassembly() {
+        let source = "start: CLS()\nloop: CALL(sub)\n  JUMP(loop)\nsub: RET()\n";
+        let program = assemble_program(source, "test.asm", None, &Extensions::new()).unwrap();
+        let disassembled = disassemble_with(&program.data, None, &Extensions::new(), &program.debug);
+        assert!(disassembled.contains("loop:\n    CALL(sub)"));
+        assert!(disassembled.contains("JUMP(loop)           ; 204: 1202 test.asm:3"));
+        assert_eq!(assemble(&disassembled, None).unwrap(), program.data);
+    }
+
+    #[test]
+    fn constants_aliases_and_macros_expand() {
+        let source = "\
+            :const WIDTH 8\n\
+            :const ROWS height / 2\n\
+            :const height 4 * 2\n\
+            :alias score v6\n\
+            :macro draw x, y\n\
+              SETI(va, x)\n\
+              SETI(vb, y)\n\
+              DRAW(va, vb, ROWS)\n\
+            :end\n\
+            start: SETI(score, WIDTH - 1)\n\
+            draw 3, (WIDTH + 1) * 2 ; macros are used like instructions\n\
+            again: draw(-1, %101)\n\
+            JUMP(again)\n\
+            sprite: :byte %11110000, 0x90, -1\n\
+            :align 4\n\
+            :word sprite, sprite >> 4\n";
+        assert_eq!(assemble(source, None).unwrap(), vec![
+            0x66, 0x07,
+            0x6A, 0x03, 0x6B, 0x12, 0xDA, 0xB4,
+            0x6A, 0xFF, 0x6B, 0x05, 0xDA, 0xB4,
+            0x12, 0x08,
+            0xF0, 0x90, 0xFF, 0x00,
+            0x02, 0x10, 0x00, 0x21,
+        ]);
+
+        assert!(assemble(":const a a + 1\nSETI(v0, a)", None).is_err());
+        assert!(assemble(":const x 1\nx: CLS()", None).is_err());
+        assert!(assemble(":byte 100", None).is_err());
+        assert!(assemble(":macro m\nCLS()", None).is_err());
+        assert!(assemble(":macro m\nm()\n:end\nm()", None).is_err());
+    }
+
+    #[test]
+    fn macro_labels_are_local_to_each_use() {
+        let source = "\
+            :macro wait t\n\
+              SETI(v0, t)\n\
+              loop: ADDI(v0, -1)\n\
+              SNE(v0, 0)\n\
+              JUMP(loop)\n\
+            :end\n\
+            wait 2\n\
+            wait 3\n";
+        let program = assemble_program(source, "test.asm", None, &Extensions::new()).unwrap();
+        assert_eq!(program.data, vec![
+            0x60, 0x02, 0x70, 0xFF, 0x40, 0x00, 0x12, 0x02,
+            0x60, 0x03, 0x70, 0xFF, 0x40, 0x00, 0x12, 0x0A,
+        ]);
+        assert_eq!(program.debug.address_of("loop_2"), Some(0x20A));
+    }
+
+    #[test]
+    fn macros_cant_include_files_or_define_macros() {
+        let message = assemble(":macro m\n:include \"font.txt\"\n:end\nm()", None).err().unwrap();
+        assert_eq!(message, "Line 2: Macro 'm' can't include files or define macros");
+        assert!(assemble(":macro m\n:macro n\n:end\n:end", None).is_err());
+    }
+
+    #[test]
+    fn includes_are_relative_to_the_including_file() {
+        let directory = std::env::temp_dir().join(format!("chip8-asm-{}", std::process::id()));
+        fs::create_dir_all(directory.join("lib")).unwrap();
+        fs::write(directory.join("lib/print.asm"), ":include \"digits.asm\"\nprint: RET()\n").unwrap();
+        fs::write(directory.join("lib/digits.asm"), ":const ZERO 30\n").unwrap();
+        let main = directory.join("main.asm");
+        let source = "CALL(print)\nSETI(v0, ZERO)\n:include \"lib/print.asm\"\n";
+
+        let program = assemble_program(source, &main.to_string_lossy(), None, &Extensions::new()).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(program.data, vec![0x22, 0x04, 0x60, 0x30, 0x00, 0xEE]);
+        assert!(program.debug.locate(0x204).ends_with("print.asm:2)"));
+    }
+
+    #[test]
+    fn sources_assemble_to_their_roms() {
+        let directory = concat!(env!("CARGO_MANIFEST_DIR"), "/test_roms");
+        for entry in fs::read_dir(directory).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|extension| extension == "txt") {
+                let source = fs::read_to_string(&path).unwrap();
+                let program = assemble_program(&source, &path.to_string_lossy(), None, &Extensions::new()).unwrap();
+                assert_eq!(program.data, fs::read(path.with_extension("")).unwrap(), "{}", path.display());
+            }
+        }
+    }
+
+    #[test]
+    fn the_delay_timer_test_assembles_to_its_rom() {
+        let directory = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/test_roms"));
+        let source = fs::read_to_string(directory.join("delay_timer_test.ch8.txt")).unwrap();
+        let program = assemble_program(&source, "delay_timer_test.ch8.txt", None, &Extensions::new()).unwrap();
+        assert_eq!(program.data, fs::read(directory.join("delay_timer_test.ch8")).unwrap());
+        assert_eq!(program.debug.format(), fs::read_to_string(directory.join("delay_timer_test.ch8.debug")).unwrap());
+
+        assert_eq!(program.debug.describe(&Instruction::from(0x1202)).unwrap(), "JUMP(_l_start)");
+        assert_eq!(program.debug.locate(0x210), "210 _l_start+e (delay_timer_test.ch8.txt:13)");
+    }
+}
@@ -0,0 +1,146 @@
+// ----- Consts ----- //
+
+/// Binary operators, from the lowest precedence to the highest.
+const LEVELS: &[&[&str]] = &[&["|"], &["^"], &["&"], &["<<", ">>"], &["+", "-"], &["*", "/", "%"]];
+
+const OPERATORS: &[&str] = &["<<", ">>", "|", "^", "&", "+", "-", "*", "/", "%", "~"];
+
+// ----- Structs ----- //
+
+#[derive(Clone, PartialEq)]
+enum Token<'a> {
+    Number(i64),
+    /// A name, or a number in hexadecimal: which one is worked out when it is
+    /// evaluated, as names take precedence.
+    Word(&'a str),
+    Operator(&'static str),
+    Open,
+    Close,
+}
+
+/// Evaluates an expression, one token at a time.
+struct Parser<'a, 'b> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+    lookup: &'b dyn Fn(&str) -> Result<Option<i64>, String>,
+}
+
+impl<'a> Parser<'a, '_> {
+    fn peek(&self) -> Option<&Token<'a>> {
+        return self.tokens.get(self.position);
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        return token;
+    }
+
+    /// Parse the operations at precedence `level` and above.
+    fn binary(&mut self, level: usize) -> Result<i64, String> {
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+
+        let mut value = self.binary(level + 1)?;
+        while let Some(&Token::Operator(operator)) = self.peek() {
+            if !LEVELS[level].contains(&operator) {
+                break;
+            }
+            self.position += 1;
+            let right = self.binary(level + 1)?;
+            value = match operator {
+                "|" => { value | right }
+                "^" => { value ^ right }
+                "&" => { value & right }
+                "<<" => { value.checked_shl(right as u32).filter(|_| (0..64).contains(&right)).ok_or("Invalid shift")? }
+                ">>" => { value.checked_shr(right as u32).filter(|_| (0..64).contains(&right)).ok_or("Invalid shift")? }
+                "+" => { value.wrapping_add(right) }
+                "-" => { value.wrapping_sub(right) }
+                "*" => { value.wrapping_mul(right) }
+                "/" => { value.checked_div(right).ok_or("Division by zero")? }
+                _ => { value.checked_rem(right).ok_or("Division by zero")? }
+            };
+        }
+        return Ok(value);
+    }
+
+    fn unary(&mut self) -> Result<i64, String> {
+        match self.next() {
+            Some(Token::Operator("-")) => { return Ok(self.unary()?.wrapping_neg()); }
+            Some(Token::Operator("~")) => { return Ok(!self.unary()?); }
+            Some(Token::Operator("+")) => { return self.unary(); }
+            Some(Token::Operator("%")) => {
+                // Binary numbers, as in `%11110000`.
+                match self.next() {
+                    Some(Token::Word(digits)) => {
+                        return i64::from_str_radix(digits, 2).map_err(|_| format!("Invalid binary number '%{}'", digits));
+                    }
+                    _ => { return Err(String::from("Missing binary digits after '%'")); }
+                }
+            }
+            Some(Token::Open) => {
+                let value = self.binary(0)?;
+                if self.next() != Some(Token::Close) {
+                    return Err(String::from("Missing ')'"));
+                }
+                return Ok(value);
+            }
+            Some(Token::Number(value)) => { return Ok(value); }
+            Some(Token::Word(word)) => {
+                if let Some(value) = (self.lookup)(word)? {
+                    return Ok(value);
+                }
+                return i64::from_str_radix(word, 16).map_err(|_| format!("Unknown name '{}'", word));
+            }
+            _ => { return Err(String::from("Missing operand")); }
+        }
+    }
+}
+
+// ----- Functions ----- //
+
+fn tokenize(text: &str) -> Result<Vec<Token<'_>>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        let word_length = rest.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(rest.len());
+        if word_length > 0 {
+            let word = &rest[..word_length];
+            let token = match word.strip_prefix("0x") {
+                Some(hex) => {
+                    Token::Number(i64::from_str_radix(hex, 16).map_err(|_| format!("Invalid number '{}'", word))?)
+                }
+                None => { Token::Word(word) }
+            };
+            tokens.push(token);
+            rest = &rest[word_length..];
+        } else if let Some(stripped) = rest.strip_prefix('(') {
+            tokens.push(Token::Open);
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix(')') {
+            tokens.push(Token::Close);
+            rest = stripped;
+        } else {
+            let operator = OPERATORS.iter()
+                .find(|operator| rest.starts_with(*operator))
+                .ok_or(format!("Unexpected '{}'", rest.chars().next().unwrap_or_default()))?;
+            tokens.push(Token::Operator(operator));
+            rest = &rest[operator.len()..];
+        }
+        rest = rest.trim_start();
+    }
+    return Ok(tokens);
+}
+
+/// Work out the value of the expression in `text`: numbers (in hexadecimal,
+/// or in binary with a `%` prefix), names looked up with `lookup`, the usual
+/// arithmetic and bitwise operators, and parentheses.
+pub fn evaluate(text: &str, lookup: &dyn Fn(&str) -> Result<Option<i64>, String>) -> Result<i64, String> {
+    let mut parser = Parser { tokens: tokenize(text)?, position: 0, lookup };
+    let value = parser.binary(0)?;
+    if parser.position != parser.tokens.len() {
+        return Err(format!("Invalid expression '{}'", text.trim()));
+    }
+    return Ok(value);
+}
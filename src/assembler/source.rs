@@ -0,0 +1,161 @@
+// ----- Imports ----- //
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::debug::Location;
+use super::{place, split_label, split_statement};
+
+// ----- Consts ----- //
+
+/// How deep includes and macros can nest, to catch those that refer to
+/// themselves.
+const MAX_DEPTH: usize = 16;
+
+// ----- Structs ----- //
+
+/// A line of source, once includes and macros are expanded, along with where
+/// it comes from.
+pub struct SourceLine {
+    pub text: String,
+    pub location: Location,
+}
+
+struct Macro {
+    parameters: Vec<String>,
+    /// Labels the body defines, renamed in each expansion.
+    labels: Vec<String>,
+    body: Vec<String>,
+}
+
+/// Expands includes and macros, one source file at a time.
+struct Expander {
+    macros: HashMap<String, Macro>,
+    lines: Vec<SourceLine>,
+    expansions: usize,  // Macros expanded so far, to name their labels
+}
+
+impl Expander {
+    fn file(&mut self, source: &str, file: &str, depth: usize) -> Result<(), String> {
+        let mut lines = source.lines().enumerate();
+        while let Some((index, text)) = lines.next() {
+            let location = Location { file: file.to_string(), line: index + 1 };
+            let error = |message: String| format!("{}: {}", place(&location), message);
+            let code = text.split(';').next().unwrap_or("").trim();
+
+            match code.split_once(char::is_whitespace).unwrap_or((code, "")) {
+                (":include", name) => {
+                    if depth == MAX_DEPTH {
+                        return Err(error(String::from("Includes are nested too deep")));
+                    }
+                    let name = name.trim().trim_matches('"');
+                    let path = Path::new(file).parent().unwrap_or(Path::new("")).join(name);
+                    let included = fs::read_to_string(&path)
+                        .map_err(|reason| error(format!("Failed to read {}: {}", path.display(), reason)))?;
+                    self.file(&included, &path.to_string_lossy(), depth + 1)?;
+                }
+                (":macro", signature) => {
+                    let mut words = signature.split(|c: char| c.is_whitespace() || c == ',').filter(|word| !word.is_empty());
+                    let name = words.next().ok_or_else(|| error(String::from("Missing macro name")))?;
+                    let parameters = words.map(str::to_string).collect();
+
+                    let mut labels = Vec::new();
+                    let mut body = Vec::new();
+                    loop {
+                        let Some((index, text)) = lines.next() else {
+                            return Err(error(format!("Macro '{}' has no ':end'", name)));
+                        };
+                        let location = Location { file: file.to_string(), line: index + 1 };
+                        let error = |message: String| format!("{}: {}", place(&location), message);
+                        let code = text.split(';').next().unwrap_or("").trim();
+                        if code == ":end" {
+                            break;
+                        }
+                        if code.starts_with(":include") || code.starts_with(":macro") {
+                            return Err(error(format!("Macro '{}' can't include files or define macros", name)));
+                        }
+                        if let (Some(label), _) = split_label(code).map_err(error)? {
+                            labels.push(label.to_string());
+                        }
+                        body.push(code.to_string());
+                    }
+                    if self.macros.insert(name.to_string(), Macro { parameters, labels, body }).is_some() {
+                        return Err(error(format!("Macro '{}' is defined twice", name)));
+                    }
+                }
+                (":end", _) => { return Err(error(String::from("':end' outside of a macro"))); }
+                _ => { self.line(code, &location, 0).map_err(error)?; }
+            }
+        }
+        return Ok(());
+    }
+
+    /// Add the line, expanding it if it invokes a macro. Lines of the macro
+    /// are given the `location` of the invocation.
+    fn line(&mut self, text: &str, location: &Location, depth: usize) -> Result<(), String> {
+        let (label, statement) = split_label(text)?;
+        let (name, arguments) = split_statement(statement)?;
+        let Some(definition) = self.macros.get(name) else {
+            self.lines.push(SourceLine { text: text.to_string(), location: location.clone() });
+            return Ok(());
+        };
+
+        if depth == MAX_DEPTH {
+            return Err(format!("Macros are nested too deep in '{}'", name));
+        }
+        if arguments.len() != definition.parameters.len() {
+            return Err(format!("Macro '{}' takes {} arguments", name, definition.parameters.len()));
+        }
+        if let Some(label) = label {
+            self.lines.push(SourceLine { text: format!("{}:", label), location: location.clone() });
+        }
+
+        // Labels of the body get the number of the expansion, so that each
+        // expansion has its own.
+        self.expansions += 1;
+        let names: Vec<String> = definition.parameters.iter().chain(definition.labels.iter()).cloned().collect();
+        let labels: Vec<String> = definition.labels.iter()
+            .map(|label| format!("{}_{}", label, self.expansions))
+            .collect();
+        let values: Vec<&str> = arguments.iter().copied().chain(labels.iter().map(String::as_str)).collect();
+        let body: Vec<String> = definition.body.iter()
+            .map(|line| substitute(line, &names, &values))
+            .collect();
+        for line in body.iter() {
+            self.line(line, location, depth + 1)?;
+        }
+        return Ok(());
+    }
+}
+
+// ----- Functions ----- //
+
+/// Replace the whole words of `text` that are `parameters` with the matching
+/// `arguments`.
+fn substitute(text: &str, parameters: &[String], arguments: &[&str]) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let length = match rest.find(|c: char| !c.is_alphanumeric() && c != '_') {
+            Some(0) => { rest.chars().next().map_or(1, char::len_utf8) }
+            Some(length) => { length }
+            None => { rest.len() }
+        };
+        let word = &rest[..length];
+        match parameters.iter().position(|parameter| parameter == word) {
+            Some(index) => { result += arguments[index]; }
+            None => { result += word; }
+        }
+        rest = &rest[word.len()..];
+    }
+    return result;
+}
+
+/// Read the program in `source`, given as lines of `file`, expanding its
+/// `:include` directives and the macros it defines.
+pub fn expand(source: &str, file: &str) -> Result<Vec<SourceLine>, String> {
+    let mut expander = Expander { macros: HashMap::new(), lines: Vec::new(), expansions: 0 };
+    expander.file(source, file, 0)?;
+    return Ok(expander.lines);
+}
@@ -0,0 +1,345 @@
+// ----- Imports ----- //
+
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use sha1_smol::Sha1;
+
+use crate::config::Config;
+use crate::cpu::Status;
+use crate::headless;
+use crate::input::InputScript;
+use crate::machine::Machine;
+use crate::rom::RomImage;
+
+// ----- Consts ----- //
+
+/// Frames each ROM runs for, when neither the manifest nor `--frames` say.
+const DEFAULT_FRAMES: u64 = 600;
+
+/// Optional file in the directory listing the ROMs to run, and how.
+const MANIFEST: &str = "batch.txt";
+
+// ----- Structs ----- //
+
+/// A ROM to run, as listed in the manifest.
+///
+/// Manifests list one ROM per line, relative to the directory, followed by
+/// any of `frames=N`, `input=FILE` (an input script, also relative to the
+/// directory) and `expect=SHA1` (the SHA-1 of the display dump once the run
+/// is over). Empty lines and lines starting with `#` are ignored.
+///
+/// ```text
+/// ibm.ch8 frames=60 expect=5c6d1c0e...
+/// test_keyboard.ch8 frames=300 input=test_keyboard.input
+/// ```
+struct Entry {
+    rom: String,
+    frames: Option<u64>,
+    input: Option<String>,
+    expect: Option<String>,
+}
+
+/// A ROM ready to run on a worker thread.
+struct Job {
+    index: usize,
+    machine: Machine,
+    frames: u64,
+    input: Option<InputScript>,
+    expect: Option<String>,
+}
+
+/// What came out of running a ROM.
+struct Outcome {
+    name: String,
+    passed: bool,
+    /// How the run ended: halted, still running, or the error.
+    status: String,
+    frames: u64,
+    /// SHA-1 of the display dump once the run is over.
+    display: Option<String>,
+    time: Duration,
+    /// Output of the debug console, if it was on.
+    console: String,
+}
+
+// ----- Functions ----- //
+
+/// Read the manifest in `directory`, or list every `.ch8` file there when it
+/// has none.
+fn entries(directory: &Path) -> Result<Vec<Entry>, String> {
+    let manifest = directory.join(MANIFEST);
+    if !manifest.exists() {
+        let listing = fs::read_dir(directory)
+            .map_err(|error| format!("Failed to read {}: {}", directory.display(), error))?;
+        let mut roms: Vec<String> = listing
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| name.ends_with(".ch8"))
+            .collect();
+        roms.sort();
+        return Ok(roms.into_iter().map(|rom| Entry { rom, frames: None, input: None, expect: None }).collect());
+    }
+
+    let text = fs::read_to_string(&manifest)
+        .map_err(|error| format!("Failed to read {}: {}", manifest.display(), error))?;
+    let mut result = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let error = || format!("Invalid manifest entry on line {}: '{}'", number + 1, line);
+        let mut words = line.split_whitespace();
+        let mut entry = Entry {
+            rom: words.next().ok_or_else(error)?.to_string(),
+            frames: None,
+            input: None,
+            expect: None,
+        };
+        for word in words {
+            match word.split_once('=').ok_or_else(error)? {
+                ("frames", value) => { entry.frames = Some(value.parse().map_err(|_| error())?); }
+                ("input", value) => { entry.input = Some(value.to_string()); }
+                ("expect", value) => { entry.expect = Some(value.to_lowercase()); }
+                _ => { return Err(error()); }
+            }
+        }
+        result.push(entry);
+    }
+    return Ok(result);
+}
+
+/// Load the ROM and input script of the `entry`, and set up a machine for it.
+fn prepare(index: usize, entry: &Entry, directory: &Path, config: &Config) -> Result<Job, String> {
+    let path = directory.join(&entry.rom);
+    let rom = RomImage::load(&path.to_string_lossy())
+        .map_err(|error| format!("Failed to read {}: {}", path.display(), error))?;
+    let mut machine = Machine::new(&rom, &config.settings(&rom));
+    machine.set_backend(config.backend);
+    if let Some(code) = config.load_rng_code()? {
+        machine.load_rng_code(&code);
+    }
+
+    let input = match &entry.input {
+        Some(input) => { Some(InputScript::load(&directory.join(input).to_string_lossy())?) }
+        None => { None }
+    };
+    return Ok(Job {
+        index,
+        machine,
+        frames: entry.frames.or(config.frames).unwrap_or(DEFAULT_FRAMES),
+        input,
+        expect: entry.expect.clone(),
+    });
+}
+
+/// Run the `job` to the end, checking the display against what's expected.
+fn run(mut job: Job, name: String) -> Outcome {
+    let start = Instant::now();
+    let mut console = String::new();
+    let result = headless::run(&mut job.machine, Some(job.frames), job.input.as_ref(), &mut |output| console += output);
+    let time = start.elapsed();
+
+    let display = Sha1::from(job.machine.display().dump()).digest().to_string();
+    let (mut status, mut passed) = match result {
+        Ok(Status::Halted) => { (String::from("halted"), true) }
+        Ok(_) => { (String::from("running"), true) }
+        Err(error) => { (error.to_string(), false) }
+    };
+    if job.expect.as_ref().is_some_and(|expect| *expect != display) {
+        status += ", display differs";
+        passed = false;
+    }
+
+    return Outcome { name, passed, status, frames: job.frames, display: Some(display), time, console };
+}
+
+/// Entry point of the `batch` tool, given the arguments after its name: the
+/// usual options, `--jobs N` for the amount of threads, and the directory of
+/// ROMs to run.
+///
+/// returns The exit code: 0 when every ROM passed, 1 otherwise.
+pub fn command(args: &[String]) -> i32 {
+    let mut jobs = thread::available_parallelism().map_or(1, |count| count.get());
+    let mut options = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--jobs" {
+            match args.next().and_then(|value| value.parse::<usize>().ok()).filter(|&value| value > 0) {
+                Some(value) => { jobs = value; }
+                None => {
+                    eprintln!("Invalid value for --jobs");
+                    return 1;
+                }
+            }
+        } else {
+            options.push(arg.clone());
+        }
+    }
+
+    let config = match Config::from_args(&options) {
+        Ok(config) => { config }
+        Err(_) if options.is_empty() => {
+            eprintln!("Usage: batch [OPTIONS] [--jobs N] DIRECTORY");
+            return 1;
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    };
+    let directory = Path::new(&config.rom_path);
+    let entries = match entries(directory) {
+        Ok(entries) => { entries }
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    };
+
+    // Machines are set up here and sent to the workers as they pick them up.
+    let mut outcomes: Vec<Option<Outcome>> = entries.iter().map(|_| None).collect();
+    let mut queue = Vec::new();
+    for (index, entry) in entries.iter().enumerate() {
+        match prepare(index, entry, directory, &config) {
+            Ok(job) => { queue.push(job); }
+            Err(message) => {
+                outcomes[index] = Some(Outcome {
+                    name: entry.rom.clone(),
+                    passed: false,
+                    status: message,
+                    frames: 0,
+                    display: None,
+                    time: Duration::ZERO,
+                    console: String::new(),
+                });
+            }
+        }
+    }
+    queue.reverse();
+
+    let start = Instant::now();
+    let queue = Mutex::new(queue);
+    let outcomes = Mutex::new(outcomes);
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| {
+                loop {
+                    let Some(job) = queue.lock().unwrap().pop() else {
+                        break;
+                    };
+                    let index = job.index;
+                    let outcome = run(job, entries[index].rom.clone());
+                    outcomes.lock().unwrap()[index] = Some(outcome);
+                }
+            });
+        }
+    });
+    let time = start.elapsed();
+
+    let outcomes: Vec<Outcome> = outcomes.into_inner().unwrap().into_iter().flatten().collect();
+    let passed = outcomes.iter().filter(|outcome| outcome.passed).count();
+    for outcome in outcomes.iter() {
+        println!("{} {:<24} {:>6} frames  {:<40}  {:>9.3}s  {}",
+                 if outcome.passed { "PASS" } else { "FAIL" },
+                 outcome.name,
+                 outcome.frames,
+                 outcome.display.as_deref().unwrap_or("-"),
+                 outcome.time.as_secs_f64(),
+                 outcome.status);
+        for line in outcome.console.lines() {
+            println!("    {}", line);
+        }
+    }
+    println!("{} passed, {} failed, in {:.3}s on {} threads",
+             passed, outcomes.len() - passed, time.as_secs_f64(), jobs);
+
+    return if passed == outcomes.len() { 0 } else { 1 };
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Make a fresh directory for the test `name`, holding the given `files`.
+    fn directory(name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("chip8-batch-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        for (file, data) in files {
+            fs::write(directory.join(file), data).unwrap();
+        }
+        return directory;
+    }
+
+    fn manifest(text: &str) -> Result<Vec<Entry>, String> {
+        let directory = directory("manifest", &[(MANIFEST, text.as_bytes())]);
+        let result = entries(&directory);
+        fs::remove_dir_all(&directory).unwrap();
+        return result;
+    }
+
+    #[test]
+    fn machines_can_move_to_workers() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Machine>();
+        assert_send::<Job>();
+    }
+
+    #[test]
+    fn manifests_list_roms_and_how_to_run_them() {
+        let entries = manifest("# Smoke tests\n\nibm.ch8 frames=60 expect=5C6D\n  keys.ch8 input=keys.input\n").unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].rom, "ibm.ch8");
+        assert_eq!(entries[0].frames, Some(60));
+        assert_eq!(entries[0].expect.as_deref(), Some("5c6d"));
+        assert_eq!(entries[1].rom, "keys.ch8");
+        assert_eq!(entries[1].input.as_deref(), Some("keys.input"));
+        assert!(entries[1].frames.is_none() && entries[1].expect.is_none());
+    }
+
+    #[test]
+    fn invalid_manifest_entries_are_reported_with_their_line() {
+        for text in ["\nibm.ch8 frames=many", "\nibm.ch8 speed=2", "\nibm.ch8 60"] {
+            assert!(manifest(text).err().is_some_and(|message| message.starts_with("Invalid manifest entry on line 2")));
+        }
+    }
+
+    #[test]
+    fn directories_without_a_manifest_run_every_rom() {
+        let directory = directory("listing", &[("b.ch8", &[]), ("a.ch8", &[]), ("a.ch8.txt", &[])]);
+        let entries = entries(&directory);
+        fs::remove_dir_all(&directory).unwrap();
+        let roms: Vec<String> = entries.unwrap().into_iter().map(|entry| entry.rom).collect();
+        assert_eq!(roms, vec!["a.ch8", "b.ch8"]);
+    }
+
+    #[test]
+    fn displays_are_checked_against_the_manifest() {
+        // Draw the font's 0, and wait there.
+        let rom: &[u8] = &[0xA0, 0x50, 0xD0, 0x05, 0x12, 0x04];
+        let directory = directory("expect", &[("zero.ch8", rom)]);
+        let config = Config::from_args(&[directory.to_string_lossy().to_string()]).unwrap();
+        let mut entry = Entry { rom: String::from("zero.ch8"), frames: Some(2), input: None, expect: None };
+
+        let outcome = run(prepare(0, &entry, &directory, &config).unwrap(), entry.rom.clone());
+        assert!(outcome.passed);
+
+        entry.expect = outcome.display;
+        let outcome = run(prepare(0, &entry, &directory, &config).unwrap(), entry.rom.clone());
+        assert!(outcome.passed);
+
+        entry.expect = Some(String::from("0000000000000000000000000000000000000000"));
+        let outcome = run(prepare(0, &entry, &directory, &config).unwrap(), entry.rom.clone());
+        fs::remove_dir_all(&directory).unwrap();
+        assert!(!outcome.passed);
+        assert!(outcome.status.ends_with(", display differs"));
+    }
+}
@@ -0,0 +1,548 @@
+// ----- Imports ----- //
+
+use crate::memory::address::Address;
+use crate::memory::Memory;
+
+// ----- Consts ----- //
+
+const REGISTER_COUNT: usize = 0x10;
+
+/// Machine cycles taken by most instructions.
+const SHORT_CYCLES: u32 = 2;
+/// Machine cycles taken by long branches and skips.
+const LONG_CYCLES: u32 = 3;
+
+// ----- Structs ----- //
+
+/// Everything the processor is connected to.
+pub trait Bus {
+    fn read(&mut self, address: u16) -> u8;
+    fn write(&mut self, address: u16, value: u8);
+
+    /// Read a byte from the device on input port `port` (1-7).
+    fn input(&mut self, _port: u8) -> u8 {
+        return 0;
+    }
+
+    /// Write a byte to the device on output port `port` (1-7).
+    fn output(&mut self, _port: u8, _value: u8) {}
+
+    /// Get the state of the external flag line EF`flag` (1-4).
+    fn flag(&mut self, _flag: u8) -> bool {
+        return false;
+    }
+}
+
+/// The RCA CDP1802 processor of the COSMAC VIP.
+pub struct Cdp1802 {
+    r: [u16; REGISTER_COUNT],  // Scratchpad registers
+    d: u8,  // Accumulator
+    df: bool,  // Carry flag
+    p: u8,  // Index of the program counter register
+    x: u8,  // Index of the data pointer register
+    t: u8,  // X and P saved by an interrupt or MARK
+    ie: bool,  // Interrupt enable
+    q: bool,  // Q output flip-flop
+    idle: bool,
+}
+
+impl Cdp1802 {
+    /// Create a processor in its reset state.
+    pub fn new() -> Self {
+        return Cdp1802 {
+            r: [0; REGISTER_COUNT],
+            d: 0,
+            df: false,
+            p: 0,
+            x: 0,
+            t: 0,
+            ie: true,
+            q: false,
+            idle: false,
+        };
+    }
+
+    pub fn get_register(&self, index: u8) -> u16 {
+        return self.r[index as usize];
+    }
+
+    pub fn set_register(&mut self, index: u8, value: u16) {
+        self.r[index as usize] = value;
+    }
+
+    pub fn get_p(&self) -> u8 {
+        return self.p;
+    }
+
+    pub fn set_p(&mut self, value: u8) {
+        self.p = value & 0x0F;
+    }
+
+    pub fn set_x(&mut self, value: u8) {
+        self.x = value & 0x0F;
+    }
+
+    /// Check whether the processor stopped on `IDL`, until the next
+    /// interrupt or DMA cycle.
+    pub fn is_idle(&self) -> bool {
+        return self.idle;
+    }
+
+    /// Execute a single instruction.
+    ///
+    /// returns The amount of machine cycles taken.
+    pub fn step(&mut self, bus: &mut impl Bus) -> u32 {
+        if self.idle {
+            return 1;
+        }
+
+        let opcode = self.fetch(bus);
+        let group = opcode >> 4;
+        let n = opcode & 0x0F;
+
+        match group {
+            0x0 => {
+                if n == 0 {
+                    self.idle = true;
+                } else {
+                    self.d = bus.read(self.r[n as usize]);
+                }
+            }
+            0x1 => { self.inc(n); }
+            0x2 => { self.dec(n); }
+            0x3 => {
+                let condition = self.condition(n & 0x07, bus);
+                let taken = condition ^ (n & 0x08 > 0);
+
+                if taken {
+                    let target = bus.read(self.r[self.p as usize]);
+                    let pc = &mut self.r[self.p as usize];
+                    *pc = (*pc & 0xFF00) | target as u16;
+                } else {
+                    self.inc(self.p);
+                }
+            }
+            0x4 => {
+                self.d = bus.read(self.r[n as usize]);
+                self.inc(n);
+            }
+            0x5 => { bus.write(self.r[n as usize], self.d); }
+            0x6 => {
+                if n == 0 {
+                    self.inc(self.x);
+                } else if n < 8 {
+                    let value = bus.read(self.r[self.x as usize]);
+                    bus.output(n, value);
+                    self.inc(self.x);
+                } else if n > 8 {
+                    let value = bus.input(n - 8);
+                    bus.write(self.r[self.x as usize], value);
+                    self.d = value;
+                }
+            }
+            0x7 => { self.execute_control(n, bus); }
+            0x8 => { self.d = self.r[n as usize] as u8; }
+            0x9 => { self.d = (self.r[n as usize] >> 8) as u8; }
+            0xA => {
+                let r = &mut self.r[n as usize];
+                *r = (*r & 0xFF00) | self.d as u16;
+            }
+            0xB => {
+                let r = &mut self.r[n as usize];
+                *r = (*r & 0x00FF) | ((self.d as u16) << 8);
+            }
+            0xC => {
+                self.execute_long(n, bus);
+                return LONG_CYCLES;
+            }
+            0xD => { self.p = n; }
+            0xE => { self.x = n; }
+            _ => { self.execute_alu(n, bus); }
+        }
+
+        return SHORT_CYCLES;
+    }
+
+    /// Request an interrupt. It is only taken if interrupts are enabled.
+    ///
+    /// returns `true` if the interrupt was taken.
+    pub fn interrupt(&mut self) -> bool {
+        if !self.ie {
+            return false;
+        }
+
+        self.t = (self.x << 4) | self.p;
+        self.p = 1;
+        self.x = 2;
+        self.ie = false;
+        self.idle = false;
+        return true;
+    }
+
+    /// Perform an output DMA cycle, reading the byte R0 points to.
+    pub fn dma_out(&mut self, bus: &mut impl Bus) -> u8 {
+        let value = bus.read(self.r[0]);
+        self.inc(0);
+        self.idle = false;
+        return value;
+    }
+
+    fn fetch(&mut self, bus: &mut impl Bus) -> u8 {
+        let value = bus.read(self.r[self.p as usize]);
+        self.inc(self.p);
+        return value;
+    }
+
+    fn inc(&mut self, n: u8) {
+        self.r[n as usize] = self.r[n as usize].wrapping_add(1);
+    }
+
+    fn dec(&mut self, n: u8) {
+        self.r[n as usize] = self.r[n as usize].wrapping_sub(1);
+    }
+
+    /// Evaluate a branch condition (`0` is always true, `1` Q, `2` D is zero,
+    /// `3` DF, `4`-`7` the external flags).
+    fn condition(&self, condition: u8, bus: &mut impl Bus) -> bool {
+        match condition {
+            0 => { true }
+            1 => { self.q }
+            2 => { self.d == 0 }
+            3 => { self.df }
+            _ => { bus.flag(condition - 3) }
+        }
+    }
+
+    /// Execute the `7N` group: returns, stack operations, Q and the
+    /// arithmetic with carry.
+    fn execute_control(&mut self, n: u8, bus: &mut impl Bus) {
+        let rx = self.r[self.x as usize];
+
+        match n {
+            0x0 | 0x1 => {
+                let value = bus.read(rx);
+                self.inc(self.x);
+                self.x = value >> 4;
+                self.p = value & 0x0F;
+                self.ie = n == 0x0;
+            }
+            0x2 => {
+                self.d = bus.read(rx);
+                self.inc(self.x);
+            }
+            0x3 => {
+                bus.write(rx, self.d);
+                self.dec(self.x);
+            }
+            0x4 => { self.add(bus.read(rx), self.df); }
+            0x5 => { self.subtract(bus.read(rx), self.d, self.df); }
+            0x6 => {
+                let carry = self.d & 0x01 > 0;
+                self.d = (self.d >> 1) | ((self.df as u8) << 7);
+                self.df = carry;
+            }
+            0x7 => { self.subtract(self.d, bus.read(rx), self.df); }
+            0x8 => { bus.write(rx, self.t); }
+            0x9 => {
+                self.t = (self.x << 4) | self.p;
+                bus.write(self.r[2], self.t);
+                self.x = self.p;
+                self.dec(2);
+            }
+            0xA => { self.q = false; }
+            0xB => { self.q = true; }
+            0xC => {
+                let value = self.fetch(bus);
+                self.add(value, self.df);
+            }
+            0xD => {
+                let value = self.fetch(bus);
+                self.subtract(value, self.d, self.df);
+            }
+            0xE => {
+                let carry = self.d & 0x80 > 0;
+                self.d = (self.d << 1) | self.df as u8;
+                self.df = carry;
+            }
+            _ => {
+                let value = self.fetch(bus);
+                self.subtract(self.d, value, self.df);
+            }
+        }
+    }
+
+    /// Execute the `CN` group: long branches and skips.
+    fn execute_long(&mut self, n: u8, bus: &mut impl Bus) {
+        let condition = match n & 0x03 {
+            0 => { true }
+            1 => { self.q }
+            2 => { self.d == 0 }
+            _ => { self.df }
+        };
+
+        if n == 0x4 {
+            // NOP
+        } else if n == 0xC {
+            // LSIE
+            if self.ie {
+                self.r[self.p as usize] = self.r[self.p as usize].wrapping_add(2);
+            }
+        } else if n & 0x04 == 0 {
+            // Long branches; the upper half of the group branches when the
+            // condition is false.
+            if condition ^ (n & 0x08 > 0) {
+                let high = bus.read(self.r[self.p as usize]) as u16;
+                let low = bus.read(self.r[self.p as usize].wrapping_add(1)) as u16;
+                self.r[self.p as usize] = (high << 8) | low;
+            } else {
+                self.r[self.p as usize] = self.r[self.p as usize].wrapping_add(2);
+            }
+        } else {
+            // Long skips; the lower half of the group skips when the
+            // condition is false.
+            if condition ^ (n & 0x08 == 0) {
+                self.r[self.p as usize] = self.r[self.p as usize].wrapping_add(2);
+            }
+        }
+    }
+
+    /// Execute the `FN` group: logic and arithmetic with memory or immediate
+    /// operands.
+    fn execute_alu(&mut self, n: u8, bus: &mut impl Bus) {
+        if n == 0x6 {
+            self.df = self.d & 0x01 > 0;
+            self.d >>= 1;
+            return;
+        }
+        if n == 0xE {
+            self.df = self.d & 0x80 > 0;
+            self.d <<= 1;
+            return;
+        }
+
+        let operand = if n & 0x08 > 0 {
+            self.fetch(bus)
+        } else {
+            bus.read(self.r[self.x as usize])
+        };
+
+        match n & 0x07 {
+            0x0 => { self.d = operand; }
+            0x1 => { self.d |= operand; }
+            0x2 => { self.d &= operand; }
+            0x3 => { self.d ^= operand; }
+            0x4 => { self.add(operand, false); }
+            0x5 => { self.subtract(operand, self.d, true); }
+            _ => { self.subtract(self.d, operand, true); }
+        }
+    }
+
+    /// Set D to D + `value` + `carry`, and DF to the carry out.
+    fn add(&mut self, value: u8, carry: bool) {
+        let result = self.d as u16 + value as u16 + carry as u16;
+        self.d = result as u8;
+        self.df = result > 0xFF;
+    }
+
+    /// Set D to `a` - `b`, borrowing if `no_borrow` is not set. DF is set when
+    /// no borrow occurred.
+    fn subtract(&mut self, a: u8, b: u8, no_borrow: bool) {
+        let result = 0x100 + a as u16 - b as u16 - (!no_borrow) as u16;
+        self.d = result as u8;
+        self.df = result > 0xFF;
+    }
+}
+
+impl Bus for Memory {
+    fn read(&mut self, address: u16) -> u8 {
+        return self.read_byte(Address::from(address as usize));
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        Memory::write(self, Address::from(address as usize), &[value]);
+    }
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    /// 64K of RAM, and external flags that can be set by hand.
+    struct TestBus {
+        memory: Vec<u8>,
+        flags: [bool; 4],
+    }
+
+    impl Bus for TestBus {
+        fn read(&mut self, address: u16) -> u8 {
+            return self.memory[address as usize];
+        }
+
+        fn write(&mut self, address: u16, value: u8) {
+            self.memory[address as usize] = value;
+        }
+
+        fn flag(&mut self, flag: u8) -> bool {
+            return self.flags[flag as usize - 1];
+        }
+    }
+
+    /// Set up a processor running `program` from address 0, with R0 as its
+    /// program counter.
+    fn setup(program: &[u8]) -> (Cdp1802, TestBus) {
+        let mut bus = TestBus { memory: vec![0; 0x10000], flags: [false; 4] };
+        bus.memory[..program.len()].copy_from_slice(program);
+        return (Cdp1802::new(), bus);
+    }
+
+    #[test]
+    fn short_branches_stay_in_the_page() {
+        let (mut cpu, mut bus) = setup(&[0x30, 0x42]);
+        cpu.r[0] = 0x1200;
+        bus.memory[0x1200..0x1202].copy_from_slice(&[0x30, 0x42]);
+        assert_eq!(cpu.step(&mut bus), SHORT_CYCLES);
+        assert_eq!(cpu.r[0], 0x1242);
+
+        // BZ and BNZ, with D not zero.
+        let (mut cpu, mut bus) = setup(&[0x32, 0x42, 0x3A, 0x80]);
+        cpu.d = 1;
+        cpu.step(&mut bus);
+        assert_eq!(cpu.r[0], 0x02);
+        cpu.step(&mut bus);
+        assert_eq!(cpu.r[0], 0x80);
+
+        // B1, on the external flag, and SKP, which skips a byte.
+        let (mut cpu, mut bus) = setup(&[0x34, 0x10, 0x38]);
+        bus.memory[0x10] = 0x38;
+        bus.flags[0] = true;
+        cpu.step(&mut bus);
+        assert_eq!(cpu.r[0], 0x10);
+        cpu.step(&mut bus);
+        assert_eq!(cpu.r[0], 0x12);
+    }
+
+    #[test]
+    fn long_branches_and_skips() {
+        let (mut cpu, mut bus) = setup(&[0xC0, 0x12, 0x34]);
+        assert_eq!(cpu.step(&mut bus), LONG_CYCLES);
+        assert_eq!(cpu.r[0], 0x1234);
+
+        // LBZ not taken, then LBNZ taken.
+        let (mut cpu, mut bus) = setup(&[0xC2, 0x12, 0x34, 0xCA, 0x05, 0x67]);
+        cpu.d = 1;
+        cpu.step(&mut bus);
+        assert_eq!(cpu.r[0], 0x03);
+        cpu.step(&mut bus);
+        assert_eq!(cpu.r[0], 0x0567);
+
+        // LSNZ doesn't skip with D zero, LSZ does, and so does LSKP.
+        let (mut cpu, mut bus) = setup(&[0xC6, 0xCE, 0, 0, 0xC8, 0, 0, 0xC4]);
+        cpu.step(&mut bus);
+        assert_eq!(cpu.r[0], 0x01);
+        cpu.step(&mut bus);
+        assert_eq!(cpu.r[0], 0x04);
+        cpu.step(&mut bus);
+        assert_eq!(cpu.r[0], 0x07);
+        assert_eq!(cpu.step(&mut bus), LONG_CYCLES);
+        assert_eq!(cpu.r[0], 0x08);
+
+        // LSIE skips while interrupts are enabled.
+        let (mut cpu, mut bus) = setup(&[0xCC, 0, 0, 0xCC]);
+        cpu.step(&mut bus);
+        assert_eq!(cpu.r[0], 0x03);
+        cpu.ie = false;
+        cpu.step(&mut bus);
+        assert_eq!(cpu.r[0], 0x04);
+    }
+
+    #[test]
+    fn arithmetic_with_borrow_and_carry() {
+        // SDB: M(R(X)) - D - (not DF), with X = R2 pointing at 0x10.
+        let (mut cpu, mut bus) = setup(&[0x75, 0x75]);
+        cpu.x = 2;
+        cpu.r[2] = 0x100;
+        bus.memory[0x100] = 0x10;
+        cpu.d = 0x20;
+        cpu.df = true;
+        cpu.step(&mut bus);
+        assert_eq!((cpu.d, cpu.df), (0xF0, false));
+        cpu.d = 0x08;
+        cpu.step(&mut bus);
+        assert_eq!((cpu.d, cpu.df), (0x07, true));
+
+        // SMB: D - M(R(X)) - (not DF).
+        let (mut cpu, mut bus) = setup(&[0x77, 0x77]);
+        cpu.x = 2;
+        cpu.r[2] = 0x100;
+        bus.memory[0x100] = 0x10;
+        cpu.d = 0x20;
+        cpu.df = false;
+        cpu.step(&mut bus);
+        assert_eq!((cpu.d, cpu.df), (0x0F, true));
+        cpu.step(&mut bus);
+        assert_eq!((cpu.d, cpu.df), (0xFF, false));
+
+        // SHRC and SHLC rotate through DF, SHR shifts a zero in.
+        let (mut cpu, mut bus) = setup(&[0x76, 0x7E, 0xF6]);
+        cpu.d = 0x03;
+        cpu.df = true;
+        cpu.step(&mut bus);
+        assert_eq!((cpu.d, cpu.df), (0x81, true));
+        cpu.step(&mut bus);
+        assert_eq!((cpu.d, cpu.df), (0x03, true));
+        cpu.step(&mut bus);
+        assert_eq!((cpu.d, cpu.df), (0x01, true));
+    }
+
+    #[test]
+    fn mark_save_and_return() {
+        // MARK, SEX 2, SAV, INC 2, RET: the usual way to call a subroutine
+        // and return from it, with the return X and P kept on the R2 stack.
+        let (mut cpu, mut bus) = setup(&[]);
+        bus.memory[0x10..0x15].copy_from_slice(&[0x79, 0xE2, 0x78, 0x12, 0x70]);
+        cpu.p = 3;
+        cpu.x = 5;
+        cpu.r[3] = 0x10;
+        cpu.r[2] = 0x80;
+        cpu.ie = false;
+
+        cpu.step(&mut bus);
+        assert_eq!((cpu.t, cpu.x, cpu.r[2]), (0x53, 3, 0x7F));
+        assert_eq!(bus.memory[0x80], 0x53);
+        cpu.step(&mut bus);
+        cpu.step(&mut bus);
+        assert_eq!(bus.memory[0x7F], 0x53);
+        cpu.step(&mut bus);
+        cpu.step(&mut bus);
+        assert_eq!((cpu.x, cpu.p, cpu.r[2], cpu.ie), (5, 3, 0x81, true));
+        assert_eq!(cpu.r[3], 0x15);
+    }
+
+    #[test]
+    fn interrupts_and_dma_wake_the_processor() {
+        let (mut cpu, mut bus) = setup(&[0x00, 0xC4]);
+        cpu.step(&mut bus);
+        assert!(cpu.is_idle());
+        assert_eq!(cpu.step(&mut bus), 1);
+        assert_eq!(cpu.r[0], 0x01);
+
+        // DMA reads the byte R0 points to, and moves on.
+        bus.memory[0x01] = 0xAB;
+        assert_eq!(cpu.dma_out(&mut bus), 0xAB);
+        assert_eq!(cpu.r[0], 0x02);
+        assert!(!cpu.is_idle());
+
+        // Interrupts save X and P in T, and run R1 with R2 as the data
+        // pointer, until interrupts are enabled again.
+        cpu.idle = true;
+        cpu.x = 5;
+        cpu.p = 3;
+        assert!(cpu.interrupt());
+        assert_eq!((cpu.t, cpu.x, cpu.p, cpu.ie, cpu.idle), (0x53, 2, 1, false, false));
+        assert!(!cpu.interrupt());
+    }
+}
@@ -0,0 +1,509 @@
+// ----- Imports ----- //
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+
+use crate::console;
+use crate::console::Port;
+use crate::cpu::Status;
+use crate::cpu::instructions;
+use crate::error::Error;
+use crate::keyboard::Keymap;
+use crate::memory;
+use crate::machine::Backend;
+use crate::platform::{MemoryLayout, Platform, Quirks};
+use crate::random;
+use crate::random::Generator;
+use crate::rom::RomImage;
+use crate::rom::database::RomInfo;
+use crate::speed::{DEFAULT_FAST_FORWARD, DEFAULT_SLOW_MOTION};
+use crate::timing::Timing;
+use crate::trace;
+use crate::trace::TraceFormat;
+
+// ----- Consts ----- //
+
+/// Instructions per frame, when neither the database nor the user says better.
+pub const DEFAULT_SPEED: u32 = 12;
+
+/// Exit code when the program fails, or can't even start.
+pub const DEFAULT_ERROR_EXIT: i32 = 1;
+const DEFAULT_TIMEOUT_EXIT: i32 = 124;
+
+// ----- Structs ----- //
+
+/// Options given on the command line.
+pub struct Config {
+    pub rom_path: String,
+    pub platform: Option<Platform>,
+    pub quirks: Vec<(String, bool)>,
+    pub layout: Option<MemoryLayout>,
+    pub speed: Option<u32>,
+    pub fast_forward: u32,
+    pub slow_motion: u32,
+    pub timing: Option<Timing>,
+    pub generator: Option<Generator>,
+    /// VIP interpreter image, whose code page the `vip` generator reads.
+    pub rng_code: Option<String>,
+    /// Seed for the random number generator, to replay a run.
+    pub seed: Option<u64>,
+    pub backend: Backend,
+    /// Address of the debug console port, to turn the console on.
+    pub console: Option<usize>,
+    pub console_format: console::Format,
+    /// Debugging annotations to check, as written by the assembler.
+    pub debug: Option<String>,
+    /// Run without showing the display or reading the keyboard.
+    pub headless: bool,
+    /// Amount of frames to run before stopping.
+    pub frames: Option<u64>,
+    /// Exit with this code once the program halts.
+    pub exit_on_halt: Option<i32>,
+    /// Exit code when the program fails.
+    pub exit_on_error: i32,
+    /// Exit code when the program is still running after `frames` frames.
+    pub exit_on_timeout: i32,
+    pub dump_screen: bool,
+    pub dump_registers: bool,
+    /// Script of the keys to press, when running without a keyboard.
+    pub input: Option<String>,
+    /// Trace the instructions as they run, in this format.
+    pub trace: Option<TraceFormat>,
+    /// File to write the trace to, rather than the standard error.
+    pub trace_file: Option<String>,
+    /// Include the whole memory in JSON traces.
+    pub trace_memory: bool,
+    /// Only trace instructions at these addresses.
+    pub trace_range: Option<RangeInclusive<usize>>,
+    /// Only trace these instructions.
+    pub trace_kinds: Vec<String>,
+    /// CHIP-8 interpreter image to run on an emulated COSMAC VIP.
+    pub vip_interpreter: Option<String>,
+    /// VIP monitor ROM image, for interpreters that need it.
+    pub vip_monitor: Option<String>,
+}
+
+/// The final configuration of the emulator for a specific ROM.
+pub struct Settings {
+    pub platform: Platform,
+    pub quirks: Quirks,
+    pub layout: MemoryLayout,
+    pub keymap: Keymap,
+    pub speed: u32,
+    pub fast_forward: u32,
+    pub slow_motion: u32,
+    pub timing: Timing,
+    pub generator: Generator,
+    pub seed: u64,
+    /// The debug console port, when the console is on.
+    pub console: Option<Port>,
+}
+
+impl Config {
+    /// Parse the command line `args` (not including the program name).
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut rom_path: Option<String> = None;
+        let mut platform: Option<Platform> = None;
+        let mut quirks: Vec<(String, bool)> = Vec::new();
+        let mut layout: Option<MemoryLayout> = None;
+        let mut speed: Option<u32> = None;
+        let mut fast_forward = DEFAULT_FAST_FORWARD;
+        let mut slow_motion = DEFAULT_SLOW_MOTION;
+        let mut timing: Option<Timing> = None;
+        let mut generator: Option<Generator> = None;
+        let mut rng_code: Option<String> = None;
+        let mut seed: Option<u64> = None;
+        let mut backend = Backend::Interpreter;
+        let mut console: Option<usize> = None;
+        let mut console_format = console::Format::Text;
+        let mut debug: Option<String> = None;
+        let mut headless = false;
+        let mut frames: Option<u64> = None;
+        let mut exit_on_halt: Option<i32> = None;
+        let mut exit_on_error = DEFAULT_ERROR_EXIT;
+        let mut exit_on_timeout = DEFAULT_TIMEOUT_EXIT;
+        let mut dump_screen = false;
+        let mut dump_registers = false;
+        let mut input: Option<String> = None;
+        let mut trace: Option<TraceFormat> = None;
+        let mut trace_file: Option<String> = None;
+        let mut trace_memory = false;
+        let mut trace_range: Option<RangeInclusive<usize>> = None;
+        let mut trace_kinds: Vec<String> = Vec::new();
+        let mut vip_interpreter: Option<String> = None;
+        let mut vip_monitor: Option<String> = None;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--platform" => {
+                    let name = args.next().ok_or("Missing value for --platform")?;
+                    let value = Platform::from_name(name)
+                        .ok_or(format!("Unknown platform '{}'", name))?;
+                    platform = Some(value);
+                }
+                "--quirk" => {
+                    let quirk = args.next().ok_or("Missing value for --quirk")?;
+                    quirks.push(parse_quirk(quirk)?);
+                }
+                "--layout" => {
+                    let name = args.next().ok_or("Missing value for --layout")?;
+                    let value = MemoryLayout::from_name(name)
+                        .ok_or(format!("Unknown memory layout '{}'", name))?;
+                    layout = Some(value);
+                }
+                "--speed" | "--ipf" => {
+                    let value = args.next().ok_or(format!("Missing value for {}", arg))?;
+                    speed = Some(parse_factor(arg, value)?);
+                }
+                "--fast-forward" => {
+                    let value = args.next().ok_or("Missing value for --fast-forward")?;
+                    fast_forward = parse_factor(arg, value)?;
+                }
+                "--slow-motion" => {
+                    let value = args.next().ok_or("Missing value for --slow-motion")?;
+                    slow_motion = parse_factor(arg, value)?;
+                }
+                "--timing" => {
+                    let name = args.next().ok_or("Missing value for --timing")?;
+                    let value = Timing::from_name(name)
+                        .ok_or(format!("Unknown timing '{}'", name))?;
+                    timing = Some(value);
+                }
+                "--rng" => {
+                    let name = args.next().ok_or("Missing value for --rng")?;
+                    let value = Generator::from_name(name)
+                        .ok_or(format!("Unknown random number generator '{}'", name))?;
+                    generator = Some(value);
+                }
+                "--rng-code" => {
+                    let path = args.next().ok_or("Missing value for --rng-code")?;
+                    rng_code = Some(path.clone());
+                }
+                "--seed" => {
+                    let value = args.next().ok_or("Missing value for --seed")?;
+                    seed = Some(parse_seed(value)?);
+                }
+                "--backend" => {
+                    let name = args.next().ok_or("Missing value for --backend")?;
+                    backend = Backend::from_name(name)
+                        .ok_or(format!("Unknown backend '{}'", name))?;
+                }
+                "--console" => {
+                    let value = args.next().ok_or("Missing value for --console")?;
+                    let address = usize::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+                        .filter(|&address| address < memory::MEMORY_SIZE)
+                        .ok_or(format!("Invalid address '{}' for --console", value))?;
+                    console = Some(address);
+                }
+                "--console-format" => {
+                    let name = args.next().ok_or("Missing value for --console-format")?;
+                    console_format = console::Format::from_name(name)
+                        .ok_or(format!("Unknown console format '{}'", name))?;
+                }
+                "--debug" => {
+                    let path = args.next().ok_or("Missing value for --debug")?;
+                    debug = Some(path.clone());
+                }
+                "--headless" => { headless = true; }
+                "--frames" => {
+                    let value = args.next().ok_or("Missing value for --frames")?;
+                    frames = Some(value.parse::<u64>()
+                        .map_err(|_| format!("Invalid value '{}' for --frames", value))?);
+                }
+                "--exit-on-halt" => {
+                    let value = args.next().ok_or("Missing value for --exit-on-halt")?;
+                    exit_on_halt = Some(parse_exit_code(arg, value)?);
+                }
+                "--exit-on-error" => {
+                    let value = args.next().ok_or("Missing value for --exit-on-error")?;
+                    exit_on_error = parse_exit_code(arg, value)?;
+                }
+                "--exit-on-timeout" => {
+                    let value = args.next().ok_or("Missing value for --exit-on-timeout")?;
+                    exit_on_timeout = parse_exit_code(arg, value)?;
+                }
+                "--dump-screen" => { dump_screen = true; }
+                "--dump-registers" => { dump_registers = true; }
+                "--input" => {
+                    let path = args.next().ok_or("Missing value for --input")?;
+                    input = Some(path.clone());
+                }
+                "--trace" => {
+                    let name = args.next().ok_or("Missing value for --trace")?;
+                    let value = TraceFormat::from_name(name)
+                        .ok_or(format!("Unknown trace format '{}'", name))?;
+                    trace = Some(value);
+                }
+                "--trace-file" => {
+                    let path = args.next().ok_or("Missing value for --trace-file")?;
+                    trace_file = Some(path.clone());
+                }
+                "--trace-memory" => { trace_memory = true; }
+                "--trace-range" => {
+                    let range = args.next().ok_or("Missing value for --trace-range")?;
+                    let value = trace::parse_range(range)
+                        .ok_or(format!("Invalid address range '{}'", range))?;
+                    trace_range = Some(value);
+                }
+                "--trace-kind" => {
+                    let kinds = args.next().ok_or("Missing value for --trace-kind")?;
+                    for kind in kinds.split(',') {
+                        let kind = kind.to_uppercase();
+                        if kind != "INVALID" && instructions::find(&kind).is_none() {
+                            return Err(format!("Unknown instruction '{}'", kind));
+                        }
+                        trace_kinds.push(kind);
+                    }
+                }
+                "--vip-interpreter" => {
+                    let path = args.next().ok_or("Missing value for --vip-interpreter")?;
+                    vip_interpreter = Some(path.clone());
+                }
+                "--vip-monitor" => {
+                    let path = args.next().ok_or("Missing value for --vip-monitor")?;
+                    vip_monitor = Some(path.clone());
+                }
+                _ if arg.starts_with("--") => {
+                    return Err(format!("Unknown option '{}'", arg));
+                }
+                _ => {
+                    if rom_path.is_some() {
+                        return Err(format!("Unexpected argument '{}'", arg));
+                    }
+                    rom_path = Some(arg.clone());
+                }
+            }
+        }
+
+        let rom_path = rom_path.ok_or("Missing target executable!")?;
+        if generator == Some(Generator::Vip) && rng_code.is_none() {
+            return Err(String::from("The vip generator reads the interpreter's code: give its image with --rng-code"));
+        }
+        return Ok(Config {
+            rom_path,
+            platform,
+            quirks,
+            layout,
+            speed,
+            fast_forward,
+            slow_motion,
+            timing,
+            generator,
+            rng_code,
+            seed,
+            backend,
+            console,
+            console_format,
+            debug,
+            headless,
+            frames,
+            exit_on_halt,
+            exit_on_error,
+            exit_on_timeout,
+            dump_screen,
+            dump_registers,
+            input,
+            trace,
+            trace_file,
+            trace_memory,
+            trace_range,
+            trace_kinds,
+            vip_interpreter,
+            vip_monitor,
+        });
+    }
+
+    /// Work out the settings for the given `rom`. Values from the ROM database
+    /// are used when the ROM is known, and anything given on the command line
+    /// takes precedence over them.
+    pub fn settings(&self, rom: &RomImage) -> Settings {
+        return self.settings_for(rom.info());
+    }
+
+    /// Work out the settings for a ROM, given its database entry if it has
+    /// one.
+    fn settings_for(&self, info: Option<&RomInfo>) -> Settings {
+        let platform = self.platform
+            .or(info.map(|info| info.platform))
+            .unwrap_or_default();
+
+        let mut quirks = platform.quirks();
+        if let Some(known) = info.and_then(|info| info.quirks) {
+            if self.platform.is_none() {
+                quirks = known;
+            }
+        }
+        for (name, value) in self.quirks.iter() {
+            quirks.set(name, *value);
+        }
+
+        let layout = self.layout.unwrap_or(platform.layout());
+        let keymap = info.and_then(|info| info.keymap).unwrap_or_default();
+        let speed = self.speed
+            .or(info.and_then(|info| info.speed))
+            .unwrap_or(DEFAULT_SPEED);
+
+        let timing = self.timing.unwrap_or(platform.timing());
+        // Without the interpreter's code, the VIP generator wouldn't give a
+        // VIP's values, and the platform gets the default one instead.
+        let generator = match self.generator.unwrap_or(platform.generator()) {
+            Generator::Vip if self.rng_code.is_none() => { Generator::Xorshift }
+            generator => { generator }
+        };
+        let seed = self.seed.unwrap_or_else(random::default_seed);
+        let console = self.console.map(|address| Port { address, format: self.console_format });
+
+        return Settings {
+            platform,
+            quirks,
+            layout,
+            keymap,
+            speed,
+            fast_forward: self.fast_forward,
+            slow_motion: self.slow_motion,
+            timing,
+            generator,
+            seed,
+            console,
+        };
+    }
+
+    /// Read the interpreter image given with `--rng-code`, if any.
+    #[cfg(feature = "std")]
+    pub fn load_rng_code(&self) -> Result<Option<Vec<u8>>, String> {
+        let Some(path) = &self.rng_code else {
+            return Ok(None);
+        };
+        let code = std::fs::read(path).map_err(|error| format!("Failed to read {}: {}", path, error))?;
+        return Ok(Some(code));
+    }
+
+    /// Get the exit code for a run that ended with `result`: the status of its
+    /// last instruction, which is only `Halted` if the program stopped by
+    /// itself.
+    pub fn exit_code(&self, result: &Result<Status, Error>) -> i32 {
+        match result {
+            Ok(Status::Halted) => { self.exit_on_halt.unwrap_or(0) }
+            Ok(_) => { self.exit_on_timeout }
+            Err(_) => { self.exit_on_error }
+        }
+    }
+}
+
+/// Parse the positive number `value`, given for the `option`.
+fn parse_factor(option: &str, value: &str) -> Result<u32, String> {
+    let value = value.parse::<u32>()
+        .map_err(|_| format!("Invalid value '{}' for {}", value, option))?;
+    if value == 0 {
+        return Err(format!("{} must be at least 1", option));
+    }
+    return Ok(value);
+}
+
+/// Parse the exit code `value`, given for the `option`.
+fn parse_exit_code(option: &str, value: &str) -> Result<i32, String> {
+    return value.parse::<i32>()
+        .map_err(|_| format!("Invalid value '{}' for {}", value, option));
+}
+
+/// Parse a seed, in decimal or in hexadecimal with a `0x` prefix.
+fn parse_seed(value: &str) -> Result<u64, String> {
+    let result = match value.strip_prefix("0x") {
+        Some(hex) => { u64::from_str_radix(hex, 16) }
+        None => { value.parse::<u64>() }
+    };
+    return result.map_err(|_| format!("Invalid value '{}' for --seed", value));
+}
+
+/// Parse a quirk override in the form `name`, `name=on` or `name=off`.
+fn parse_quirk(quirk: &str) -> Result<(String, bool), String> {
+    let (name, value) = match quirk.split_once('=') {
+        Some((name, "on")) | Some((name, "true")) | Some((name, "1")) => { (name, true) }
+        Some((name, "off")) | Some((name, "false")) | Some((name, "0")) => { (name, false) }
+        Some(_) => { return Err(format!("Invalid quirk '{}'", quirk)); }
+        None => { (quirk, true) }
+    };
+
+    if !Quirks::default().set(name, value) {
+        return Err(format!("Unknown quirk '{}'", name));
+    }
+    return Ok((String::from(name), value));
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    /// A database entry that differs from the defaults in every way.
+    const INFO: RomInfo = RomInfo {
+        title: "Test",
+        author: None,
+        platform: Platform::SuperChip,
+        quirks: Some(Quirks { shift: false, memory_increment: true, jump: true, logic: true, wrap: true, display_wait: false }),
+        keymap: None,
+        speed: Some(30),
+    };
+
+    fn config(args: &[&str]) -> Config {
+        let args: Vec<String> = ["rom.ch8"].iter().chain(args).map(|arg| arg.to_string()).collect();
+        return Config::from_args(&args).unwrap();
+    }
+
+    #[test]
+    fn database_entries_fill_in_the_settings() {
+        let settings = config(&[]).settings_for(Some(&INFO));
+        assert!(settings.platform == Platform::SuperChip);
+        assert!(settings.quirks == INFO.quirks.unwrap());
+        assert_eq!(settings.speed, 30);
+
+        let settings = config(&[]).settings_for(None);
+        assert!(settings.platform == Platform::Chip8);
+        assert!(settings.quirks == Platform::Chip8.quirks());
+        assert_eq!(settings.speed, DEFAULT_SPEED);
+    }
+
+    #[test]
+    fn command_line_overrides_the_database() {
+        let settings = config(&["--platform", "vip", "--speed", "8"]).settings_for(Some(&INFO));
+        assert!(settings.platform == Platform::CosmacVip);
+        // The known quirks are for the database's platform, not this one.
+        assert!(settings.quirks == Platform::CosmacVip.quirks());
+        assert_eq!(settings.speed, 8);
+
+        let settings = config(&["--quirk", "wrap=off", "--quirk", "shift"]).settings_for(Some(&INFO));
+        assert!(settings.platform == Platform::SuperChip);
+        assert!(!settings.quirks.wrap && settings.quirks.shift && settings.quirks.jump);
+    }
+
+    #[test]
+    fn the_vip_generator_needs_the_interpreter_code() {
+        assert!(config(&["--platform", "vip"]).settings_for(None).generator == Generator::Xorshift);
+        let settings = config(&["--platform", "vip", "--rng-code", "chip8.bin"]).settings_for(None);
+        assert!(settings.generator == Generator::Vip);
+
+        let args = ["rom.ch8", "--rng", "vip"].map(String::from);
+        assert!(Config::from_args(&args).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn known_roms_are_set_up_from_the_database() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/test_roms/ibm.ch8");
+        let rom = RomImage::load(path).unwrap();
+        let settings = config(&[]).settings(&rom);
+        assert!(settings.platform == Platform::CosmacVip);
+        assert!(settings.quirks != Platform::Chip8.quirks());
+
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/test_roms/test_keyboard.ch8");
+        let rom = RomImage::load(path).unwrap();
+        let settings = config(&[]).settings(&rom);
+        assert_eq!(settings.keymap.key('c').map(|key| key.get()), Some(0xC));
+        assert_eq!(Keymap::default().key('c').map(|key| key.get()), Some(0xB));
+    }
+}
@@ -0,0 +1,116 @@
+// ----- Imports ----- //
+
+use alloc::format;
+use alloc::string::String;
+
+// ----- Consts ----- //
+
+/// Opcode that dumps the registers to the console: `SET(vf, vf)`, which does
+/// nothing on any interpreter, so that ROMs using it still run elsewhere.
+pub const DEBUG_OPCODE: u16 = 0x8FF0;
+
+// ----- Structs ----- //
+
+/// How bytes written to the console port are logged.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Format {
+    /// As ASCII characters, with `0a` starting a new line.
+    Text,
+    /// As hexadecimal numbers, one per line.
+    Hex,
+}
+
+/// Where the console port is, and how writes to it are logged.
+#[derive(Copy, Clone)]
+pub struct Port {
+    pub address: usize,
+    pub format: Format,
+}
+
+/// Debug output of the program: the bytes it writes to the console port, and
+/// the registers at each debug opcode.
+pub struct Console {
+    port: Port,
+    output: String,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" => { Some(Format::Text) }
+            "hex" => { Some(Format::Hex) }
+            _ => { None }
+        }
+    }
+}
+
+impl Console {
+    pub fn new(port: Port) -> Self {
+        return Console { port, output: String::new() };
+    }
+
+    /// Log the `byte` written to the port.
+    pub fn write(&mut self, byte: u8) {
+        match self.port.format {
+            Format::Text => {
+                if byte == b'\n' || byte.is_ascii_graphic() || byte == b' ' {
+                    self.output.push(byte as char);
+                } else {
+                    self.output += &format!("\\x{:02x}", byte);
+                }
+            }
+            Format::Hex => { self.output += &format!("{:02x}\n", byte); }
+        }
+    }
+
+    /// Log the state of the registers, as dumped when the debug opcode ran at
+    /// `address`.
+    pub fn dump(&mut self, address: usize, registers: &str) {
+        if !self.output.is_empty() && !self.output.ends_with('\n') {
+            self.output.push('\n');
+        }
+        self.output += &format!("debug at {:03x}:\n{}", address, registers);
+    }
+
+    /// Take the output logged since the last call.
+    pub fn take(&mut self) -> String {
+        return core::mem::take(&mut self.output);
+    }
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn console(format: Format) -> Console {
+        return Console::new(Port { address: 0x300, format });
+    }
+
+    #[test]
+    fn text_shows_other_bytes_escaped() {
+        let mut console = console(Format::Text);
+        for &byte in b"Hi!\n\x00 ok" {
+            console.write(byte);
+        }
+        assert_eq!(console.take(), "Hi!\n\\x00 ok");
+        assert_eq!(console.take(), "");
+    }
+
+    #[test]
+    fn hex_shows_a_byte_per_line() {
+        let mut console = console(Format::Hex);
+        console.write(0x0A);
+        console.write(0xFF);
+        assert_eq!(console.take(), "0a\nff\n");
+    }
+
+    #[test]
+    fn dumps_start_on_their_own_line() {
+        let mut console = console(Format::Text);
+        console.write(b'x');
+        console.dump(0x2F0, "v0=01\n");
+        assert_eq!(console.take(), "x\ndebug at 2f0:\nv0=01\n");
+    }
+}
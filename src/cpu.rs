@@ -1,101 +1,351 @@
 // ----- Modules ----- //
 
+pub mod extensions;
 pub mod instructions;
+pub mod recompiler;
 
 // ----- Imports ----- //
 
-use std::cell::RefCell;
-use std::rc::Rc;
-use std::time::Duration;
-use spin_sleep::sleep;
-use rand::Rng;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
 
+use crate::cdp1802::Cdp1802;
+use crate::config::Settings;
+use crate::console;
+use crate::console::Console;
+use crate::cpu::extensions::{Context, Extensions};
 use crate::cpu::instructions::Instruction;
-use crate::display::{Display, Sprite};
+use crate::cpu::recompiler::{Recompiler, StaticCode};
+use crate::debug::{Check, DebugInfo, Operand};
+use crate::display::{Display, Framebuffer, Sprite};
+use crate::error::{Error, ErrorKind};
 use crate::{keyboard, memory};
+use crate::keyboard::Keypad;
 use crate::memory::address::Address;
 use crate::memory::Memory;
-use crate::registers::{PC, Registers};
+use crate::platform::{MemoryLayout, Quirks};
+use crate::random;
+use crate::random::Random;
+use crate::registers::{PC, Registers, VARIABLE_COUNT};
+use crate::rom::RomImage;
 use crate::stack::Stack;
 use crate::timers::Timer;
+use crate::timing;
+use crate::trace::{TraceEntry, Tracer};
 
 // ----- Consts ----- //
 
-const INSTRUCTION_PAUSE: Duration = Duration::from_micros(1400);
+/// Initial 1802 stack pointer (R2) for machine code routines, right below the
+/// VIP interpreter work area.
+const MACHINE_STACK: u16 = 0x0ECF;
+/// Machine code routines return to the interpreter with `SEP R4`.
+const RETURN_REGISTER: u8 = 4;
+/// Machine cycles a routine may run for before it is considered stuck.
+const MACHINE_CODE_LIMIT: u32 = 1_000_000;
 
 // ----- Structs ----- //
 
 pub struct CPU {
-    display: Display,
-    memory: Rc<RefCell<Memory>>,
+    framebuffer: Framebuffer,
+    memory: Memory,
     stack: Stack,
     registers: Registers,
     pc: PC,
     delay_timer: Timer,
     sound_timer: Timer,
+    quirks: Quirks,
+    layout: MemoryLayout,
+    keypad: Keypad,
+    routine_cycles: u32,  // Cycles taken by the last machine code routine
+    status: Status,  // Status of the last instruction
+    last_loop: Option<LoopState>,  // State at the last backward jump
+    cycles: u64,  // Operation cycles run so far
+    tracer: Option<Tracer>,
+    recompiler: Option<Recompiler>,
+    static_code: Option<StaticCode>,
+    random: Random,
+    extensions: Option<Arc<Extensions>>,
+    console: Option<Console>,
+    debug: Option<Arc<DebugInfo>>,
+    breakpoint: Option<usize>,  // Breakpoint the run stopped at, to go past when resuming
+}
+
+/// Everything an iteration of a loop can depend on, within a single frame.
+/// Timers and keys only change between frames, so a loop that comes back to
+/// the same state without touching memory or the display in between would
+/// spin the same way until the frame ends.
+#[derive(Eq, PartialEq)]
+struct LoopState {
+    address: usize,
+    variables: [u8; VARIABLE_COUNT],
+    index: usize,
+    delay: u8,
+    sound: u8,
+    depth: usize,
+}
+
+/// Outcome of a single operation cycle.
+pub struct Step {
+    /// The time the instruction took on the VIP, in machine cycles.
+    pub cycles: u32,
+    pub status: Status,
+}
+
+/// Outcome of a batch of operation cycles.
+pub struct Batch {
+    pub instructions: u32,
+    /// The time the instructions took on the VIP, in machine cycles.
+    pub cycles: u32,
+    /// Status of the last instruction.
+    pub status: Status,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Status {
+    Running,
+    /// Nothing more should run until the next frame.
+    WaitVblank,
+    /// The program waits for a key to be pressed.
+    WaitKey,
+    /// The program is spinning in a loop that can't end before the next frame
+    /// (like polling the delay timer).
+    Idle,
+    /// The program jumped to itself, which is how most programs stop.
+    Halted,
+    /// The program reached a breakpoint. The instruction there runs once the
+    /// run resumes.
+    Breakpoint,
 }
 
 impl CPU {
-    pub fn new(exe_path: &str) -> Self {
-        let memory = Rc::new(RefCell::new(Memory::new(exe_path)));
+    pub fn new(rom: &RomImage, settings: &Settings) -> Self {
+        let framebuffer = match settings.layout.display {
+            Some(base) => { Framebuffer::new_mapped(base) }
+            None => { Framebuffer::new() }
+        };
+
+        let mut memory = Memory::new(rom.data());
+        if let Some(port) = settings.console {
+            memory.set_port(port.address);
+        }
 
         CPU {
-            display: Display::new(),
-            memory: Rc::clone(&memory),
-            stack: Stack::new(Rc::clone(&memory)),
+            framebuffer,
+            memory,
+            stack: Stack::new(settings.layout.stack),
             registers: Registers::new(),
             pc: PC::new(),
             delay_timer: Timer::new(),
             sound_timer: Timer::new(),
+            quirks: settings.quirks,
+            layout: settings.layout,
+            keypad: Keypad::new(),
+            routine_cycles: 0,
+            status: Status::Running,
+            last_loop: None,
+            cycles: 0,
+            tracer: None,
+            recompiler: None,
+            static_code: None,
+            random: Random::new(settings.generator, settings.seed),
+            extensions: None,
+            console: settings.console.map(Console::new),
+            debug: None,
+            breakpoint: None,
+        }
+    }
+
+    pub fn display(&self) -> Display<'_> {
+        return Display::new(&self.framebuffer, &self.memory);
+    }
+
+    pub fn keypad_mut(&mut self) -> &mut Keypad {
+        return &mut self.keypad;
+    }
+
+    /// Trace the instructions as they run, with the given `tracer`.
+    pub fn set_tracer(&mut self, tracer: Tracer) {
+        self.tracer = Some(tracer);
+    }
+
+    /// Take the entries a buffered tracer recorded since the last call.
+    pub fn take_trace(&mut self) -> Vec<TraceEntry> {
+        match &mut self.tracer {
+            Some(tracer) => { tracer.take() }
+            None => { Vec::new() }
         }
     }
 
-    /// Fetch the next opcode from the memory.
-    fn fetch(&mut self) -> u16 {
+    /// Describe the state of the registers, the timers and the call stack.
+    pub fn dump_registers(&self) -> String {
+        let variables: Vec<String> = (0..VARIABLE_COUNT)
+            .map(|i| format!("v{:x}={:02x}", i, self.registers.get_variable(i)))
+            .collect();
+        let mut result = variables.join(" ");
+        result += &format!("\nI={:03x} PC={:03x} DT={:02x} ST={:02x}\n",
+                           self.registers.get_index().get(), self.pc.get().get(),
+                           self.delay_timer.get(), self.sound_timer.get());
+
+        let calls: Vec<String> = self.stack.frames(&self.memory).map(|frame| format!("{:03x}", frame.get())).collect();
+        result += &format!("stack=[{}]\n", calls.join(", "));
+        result += &format!("seed={:#x}\n", self.random.seed());
+
+        return result;
+    }
+
+    /// Count both timers down, and advance the random number generator, once
+    /// per frame.
+    pub fn tick_timers(&mut self) {
+        self.delay_timer.tick();
+        self.sound_timer.tick();
+        self.random.tick();
+    }
+
+    /// Get the amount of instructions run so far.
+    pub fn instructions(&self) -> u64 {
+        return self.cycles;
+    }
+
+    /// Turn the basic block recompiler on or off.
+    pub fn set_recompiler(&mut self, enabled: bool) {
+        self.recompiler = if enabled { Some(Recompiler::new(self.quirks)) } else { None };
+    }
+
+    /// Run the statically recompiled code found by `lookup` where possible.
+    pub fn set_static_code(&mut self, lookup: StaticCode) {
+        self.static_code = Some(lookup);
+    }
+
+    /// Put the code page of the VIP `interpreter` image in place, for the VIP
+    /// random number generator to read.
+    pub fn load_rng_code(&mut self, interpreter: &[u8]) {
+        let end = interpreter.len().min(random::VIP_CODE_PAGE + random::VIP_PAGE_SIZE);
+        if let Some(page) = interpreter.get(random::VIP_CODE_PAGE..end) {
+            self.memory.write(Address::from(random::VIP_CODE_PAGE), page);
+            self.memory.take_port_writes();
+        }
+    }
+
+    pub fn random(&self) -> &Random {
+        return &self.random;
+    }
+
+    pub fn set_random(&mut self, random: Random) {
+        self.random = random;
+    }
+
+    /// Run the custom instructions in `extensions` in place of invalid ones.
+    pub fn set_extensions(&mut self, extensions: Arc<Extensions>) {
+        self.extensions = Some(extensions);
+    }
+
+    /// Check the assertions, and stop at the breakpoints, in `debug`.
+    pub fn set_debug(&mut self, debug: Arc<DebugInfo>) {
+        self.debug = Some(debug);
+    }
+
+    /// Take the output of the debug console since the last call. It is empty
+    /// when the console is off.
+    pub fn take_console(&mut self) -> String {
+        match &mut self.console {
+            Some(console) => { console.take() }
+            None => { String::new() }
+        }
+    }
+
+    pub fn registers(&self) -> &Registers {
+        return &self.registers;
+    }
+
+    pub fn memory(&self) -> &Memory {
+        return &self.memory;
+    }
+
+    /// Get the amount of calls currently on the stack.
+    pub fn depth(&self) -> usize {
+        return self.stack.depth();
+    }
+
+    /// Give access to the registers, for statically recompiled code.
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        return &mut self.registers;
+    }
+
+    /// Give access to the program counter, for statically recompiled code.
+    pub fn pc_mut(&mut self) -> &mut PC {
+        return &mut self.pc;
+    }
+
+    pub fn set_decode_cache(&mut self, enabled: bool) {
+        self.memory.set_decode_cache(enabled);
+    }
+
+    /// Fetch the next instruction from the memory, along with its opcode.
+    fn fetch(&mut self) -> Result<(u16, Instruction), ErrorKind> {
         let cur = self.pc.get();
-        let data = self.memory.borrow().read(cur, 2);
-        assert_eq!(data.len(), 2);
+        let fetched = self.memory.fetch(cur).ok_or(ErrorKind::EndOfMemory)?;
 
-        self.pc.increment();
-        return ((data[0] as u16) << 8) | (data[1] as u16);
+        self.pc.increment()?;
+        return Ok(fetched);
     }
 
-    /// Execute the given instruction on the CPU.
-    fn execute(&mut self, instruction: Instruction) {
+    /// Execute the given instruction on the CPU. Statically recompiled code
+    /// falls back on this for the instructions it doesn't translate.
+    pub fn execute(&mut self, instruction: Instruction) -> Result<(), ErrorKind> {
         match instruction {
-            Instruction::CLS() => { self.display.clear(); }
-            Instruction::RET() => {
-                self.pc.set(self.stack.pop());
+            Instruction::CLS {} => {
+                self.framebuffer.clear(&mut self.memory);
+                self.last_loop = None;
+            }
+            Instruction::RET {} => {
+                self.pc.set(self.stack.pop(&self.memory)?);
+            }
+            Instruction::SYS { address } if address.get() == 0 => {
+                // Opcode 0000, which is what zeroed memory decodes to.
+                return Err(ErrorKind::EmptyMemory);
+            }
+            Instruction::SYS { address } => {
+                self.routine_cycles = self.call_machine_code(address)?;
+                self.last_loop = None;
+            }
+            Instruction::JUMP { address } => {
+                if address.get() == self.pc.get().get() - 2 {
+                    self.status = Status::Halted;
+                } else {
+                    self.check_loop(address);
+                }
+                self.pc.set(address);
             }
-            Instruction::JUMP { address } => { self.pc.set(address); }
             Instruction::CALL { address } => {
-                self.stack.push(self.pc.get());
+                self.stack.push(&mut self.memory, self.pc.get())?;
                 self.pc.set(address);
             }
             Instruction::SEQ { reg, imm8 } => {
                 let value = self.registers.get_variable(reg);
                 if imm8 == value {
-                    self.pc.increment();
+                    self.pc.increment()?;
                 }
             }
             Instruction::SNE { reg, imm8 } => {
                 let value = self.registers.get_variable(reg);
                 if imm8 != value {
-                    self.pc.increment();
+                    self.pc.increment()?;
                 }
             }
             Instruction::SRE { reg_x, reg_y } => {
                 let a = self.registers.get_variable(reg_x);
                 let b = self.registers.get_variable(reg_y);
                 if a == b {
-                    self.pc.increment();
+                    self.pc.increment()?;
                 }
             }
             Instruction::SRNE { reg_x, reg_y } => {
                 let a = self.registers.get_variable(reg_x);
                 let b = self.registers.get_variable(reg_y);
                 if a != b {
-                    self.pc.increment();
+                    self.pc.increment()?;
                 }
             }
             Instruction::SETI { reg, imm8 } => {
@@ -117,16 +367,25 @@ impl CPU {
                 let a = self.registers.get_variable(reg_x);
                 let b = self.registers.get_variable(reg_y);
                 self.registers.set_variable(reg_x, a | b);
+                if self.quirks.logic {
+                    self.registers.set_flag(false);
+                }
             }
             Instruction::AND { reg_x, reg_y } => {
                 let a = self.registers.get_variable(reg_x);
                 let b = self.registers.get_variable(reg_y);
                 self.registers.set_variable(reg_x, a & b);
+                if self.quirks.logic {
+                    self.registers.set_flag(false);
+                }
             }
             Instruction::XOR { reg_x, reg_y } => {
                 let a = self.registers.get_variable(reg_x);
                 let b = self.registers.get_variable(reg_y);
                 self.registers.set_variable(reg_x, a ^ b);
+                if self.quirks.logic {
+                    self.registers.set_flag(false);
+                }
             }
             Instruction::ADD { reg_x, reg_y } => {
                 let a = self.registers.get_variable(reg_x) as u16;
@@ -153,58 +412,55 @@ impl CPU {
                 self.registers.set_variable(reg_x, result as u8);
             }
             Instruction::SHR { reg_x, reg_y } => {
-                let value = self.registers.get_variable(reg_y);
+                let source = if self.quirks.shift { reg_x } else { reg_y };
+                let value = self.registers.get_variable(source);
 
                 self.registers.set_flag((value & 0x01) > 0);
                 self.registers.set_variable(reg_x, value >> 1);
             }
             Instruction::SHL { reg_x, reg_y } => {
-                let value = self.registers.get_variable(reg_y);
+                let source = if self.quirks.shift { reg_x } else { reg_y };
+                let value = self.registers.get_variable(source);
 
                 self.registers.set_flag((value & 0x80) > 0);
                 self.registers.set_variable(reg_x, value << 1);
             }
             Instruction::JMPO { address } => {
-                let offset = self.registers.get_variable(0) as usize;
                 let target = address.get();
+                let reg = if self.quirks.jump { (target >> 8) & 0x0F } else { 0 };
+                let offset = self.registers.get_variable(reg) as usize;
                 let new = Address::from(target + offset);
                 self.pc.set(new);
             }
             Instruction::RAND { reg, imm8 } => {
-                let value = rand::thread_rng().gen_range(0..=0xFF);
+                let value = self.random.next_byte(&self.memory);
                 self.registers.set_variable(reg, value & imm8);
+                self.last_loop = None;
             }
             Instruction::DRAW { reg_x, reg_y, imm4 } => { self.draw(reg_x, reg_y, imm4); }
             Instruction::SKE { reg } => {
                 let value = self.registers.get_variable(reg) & 0x0F;
                 let key = keyboard::Key::from(value);
 
-                if let Some(pressed) = keyboard::get_key() {
-                    if pressed == key {
-                        self.pc.increment();
-                    }
+                if self.keypad.is_pressed(&key) {
+                    self.pc.increment()?;
                 }
             }
             Instruction::SKN { reg } => {
                 let value = self.registers.get_variable(reg) & 0x0F;
                 let key = keyboard::Key::from(value);
 
-                if let Some(pressed) = keyboard::get_key() {
-                    if pressed == key {
-                        return;
-                    }
+                if !self.keypad.is_pressed(&key) {
+                    self.pc.increment()?;
                 }
-                self.pc.increment();
             }
             Instruction::GTK { reg } => {
-                if let Some(key) = keyboard::get_key() {
-                    let value = key.get();
-                    if value != keyboard::INVALID_KEY {
-                        self.registers.set_variable(reg, key.get());
-                        return;
-                    }
+                if let Some(key) = self.keypad.get_pressed() {
+                    self.registers.set_variable(reg, key.get());
+                    return Ok(());
                 }
-                self.pc.decrement();
+                self.pc.decrement()?;
+                self.status = Status::WaitKey;
             }
             Instruction::ADDN { reg } => {
                 let index = self.registers.get_index();
@@ -239,7 +495,7 @@ impl CPU {
                 ];
 
                 let address = self.registers.get_index();
-                self.memory.borrow_mut().write(address, &digits);
+                self.write_memory(address, &digits);
             }
             Instruction::STM { reg } => {
                 let mut data: Vec<u8> = Vec::new();
@@ -249,52 +505,535 @@ impl CPU {
                 }
 
                 let address = self.registers.get_index();
-                // let new = Address::from(address.get() + reg as usize + 1);
-                // self.registers.set_index(new);
+                if self.quirks.memory_increment {
+                    let new = Address::from(address.get() + reg + 1);
+                    self.registers.set_index(new);
+                }
 
-                self.memory.borrow_mut().write(address, &data);
+                self.write_memory(address, &data);
             }
             Instruction::LDM { reg } => {
                 let address = self.registers.get_index();
-                // let new = Address::from(address.get() + reg as usize + 1);
-                // self.registers.set_index(new);
+                if self.quirks.memory_increment {
+                    let new = Address::from(address.get() + reg + 1);
+                    self.registers.set_index(new);
+                }
 
-                let data = self.memory.borrow().read(address, reg + 1);
-                assert_eq!(data.len(), reg + 1);
+                let data = self.memory.read(address, reg + 1);
+                if data.len() != reg + 1 {
+                    return Err(ErrorKind::EndOfMemory);
+                }
 
                 for (i, value) in data.iter().enumerate() {
                     self.registers.set_variable(i, *value);
                 }
             }
-            _ => { panic!("Tried to run instruction at {}; found {}", self.pc.get(), instruction); }
+            Instruction::INVALID { opcode } => {
+                let extension = self.extensions.as_ref().and_then(|extensions| extensions.decode(opcode));
+                let Some(extension) = extension else {
+                    return Err(ErrorKind::InvalidInstruction { opcode });
+                };
+
+                let mut context = Context {
+                    registers: &mut self.registers,
+                    pc: &mut self.pc,
+                    memory: &mut self.memory,
+                    framebuffer: &mut self.framebuffer,
+                };
+                extension.run(&mut context, opcode)?;
+                self.last_loop = None;
+            }
+        };
+
+        return Ok(());
+    }
+
+    /// Run a batch of operation cycles: statically recompiled code, or a
+    /// compiled block when the recompiler is on, if there is some for the
+    /// program counter, or a single cycle. The batch stops after
+    /// `max_instructions`, or once it took `max_cycles` machine cycles. Traced
+    /// runs, and runs with the debug console or debugging annotations, always
+    /// go through the interpreter.
+    pub fn run_batch(&mut self, max_instructions: u32, max_cycles: i64) -> Result<Batch, Error> {
+        if self.tracer.is_none() && self.console.is_none() && self.debug.is_none() {
+            let batch = self.run_static(max_instructions, max_cycles)
+                .or_else(|| self.run_block(max_instructions, max_cycles));
+            if let Some(batch) = batch {
+                return batch;
+            }
+        }
+
+        let step = self.cycle()?;
+        return Ok(Batch { instructions: 1, cycles: step.cycles, status: step.status });
+    }
+
+    /// Perform one operation cycle (fetch-decode-execute), unless the run
+    /// stops at a breakpoint first.
+    pub fn cycle(&mut self) -> Result<Step, Error> {
+        let address = self.pc.get();
+        let error = |kind| Error { address, kind };
+
+        if self.check_debug(address.get()).map_err(error)? {
+            return Ok(Step { cycles: 0, status: Status::Breakpoint });
+        }
+
+        let (opcode, instruction) = self.fetch().map_err(error)?;
+        let mut cycles = timing::vip_cycles(&instruction, &self.registers);
+        let traced = match &self.tracer {
+            Some(tracer) => { tracer.filter().accepts(address.get(), &instruction) }
+            None => { false }
+        };
+        let text = if traced { Some(self.describe(&instruction)) } else { None };
+
+        self.routine_cycles = 0;
+        self.status = Status::Running;
+        self.execute(instruction).map_err(error)?;
+        self.store_variables();
+        self.flush_console();
+        if opcode == console::DEBUG_OPCODE && self.console.is_some() {
+            let registers = self.dump_registers();
+            if let Some(console) = &mut self.console {
+                console.dump(address.get(), &registers);
+            }
+        }
+
+        if let Some(instruction) = text {
+            self.trace(address, opcode, instruction);
+        }
+        self.cycles += 1;
+
+        cycles += self.routine_cycles;
+        cycles += timing::skip_cycles(&instruction, self.pc.get().get() == address.get() + 4);
+
+        return Ok(Step { cycles, status: self.status });
+    }
+
+    /// Log the bytes written to the console port, whichever part of the
+    /// machine wrote them.
+    fn flush_console(&mut self) {
+        let writes = self.memory.take_port_writes();
+        if let Some(console) = &mut self.console {
+            for byte in writes {
+                console.write(byte);
+            }
+        }
+    }
+
+    /// Describe the `instruction` for the trace, using the names of custom
+    /// instructions and labels where known.
+    fn describe(&self, instruction: &Instruction) -> String {
+        if let Some(text) = self.debug.as_ref().and_then(|debug| debug.describe(instruction)) {
+            return text;
+        }
+        match &self.extensions {
+            Some(extensions) => { extensions.format(instruction) }
+            None => { instruction.to_string() }
+        }
+    }
+
+    /// Describe where the program counter is: its address, along with the
+    /// label and source line there when the debug info knows them.
+    pub fn location(&self) -> String {
+        let address = self.pc.get().get();
+        match &self.debug {
+            Some(debug) => { debug.locate(address) }
+            None => { format!("{:03x}", address) }
+        }
+    }
+
+    /// Check the assertions for the instruction at `address`, before it runs.
+    ///
+    /// returns Whether the run stops at a breakpoint there. It only stops
+    /// once: resuming goes past it.
+    fn check_debug(&mut self, address: usize) -> Result<bool, ErrorKind> {
+        let resumed = self.breakpoint.take() == Some(address);
+        let Some(debug) = &self.debug else {
+            return Ok(false);
+        };
+
+        let mut stop = false;
+        for check in debug.checks(address) {
+            match check {
+                Check::Assert(condition) => {
+                    if !condition.holds(|operand| self.operand(operand)) {
+                        return Err(ErrorKind::AssertionFailed { condition: condition.to_string() });
+                    }
+                }
+                Check::Break => { stop = !resumed; }
+            }
+        }
+
+        if stop {
+            self.breakpoint = Some(address);
+        }
+        return Ok(stop);
+    }
+
+    /// Get the value of an `operand` of a debugging assertion.
+    fn operand(&self, operand: Operand) -> usize {
+        match operand {
+            Operand::Variable(index) => { self.registers.get_variable(index) as usize }
+            Operand::Index => { self.registers.get_index().get() }
+            Operand::Delay => { self.delay_timer.get() as usize }
+            Operand::Sound => { self.sound_timer.get() as usize }
+            Operand::Number(value) => { value }
+        }
+    }
+
+    /// Record the `instruction` that just ran from `address` in the trace,
+    /// along with the state it left behind.
+    fn trace(&mut self, address: Address, opcode: u16, instruction: String) {
+        let mut variables = [0; VARIABLE_COUNT];
+        for (i, variable) in variables.iter_mut().enumerate() {
+            *variable = self.registers.get_variable(i);
+        }
+
+        let memory = match &self.tracer {
+            Some(tracer) if tracer.wants_memory() => {
+                Some(self.memory.read(Address::from(0), memory::MEMORY_SIZE))
+            }
+            _ => { None }
         };
+
+        let source = self.debug.as_ref()
+            .and_then(|debug| debug.line(address.get()))
+            .map(|location| location.to_string());
+        let entry = TraceEntry {
+            cycle: self.cycles,
+            pc: address.get(),
+            opcode,
+            instruction,
+            source,
+            variables,
+            index: self.registers.get_index().get(),
+            delay: self.delay_timer.get(),
+            sound: self.sound_timer.get(),
+            memory,
+        };
+        if let Some(tracer) = &mut self.tracer {
+            tracer.record(entry);
+        }
     }
 
-    /// Perform one operation cycle (fetch-decode-execute).
-    fn cycle(&mut self) {
-        let opcode = self.fetch();
-        let instruction = Instruction::from(opcode);
-        self.execute(instruction);
+    /// Write `data` to memory, at the given `address`, keeping whatever is
+    /// mapped to that memory up to date.
+    pub fn write_memory(&mut self, address: Address, data: &[u8]) {
+        self.memory.write(address, data);
+        self.last_loop = None;
+
+        if let Some(base) = self.layout.variables {
+            let start = address.get();
+            if start < base + VARIABLE_COUNT && base < start + data.len() {
+                self.load_variables();
+            }
+        }
     }
 
-    /// Run the cpu forever, I guess.
-    pub fn run(&mut self) {
-        loop {
-            self.cycle();
-            sleep(INSTRUCTION_PAUSE);
+    /// Check whether jumping to `target` closes an idle loop, and mark the
+    /// CPU as idle if it does. Only backward jumps are considered.
+    fn check_loop(&mut self, target: Address) {
+        let address = self.pc.get().get() - 2;
+        if target.get() > address {
+            return;
+        }
+
+        let mut variables = [0; VARIABLE_COUNT];
+        for (i, value) in variables.iter_mut().enumerate() {
+            *value = self.registers.get_variable(i);
         }
+        let state = LoopState {
+            address: target.get(),
+            variables,
+            index: self.registers.get_index().get(),
+            delay: self.delay_timer.get(),
+            sound: self.sound_timer.get(),
+            depth: self.stack.depth(),
+        };
+
+        if self.last_loop.as_ref() == Some(&state) {
+            self.status = Status::Idle;
+        }
+        self.last_loop = Some(state);
+    }
+
+    /// Copy the variable registers from the interpreter work area, if the
+    /// memory layout keeps them there.
+    fn load_variables(&mut self) {
+        if let Some(base) = self.layout.variables {
+            let values = self.memory.read(Address::from(base), VARIABLE_COUNT);
+            for (i, value) in values.iter().enumerate() {
+                self.registers.set_variable(i, *value);
+            }
+        }
+    }
+
+    /// Copy the variable registers to the interpreter work area, if the
+    /// memory layout keeps them there.
+    fn store_variables(&mut self) {
+        if let Some(base) = self.layout.variables {
+            let mut values = [0; VARIABLE_COUNT];
+            for (i, value) in values.iter_mut().enumerate() {
+                *value = self.registers.get_variable(i);
+            }
+            // Only write on changes, so that the console and the decode
+            // cache only see the variables that the instruction set.
+            if self.memory.read(Address::from(base), VARIABLE_COUNT) != values {
+                self.memory.write(Address::from(base), &values);
+            }
+        }
+    }
+
+    /// Run the 1802 machine code routine at `address`, the way the VIP
+    /// interpreter does: with R3 as the program counter, until the routine
+    /// hands control back with `SEP R4`. The routine finds the variable
+    /// registers in the interpreter work area and I in RA.
+    ///
+    /// returns The amount of machine cycles the routine ran for.
+    fn call_machine_code(&mut self, address: Address) -> Result<u32, ErrorKind> {
+        if self.layout.variables.is_none() {
+            return Err(ErrorKind::NoMachineCode);
+        }
+        self.store_variables();
+
+        let mut cpu = Cdp1802::new();
+        cpu.set_register(0x2, MACHINE_STACK);
+        cpu.set_register(0x3, address.get() as u16);
+        cpu.set_register(0x5, self.pc.get().get() as u16);
+        cpu.set_register(0xA, self.registers.get_index().get() as u16);
+        if let Some(base) = self.layout.display {
+            cpu.set_register(0xB, base as u16);
+        }
+        cpu.set_x(2);
+        cpu.set_p(3);
+
+        let mut cycles = 0;
+        while cpu.get_p() != RETURN_REGISTER {
+            if cycles > MACHINE_CODE_LIMIT {
+                return Err(ErrorKind::MachineCodeTimeout);
+            }
+            cycles += cpu.step(&mut self.memory);
+            // Nothing would wake it up: the interpreter isn't there to
+            // handle the display interrupt.
+            if cpu.is_idle() {
+                return Err(ErrorKind::MachineCodeIdle);
+            }
+        }
+
+        self.registers.set_index(Address::from(cpu.get_register(0xA) as usize));
+        self.load_variables();
+        return Ok(cycles);
     }
 
     fn draw(&mut self, x_reg: usize, y_reg: usize, height: u8) {
         assert!(height <= 15);
         let addr = self.registers.get_index();
-        let sprite_data = self.memory.borrow().read(addr, height as usize);
+        let sprite_data = self.memory.read(addr, height as usize);
         let sprite = Sprite::from(sprite_data);
         let x = self.registers.get_variable(x_reg);
         let y = self.registers.get_variable(y_reg);
 
         self.registers.set_flag(false);
-        let overflow = self.display.add_sprite(&sprite, x as usize, y as usize);
+        let overflow = self.framebuffer.add_sprite(&mut self.memory, &sprite, x as usize, y as usize, self.quirks.wrap);
         self.registers.set_flag(overflow);
+        self.last_loop = None;
+        if self.quirks.display_wait {
+            self.status = Status::WaitVblank;
+        }
+    }
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    /// Set up a CPU running `program`, on the given `platform`.
+    fn setup(program: &[u8], platform: &str) -> CPU {
+        return setup_with(program, &["--platform", platform]);
+    }
+
+    /// Set up a CPU running `program`, with the given extra command line
+    /// `options`.
+    fn setup_with(program: &[u8], options: &[&str]) -> CPU {
+        let args: Vec<String> = ["test.ch8", "--seed", "0"].iter().chain(options)
+            .map(|arg| arg.to_string()).collect();
+        let rom = RomImage::from(program.to_vec());
+        let settings = Config::from_args(&args).unwrap().settings(&rom);
+        return CPU::new(&rom, &settings);
+    }
+
+    #[test]
+    fn machine_code_routines_see_the_interpreter_state() {
+        // Add 5 to v0 through its copy in the work area, set the low byte of
+        // I through RA, and return with SEP R4.
+        let routine = [
+            0xF8, 0x0E, 0xB6, 0xF8, 0xF0, 0xA6,  // R6 = 0xEF0
+            0x06, 0xFC, 0x05, 0x56,  // M(R6) += 5
+            0xF8, 0x34, 0xAA,  // RA.0 = 0x34
+            0xD4,
+        ];
+        let mut cpu = setup(&routine, "vip");
+        cpu.registers.set_variable(0, 0x07);
+        cpu.registers.set_index(Address::from(0x300));
+        assert!(cpu.execute(Instruction::from(0x0200)).is_ok());
+
+        assert_eq!(cpu.registers.get_variable(0), 0x0C);
+        assert_eq!(cpu.registers.get_index().get(), 0x334);
+        assert!(cpu.routine_cycles > 0);
+    }
+
+    #[test]
+    fn taken_skips_and_routines_add_to_the_cycles() {
+        let mut cpu = setup(&[0x30, 0x00, 0x30, 0x01, 0x30, 0x01, 0x02, 0x0A, 0x00, 0x00, 0xC4, 0xD4], "vip");
+        let base = timing::vip_cycles(&Instruction::from(0x3000), &cpu.registers);
+        assert_eq!(cpu.cycle().ok().map(|step| step.cycles), Some(base + timing::SKIP_CYCLES));
+        assert_eq!(cpu.cycle().ok().map(|step| step.cycles), Some(base));
+
+        // NOP and SEP R4 take 3 and 2 machine cycles.
+        let base = timing::vip_cycles(&Instruction::from(0x020A), &cpu.registers);
+        assert_eq!(cpu.cycle().ok().map(|step| step.cycles), Some(base + 3 + 2));
+    }
+
+    #[test]
+    fn jumps_past_the_next_instruction_are_not_skips() {
+        let mut cpu = setup(&[0x12, 0x04], "vip");
+        let base = timing::vip_cycles(&Instruction::from(0x1204), &cpu.registers);
+        assert_eq!(cpu.cycle().ok().map(|step| step.cycles), Some(base));
+        assert_eq!(cpu.pc.get().get(), 0x204);
+    }
+
+    /// Run `count` instructions, and get the status of the last one.
+    fn run(cpu: &mut CPU, count: usize) -> Option<Status> {
+        let mut status = None;
+        for _ in 0..count {
+            status = cpu.cycle().ok().map(|step| step.status);
+        }
+        return status;
+    }
+
+    #[test]
+    fn polling_the_delay_timer_is_idle() {
+        // Wait for the delay timer to run out.
+        let mut cpu = setup(&[0x60, 0x05, 0xF0, 0x15, 0xF1, 0x07, 0x31, 0x00, 0x12, 0x04], "chip8");
+        assert!(run(&mut cpu, 5) == Some(Status::Running));
+        // The second time around, nothing changed since the last jump back.
+        assert!(run(&mut cpu, 3) == Some(Status::Idle));
+
+        // Once the timer changes, the loop goes on.
+        cpu.tick_timers();
+        assert!(run(&mut cpu, 3) == Some(Status::Running));
+    }
+
+    #[test]
+    fn loops_that_change_state_are_not_idle() {
+        let mut cpu = setup(&[0x70, 0x01, 0x12, 0x00], "chip8");
+        for _ in 0..10 {
+            assert!(run(&mut cpu, 2) == Some(Status::Running));
+        }
+    }
+
+    #[test]
+    fn key_waits_stay_on_the_instruction() {
+        let mut cpu = setup(&[0xF3, 0x0A], "chip8");
+        assert!(run(&mut cpu, 1) == Some(Status::WaitKey));
+        assert_eq!(cpu.pc.get().get(), 0x200);
+
+        let mut pressed = [false; 0x10];
+        pressed[0xB] = true;
+        cpu.keypad_mut().set(pressed);
+        assert!(run(&mut cpu, 1) == Some(Status::Running));
+        assert_eq!(cpu.registers.get_variable(3), 0xB);
+        assert_eq!(cpu.pc.get().get(), 0x202);
+    }
+
+    #[test]
+    fn machine_code_routines_that_never_return_are_reported() {
+        let mut cpu = setup(&[0x00], "vip");
+        assert!(matches!(cpu.execute(Instruction::from(0x0200)), Err(ErrorKind::MachineCodeIdle)));
+
+        let mut cpu = setup(&[0x30, 0x00], "vip");
+        assert!(matches!(cpu.execute(Instruction::from(0x0200)), Err(ErrorKind::MachineCodeTimeout)));
+
+        let mut cpu = setup(&[0xD4], "chip8");
+        assert!(matches!(cpu.execute(Instruction::from(0x0200)), Err(ErrorKind::NoMachineCode)));
+    }
+
+    #[test]
+    fn the_console_logs_writes_of_the_instruction_set() {
+        // STM v0 at 0x300, then BCD of 123 there.
+        let mut cpu = setup_with(&[0x60, 0x41, 0xA3, 0x00, 0xF0, 0x55, 0x60, 0x7B, 0xF0, 0x33],
+                                 &["--console", "300", "--console-format", "hex"]);
+        run(&mut cpu, 3);
+        assert_eq!(cpu.take_console(), "41\n");
+        run(&mut cpu, 2);
+        assert_eq!(cpu.take_console(), "01\n");
+    }
+
+    #[test]
+    fn the_console_logs_stack_pushes_in_memory() {
+        // The VIP stack starts at 0xEA0, and CALL pushes 0x202 there.
+        let mut cpu = setup_with(&[0x22, 0x04, 0x00, 0x00, 0x00, 0xEE],
+                                 &["--platform", "vip", "--console", "ea1", "--console-format", "hex"]);
+        run(&mut cpu, 1);
+        assert_eq!(cpu.take_console(), "02\n");
+    }
+
+    #[test]
+    fn the_console_logs_machine_code_writes() {
+        // Store 'A' at 0x300 through R6, and return with SEP R4.
+        let routine = [
+            0x02, 0x02,
+            0xF8, 0x03, 0xB6, 0xF8, 0x00, 0xA6,  // R6 = 0x300
+            0xF8, 0x41, 0x56,  // M(R6) = 0x41
+            0xD4,
+        ];
+        let mut cpu = setup_with(&routine, &["--platform", "vip", "--console", "300"]);
+        run(&mut cpu, 1);
+        assert_eq!(cpu.take_console(), "A");
+    }
+
+    #[test]
+    fn the_console_logs_custom_instruction_writes() {
+        let mut extensions = Extensions::new();
+        extensions.register("OUT", 0x5001, 0xF0FF, &[instructions::Field::X], |context, operands| {
+            let value = context.registers.get_variable(operands[0] as usize);
+            context.memory.write(Address::from(0x300), &[value]);
+            return Ok(());
+        }).unwrap();
+        let mut cpu = setup_with(&[0x63, 0x0A, 0x53, 0x01], &["--console", "300"]);
+        cpu.set_extensions(Arc::new(extensions));
+        run(&mut cpu, 2);
+        assert_eq!(cpu.take_console(), "\n");
+    }
+
+    #[test]
+    fn the_debug_opcode_dumps_the_registers_to_the_console() {
+        let mut cpu = setup_with(&[0x60, 0x07, 0x8F, 0xF0], &["--console", "300"]);
+        run(&mut cpu, 2);
+        let output = cpu.take_console();
+        assert!(output.starts_with("debug at 202:\nv0=07 v1=00"));
+        assert!(output.contains("PC=204"));
+    }
+
+    #[test]
+    fn the_debug_opcode_does_nothing_without_the_console() {
+        let mut cpu = setup(&[0x6F, 0x07, 0x8F, 0xF0], "chip8");
+        run(&mut cpu, 2);
+        assert_eq!(cpu.take_console(), "");
+        assert_eq!(cpu.registers.get_variable(0xF), 0x07);
+        assert_eq!(cpu.pc.get().get(), 0x204);
+    }
+
+    #[test]
+    fn failed_assertions_stop_the_run() {
+        let mut cpu = setup(&[0x60, 0x04, 0x60, 0x05, 0x60, 0x06], "chip8");
+        cpu.set_debug(Arc::new(DebugInfo::parse("202 assert v0 == 4\n204 assert v0 != 5").unwrap()));
+        assert!(cpu.cycle().is_ok());
+        assert!(cpu.cycle().is_ok());
+        let error = cpu.cycle().err().unwrap();
+        assert_eq!(error.address.get(), 0x204);
+        assert!(matches!(error.kind, ErrorKind::AssertionFailed { condition } if condition == "v0 != 5"));
     }
 }
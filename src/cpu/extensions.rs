@@ -0,0 +1,134 @@
+// ----- Imports ----- //
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::cpu::instructions;
+use crate::cpu::instructions::{Definition, Field, Instruction};
+use crate::display::Framebuffer;
+use crate::error::ErrorKind;
+use crate::memory::Memory;
+use crate::registers::{PC, Registers};
+
+// ----- Types ----- //
+
+/// Runs a custom instruction, given the machine state and the values of its
+/// operands, in the order of its fields.
+pub type Handler = Box<dyn Fn(&mut Context, &[u16]) -> Result<(), ErrorKind> + Send + Sync>;
+
+// ----- Structs ----- //
+
+/// The parts of the machine custom instructions can work with. The program
+/// counter already points to the next instruction.
+pub struct Context<'a> {
+    /// The variable registers, and `I`.
+    pub registers: &'a mut Registers,
+    pub pc: &'a mut PC,
+    pub memory: &'a mut Memory,
+    /// The display, whose pixels may live in `memory`.
+    pub framebuffer: &'a mut Framebuffer,
+}
+
+/// A custom instruction, decoded from opcodes the instruction set leaves
+/// invalid.
+pub struct Extension {
+    definition: Definition,
+    handler: Handler,
+}
+
+/// Registry of custom instructions. The machine runs them in place of invalid
+/// instructions, and the assembler and disassembler know their mnemonics.
+pub struct Extensions {
+    list: Vec<Extension>,
+}
+
+impl Extension {
+    /// Get the row describing the instruction, as for built-in ones.
+    pub fn definition(&self) -> &Definition {
+        return &self.definition;
+    }
+
+    /// Get the values of the operands out of the `opcode`.
+    pub fn operands(&self, opcode: u16) -> Vec<u16> {
+        return self.definition.fields.iter().map(|field| field.extract(opcode)).collect();
+    }
+
+    /// Run the instruction encoded as `opcode`.
+    pub fn run(&self, context: &mut Context, opcode: u16) -> Result<(), ErrorKind> {
+        return (self.handler)(context, &self.operands(opcode));
+    }
+}
+
+impl Extensions {
+    pub fn new() -> Self {
+        return Extensions { list: Vec::new() };
+    }
+
+    /// Register the custom instruction `name`, decoded from the opcodes
+    /// matching `pattern` in the bits set in `mask`, with operands in
+    /// `fields`. It can only take opcodes that are neither part of the
+    /// instruction set nor of another custom instruction.
+    pub fn register(&mut self, name: &'static str, pattern: u16, mask: u16, fields: &'static [Field],
+                    handler: impl Fn(&mut Context, &[u16]) -> Result<(), ErrorKind> + Send + Sync + 'static)
+                    -> Result<(), String> {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) {
+            return Err(format!("Invalid mnemonic '{}'", name));
+        }
+        if matches!(name, "BYTE" | "INVALID") || instructions::find(name).is_some() || self.find(name).is_some() {
+            return Err(format!("{} is already an instruction", name));
+        }
+        if pattern & !mask != 0 {
+            return Err(format!("The pattern of {} has bits outside of its mask", name));
+        }
+        if fields.iter().any(|field| field.place(field.max()) & mask != 0) {
+            return Err(format!("The operands of {} overlap its mask", name));
+        }
+        if let Some(other) = self.list.iter().find(|other| (other.definition.pattern ^ pattern) & other.definition.mask & mask == 0) {
+            return Err(format!("{} takes opcodes of {}", name, other.definition.name));
+        }
+
+        // Walk every opcode matching the pattern, by counting through the
+        // bits outside the mask.
+        let mut free = !mask;
+        loop {
+            let opcode = pattern | free;
+            let instruction = Instruction::from(opcode);
+            if !matches!(instruction, Instruction::INVALID { .. }) {
+                return Err(format!("{} takes opcode {:04x}, which is {}", name, opcode, instruction));
+            }
+            if free == 0 {
+                break;
+            }
+            free = (free - 1) & !mask;
+        }
+
+        self.list.push(Extension {
+            definition: Definition { name, pattern, mask, fields, platforms: instructions::EVERYWHERE },
+            handler: Box::new(handler),
+        });
+        return Ok(());
+    }
+
+    /// Find the custom instruction the `opcode` decodes to, if any.
+    pub fn decode(&self, opcode: u16) -> Option<&Extension> {
+        return self.list.iter().find(|extension| opcode & extension.definition.mask == extension.definition.pattern);
+    }
+
+    /// Find the custom instruction with the given `name`.
+    pub fn find(&self, name: &str) -> Option<&Extension> {
+        return self.list.iter().find(|extension| extension.definition.name == name);
+    }
+
+    /// Describe the `instruction`, using the mnemonic of the custom
+    /// instruction it is, if any.
+    pub fn format(&self, instruction: &Instruction) -> String {
+        if let Instruction::INVALID { opcode } = instruction {
+            if let Some(extension) = self.decode(*opcode) {
+                return extension.definition.format(&extension.operands(*opcode));
+            }
+        }
+        return instruction.to_string();
+    }
+}
@@ -1,171 +1,342 @@
 // ----- Imports ----- //
 
-use std::fmt::Formatter;
+use alloc::{format, vec};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Formatter;
 use crate::cpu::instructions::Instruction::{*};
 use crate::memory::address::Address;
+use crate::platform::Platform;
+
+// ----- Macros ----- //
+
+/// Declare the instruction set. Each row gives the name of an instruction, its
+/// operands, the opcode pattern and mask it decodes from, and the platforms it
+/// exists on. The rows generate the `Instruction` enum, its decoder and the
+/// `INSTRUCTION_SET` table everything else (encoding, formatting, assembling)
+/// is driven by.
+macro_rules! instruction_set {
+    ( $( $name:ident { $($field:ident),* } = $pattern:literal / $mask:literal on $platforms:expr; )* ) => {
+        #[derive(Copy, Clone)]
+        pub enum Instruction {
+            $( $name { $($field: instruction_set!(@type $field)),* }, )*
+            /// Any opcode that isn't part of the instruction set.
+            INVALID { opcode: u16 },
+        }
+
+        /// Every instruction, in decoding order: an opcode decodes to the first
+        /// row it matches.
+        pub const INSTRUCTION_SET: &[Definition] = &[
+            $(
+                Definition {
+                    name: stringify!($name),
+                    pattern: $pattern,
+                    mask: $mask,
+                    fields: &[$(instruction_set!(@field $field)),*],
+                    platforms: $platforms,
+                },
+            )*
+        ];
+
+        impl Instruction {
+            /// Get the name of the instruction, without its operands.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $( $name { .. } => { stringify!($name) } )*
+                    INVALID { .. } => { "INVALID" }
+                }
+            }
+
+            /// Get the values of the operands, in the order of the fields of
+            /// the instruction's definition.
+            pub fn operands(&self) -> Vec<u16> {
+                match self {
+                    $( $name { $($field),* } => { vec![$(instruction_set!(@value $field, $field)),*] } )*
+                    INVALID { .. } => { Vec::new() }
+                }
+            }
+
+            fn decode(opcode: u16) -> Self {
+                $(
+                    if opcode & $mask == $pattern {
+                        return $name { $($field: instruction_set!(@get $field, opcode)),* };
+                    }
+                )*
+                return INVALID { opcode };
+            }
+        }
+    };
+
+    (@type address) => { Address };
+    (@type reg) => { usize };
+    (@type reg_x) => { usize };
+    (@type reg_y) => { usize };
+    (@type imm8) => { u8 };
+    (@type imm4) => { u8 };
+
+    (@field address) => { Field::Address };
+    (@field reg) => { Field::X };
+    (@field reg_x) => { Field::X };
+    (@field reg_y) => { Field::Y };
+    (@field imm8) => { Field::Byte };
+    (@field imm4) => { Field::Nibble };
+
+    (@get address, $opcode:expr) => { Address::from(Field::Address.extract($opcode) as usize) };
+    (@get imm8, $opcode:expr) => { Field::Byte.extract($opcode) as u8 };
+    (@get imm4, $opcode:expr) => { Field::Nibble.extract($opcode) as u8 };
+    (@get $field:ident, $opcode:expr) => { instruction_set!(@field $field).extract($opcode) as usize };
+
+    (@value address, $value:ident) => { $value.get() as u16 };
+    (@value $field:ident, $value:ident) => { *$value as u16 };
+}
+
+// ----- Consts ----- //
+
+pub(crate) const EVERYWHERE: &[Platform] = &[Platform::Chip8, Platform::CosmacVip, Platform::SuperChip, Platform::XoChip];
+const VIP: &[Platform] = &[Platform::CosmacVip];
 
 // ----- Structs ----- //
 
-pub enum Instruction {
-    CLS(),
+instruction_set! {
+    CLS {} = 0x00E0 / 0xFFFF on EVERYWHERE;
+    RET {} = 0x00EE / 0xFFFF on EVERYWHERE;
+    SYS { address } = 0x0000 / 0xF000 on VIP;  // Call a machine code routine
 
     // Flow control:
-    RET(),
-    JUMP { address: Address },
-    CALL { address: Address },
-    JMPO { address: Address },
-    SETN { address: Address },
-    ADDN { reg: usize },
+    JUMP { address } = 0x1000 / 0xF000 on EVERYWHERE;
+    CALL { address } = 0x2000 / 0xF000 on EVERYWHERE;
+    JMPO { address } = 0xB000 / 0xF000 on EVERYWHERE;
+    SETN { address } = 0xA000 / 0xF000 on EVERYWHERE;
+    ADDN { reg } = 0xF01E / 0xF0FF on EVERYWHERE;
 
     // Skip instructions:
-    SEQ { reg: usize, imm8: u8 },
-    SNE { reg: usize, imm8: u8 },
-    SRE { reg_x: usize, reg_y: usize },
-    SRNE { reg_x: usize, reg_y: usize },
+    SEQ { reg, imm8 } = 0x3000 / 0xF000 on EVERYWHERE;
+    SNE { reg, imm8 } = 0x4000 / 0xF000 on EVERYWHERE;
+    SRE { reg_x, reg_y } = 0x5000 / 0xF00F on EVERYWHERE;
+    SRNE { reg_x, reg_y } = 0x9000 / 0xF00F on EVERYWHERE;
 
     // Arithmetics:
-    SETI { reg: usize, imm8: u8 },
-    ADDI { reg: usize, imm8: u8 },
-    SET { reg_x: usize, reg_y: usize },
-    OR { reg_x: usize, reg_y: usize },
-    AND { reg_x: usize, reg_y: usize },
-    XOR { reg_x: usize, reg_y: usize },
-    ADD { reg_x: usize, reg_y: usize },
-    SUB { reg_x: usize, reg_y: usize },
-    NSUB { reg_x: usize, reg_y: usize },
-    SHL { reg_x: usize, reg_y: usize },
-    SHR { reg_x: usize, reg_y: usize },
+    SETI { reg, imm8 } = 0x6000 / 0xF000 on EVERYWHERE;
+    ADDI { reg, imm8 } = 0x7000 / 0xF000 on EVERYWHERE;
+    SET { reg_x, reg_y } = 0x8000 / 0xF00F on EVERYWHERE;
+    OR { reg_x, reg_y } = 0x8001 / 0xF00F on EVERYWHERE;
+    AND { reg_x, reg_y } = 0x8002 / 0xF00F on EVERYWHERE;
+    XOR { reg_x, reg_y } = 0x8003 / 0xF00F on EVERYWHERE;
+    ADD { reg_x, reg_y } = 0x8004 / 0xF00F on EVERYWHERE;
+    SUB { reg_x, reg_y } = 0x8005 / 0xF00F on EVERYWHERE;
+    SHR { reg_x, reg_y } = 0x8006 / 0xF00F on EVERYWHERE;
+    NSUB { reg_x, reg_y } = 0x8007 / 0xF00F on EVERYWHERE;
+    SHL { reg_x, reg_y } = 0x800E / 0xF00F on EVERYWHERE;
 
     // Timers
-    STD { reg: usize },
-    RDD { reg: usize },
-    STS { reg: usize },
+    STD { reg } = 0xF015 / 0xF0FF on EVERYWHERE;
+    RDD { reg } = 0xF007 / 0xF0FF on EVERYWHERE;
+    STS { reg } = 0xF018 / 0xF0FF on EVERYWHERE;
 
-    RAND { reg: usize, imm8: u8 },
-    DRAW { reg_x: usize, reg_y: usize, imm4: u8 },
+    RAND { reg, imm8 } = 0xC000 / 0xF000 on EVERYWHERE;
+    DRAW { reg_x, reg_y, imm4 } = 0xD000 / 0xF000 on EVERYWHERE;
 
     // Key input
-    SKE { reg: usize },
-    SKN { reg: usize },
-    GTK { reg: usize },
+    SKE { reg } = 0xE09E / 0xF0FF on EVERYWHERE;
+    SKN { reg } = 0xE0A1 / 0xF0FF on EVERYWHERE;
+    GTK { reg } = 0xF00A / 0xF0FF on EVERYWHERE;
+
+    FONT { reg } = 0xF029 / 0xF0FF on EVERYWHERE;
+    BCD { reg } = 0xF033 / 0xF0FF on EVERYWHERE;
+    STM { reg } = 0xF055 / 0xF0FF on EVERYWHERE;
+    LDM { reg } = 0xF065 / 0xF0FF on EVERYWHERE;
+}
 
-    FONT { reg: usize },
-    BCD { reg: usize },
-    STM { reg: usize },
-    LDM { reg: usize },
+/// A row of the instruction set.
+pub struct Definition {
+    pub name: &'static str,
+    pub pattern: u16,
+    /// Bits of the opcode that are fixed by the `pattern`.
+    pub mask: u16,
+    pub fields: &'static [Field],
+    pub platforms: &'static [Platform],
+}
 
-    INVALID { opcode: u16 },
+/// An operand, and the bits of the opcode it is stored in.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Field {
+    Address,
+    X,
+    Y,
+    Byte,
+    Nibble,
+}
+
+impl Definition {
+    pub fn is_available(&self, platform: Platform) -> bool {
+        return self.platforms.contains(&platform);
+    }
+
+    /// Build the opcode for this instruction, given the `operands` for each
+    /// of its fields.
+    pub fn encode(&self, operands: &[u16]) -> u16 {
+        return self.fields.iter()
+            .zip(operands)
+            .fold(self.pattern, |opcode, (field, &value)| opcode | field.place(value));
+    }
+
+    /// Describe this instruction with the given `operands`, as in `SETI(v0, 2a)`.
+    pub fn format(&self, operands: &[u16]) -> String {
+        let operands: Vec<String> = self.fields.iter()
+            .zip(operands)
+            .map(|(field, &value)| field.format(value))
+            .collect();
+        return format!("{}({})", self.name, operands.join(", "));
+    }
+}
+
+impl Field {
+    fn mask(&self) -> u16 {
+        match self {
+            Field::Address => { 0x0FFF }
+            Field::X => { 0x0F00 }
+            Field::Y => { 0x00F0 }
+            Field::Byte => { 0x00FF }
+            Field::Nibble => { 0x000F }
+        }
+    }
+
+    fn shift(&self) -> u16 {
+        match self {
+            Field::X => { 8 }
+            Field::Y => { 4 }
+            _ => { 0 }
+        }
+    }
+
+    /// Get the largest value the field holds.
+    pub fn max(&self) -> u16 {
+        return self.mask() >> self.shift();
+    }
+
+    /// Get the value of the field out of the `opcode`.
+    pub fn extract(&self, opcode: u16) -> u16 {
+        return (opcode & self.mask()) >> self.shift();
+    }
+
+    /// Move the `value` in place, to be combined with the rest of an opcode.
+    pub fn place(&self, value: u16) -> u16 {
+        return (value << self.shift()) & self.mask();
+    }
+
+    pub fn format(&self, value: u16) -> String {
+        match self {
+            Field::Address => { format!("{:03x}", value) }
+            Field::X | Field::Y => { format!("v{:x}", value) }
+            Field::Byte => { format!("{:02x}", value) }
+            Field::Nibble => { format!("{:x}", value) }
+        }
+    }
+
+    /// Parse a value for the field, written the way `format` writes it (an
+    /// optional `0x` prefix is allowed on numbers).
+    pub fn parse(&self, text: &str) -> Option<u16> {
+        let text = text.trim();
+        let digits = match self {
+            Field::X | Field::Y => { text.strip_prefix('v').or(text.strip_prefix('V'))? }
+            _ => { text.strip_prefix("0x").unwrap_or(text) }
+        };
+
+        let value = u16::from_str_radix(digits, 16).ok()?;
+        if value > self.max() {
+            return None;
+        }
+        return Some(value);
+    }
+}
+
+impl Instruction {
+    /// Get the row of the instruction set for this instruction, if it is
+    /// valid.
+    pub fn definition(&self) -> Option<&'static Definition> {
+        return find(self.name());
+    }
+
+    /// Check whether the instruction exists on the given `platform`.
+    pub fn is_available(&self, platform: Platform) -> bool {
+        return self.definition().is_some_and(|definition| definition.is_available(platform));
+    }
 }
 
 impl From<u16> for Instruction {
     fn from(opcode: u16) -> Self {
-        // Get all possible values from opcode.
-        let inst_group = (opcode >> 12) & 0x0F;
-        let imm4: u8 = (opcode & 0x0F) as u8;
-        let imm8: u8 = (opcode & 0xFF) as u8;
-        let imm12: u16 = opcode & 0x0FFF;
-        let address = Address::from(imm12 as usize);
-        let reg_x: usize = ((opcode >> 8) & 0x0F) as usize;
-        let reg_y: usize = ((opcode >> 4) & 0x0F) as usize;
-
-        match inst_group {
-            0x0 => {
-                match imm12 {
-                    0x0E0 => { CLS() }
-                    0x0EE => { RET() }
-                    _ => { INVALID { opcode } }
-                }
-            }
-            0x1 => { JUMP { address } }
-            0x2 => { CALL { address } }
-            0x3 => { SEQ { reg: reg_x, imm8 } }
-            0x4 => { SNE { reg: reg_x, imm8 } }
-            0x5 => { SRE { reg_x, reg_y } }
-            0x6 => { SETI { reg: reg_x, imm8 } }
-            0x7 => { ADDI { reg: reg_x, imm8 } }
-            0x8 => {
-                match imm4 {
-                    0x0 => { SET { reg_x, reg_y } }
-                    0x1 => { OR { reg_x, reg_y } }
-                    0x2 => { AND { reg_x, reg_y } }
-                    0x3 => { XOR { reg_x, reg_y } }
-                    0x4 => { ADD { reg_x, reg_y } }
-                    0x5 => { SUB { reg_x, reg_y } }
-                    0x7 => { NSUB { reg_x, reg_y } }
-                    0x6 => { SHR { reg_x, reg_y } }
-                    0xe => { SHL { reg_x, reg_y } }
-                    _ => { INVALID { opcode } }
-                }
-            }
-            0x9 => { SRNE { reg_x, reg_y } }
-            0xa => { SETN { address } }
-            0xb => { JMPO { address } }
-            0xc => { RAND { reg: reg_x, imm8 } }
-            0xd => { DRAW { reg_x, reg_y, imm4 } }
-            0xe => {
-                match imm8 {
-                    0x9e => { SKE { reg: reg_x } }
-                    0xa1 => { SKN { reg: reg_x } }
-                    _ => { INVALID { opcode } }
-                }
-            }
-            0xf => {
-                match imm8 {
-                    0x1e => { ADDN { reg: reg_x } }
-                    0x07 => { RDD { reg: reg_x } }
-                    0x0a => { GTK { reg: reg_x } }
-                    0x15 => { STD { reg: reg_x } }
-                    0x18 => { STS { reg: reg_x } }
-                    0x29 => { FONT { reg: reg_x } }
-                    0x33 => { BCD { reg: reg_x } }
-                    0x55 => { STM { reg: reg_x } }
-                    0x65 => { LDM { reg: reg_x } }
-                    _ => { INVALID { opcode } }
-                }
+        return Instruction::decode(opcode);
+    }
+}
+
+impl From<&Instruction> for u16 {
+    /// Encode the instruction back into its opcode.
+    fn from(instruction: &Instruction) -> Self {
+        match instruction {
+            INVALID { opcode } => { *opcode }
+            _ => {
+                let definition = instruction.definition().expect("Valid instructions have a definition");
+                definition.encode(&instruction.operands())
             }
-            _ => { INVALID { opcode } }
         }
     }
 }
 
-impl std::fmt::Display for Instruction {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            CLS() => { write!(f, "CLS()") }
-            RET() => { write!(f, "RET()") }
-            JUMP { address } => { write!(f, "JUMP({:03x})", address.get()) }
-            CALL { address } => { write!(f, "CALL({:03x})", address.get()) }
-            SEQ { reg, imm8 } => { write!(f, "SEQ(v{:x}, {:02x})", reg, imm8) }
-            SNE { reg, imm8 } => { write!(f, "SNE(v{:x}, {:02x})", reg, imm8) }
-            SRE { reg_x, reg_y } => { write!(f, "SRE(v{:x}, v{:x})", reg_x, reg_y) }
-            SRNE { reg_x, reg_y } => { write!(f, "SRNE(v{:x}, v{:x})", reg_x, reg_y) }
-            SETI { reg, imm8 } => { write!(f, "SETI(v{:x}, {:02x})", reg, imm8) }
-            ADDI { reg, imm8 } => { write!(f, "ADDI(v{:x}, {:02x})", reg, imm8) }
-            SETN { address } => { write!(f, "SETN({:03x})", address.get()) }
-            SET { reg_x, reg_y } => { write!(f, "SET(v{:x}, v{:x})", reg_x, reg_y) }
-            OR { reg_x, reg_y } => { write!(f, "OR(v{:x}, v{:x})", reg_x, reg_y) }
-            AND { reg_x, reg_y } => { write!(f, "AND(v{:x}, v{:x})", reg_x, reg_y) }
-            XOR { reg_x, reg_y } => { write!(f, "XOR(v{:x}, v{:x})", reg_x, reg_y) }
-            ADD { reg_x, reg_y } => { write!(f, "ADD(v{:x}, v{:x})", reg_x, reg_y) }
-            SUB { reg_x, reg_y } => { write!(f, "SUB(v{:x}, v{:x})", reg_x, reg_y) }
-            NSUB { reg_x, reg_y } => { write!(f, "NSUB(v{:x}, v{:x})", reg_x, reg_y) }
-            SHR { reg_x, reg_y } => { write!(f, "SHR(v{:x}, v{:x})", reg_x, reg_y) }
-            SHL { reg_x, reg_y } => { write!(f, "SHL(v{:x}, v{:x})", reg_x, reg_y) }
-            JMPO { address } => { write!(f, "JMPO({:03x})", address.get()) }
-            RAND { reg, imm8 } => { write!(f, "RAND(v{:x}, {:02x})", reg, imm8) }
-            DRAW { reg_x, reg_y, imm4 } => { write!(f, "DRAW(v{:x}, v{:x}, {:x})", reg_x, reg_y, imm4) }
-            ADDN { reg } => { write!(f, "ADDN(v{:x})", reg) }
-            RDD { reg } => { write!(f, "RDD(v{:x})", reg) }
-            STD { reg } => { write!(f, "STD(v{:x})", reg) }
-            STS { reg } => { write!(f, "STS(v{:x})", reg) }
-            FONT { reg } => { write!(f, "FONT(v{:x})", reg) }
-            BCD { reg } => { write!(f, "BCD(v{:x})", reg) }
-            STM { reg } => { write!(f, "STM(v{:x})", reg) }
-            LDM { reg } => { write!(f, "LDM(v{:x})", reg) }
-            SKE { reg } => { write!(f, "SKE(v{:x})", reg) }
-            SKN { reg } => { write!(f, "SKN(v{:x})", reg) }
-            GTK { reg } => { write!(f, "GTK(v{:x})", reg) }
-            INVALID { opcode } => { write!(f, "INVALID({:04x})", opcode) }
+impl From<Instruction> for u16 {
+    fn from(instruction: Instruction) -> Self {
+        return u16::from(&instruction);
+    }
+}
+
+impl core::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match (self, self.definition()) {
+            (INVALID { opcode }, _) => { write!(f, "INVALID({:04x})", opcode) }
+            (_, Some(definition)) => { write!(f, "{}", definition.format(&self.operands())) }
+            (_, None) => { write!(f, "{}()", self.name()) }
+        }
+    }
+}
+
+// ----- Functions ----- //
+
+/// Find the row of the instruction set with the given `name`.
+pub fn find(name: &str) -> Option<&'static Definition> {
+    return INSTRUCTION_SET.iter().find(|definition| definition.name == name);
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Whether the `opcode` is part of the instruction set, worked out from
+    /// the specification rather than the decoder.
+    fn is_valid(opcode: u16) -> bool {
+        match opcode >> 12 {
+            0x5 | 0x9 => { opcode & 0x000F == 0 }
+            0x8 => { matches!(opcode & 0x000F, 0x0..=0x7 | 0xE) }
+            0xE => { matches!(opcode & 0x00FF, 0x9E | 0xA1) }
+            0xF => { matches!(opcode & 0x00FF, 0x07 | 0x0A | 0x15 | 0x18 | 0x1E | 0x29 | 0x33 | 0x55 | 0x65) }
+            _ => { true }
+        }
+    }
+
+    #[test]
+    fn every_opcode_round_trips() {
+        for opcode in 0..=u16::MAX {
+            let instruction = Instruction::from(opcode);
+            assert_eq!(u16::from(&instruction), opcode, "{} doesn't encode back to {:04x}", instruction, opcode);
+        }
+    }
+
+    #[test]
+    fn only_invalid_opcodes_decode_to_invalid() {
+        for opcode in 0..=u16::MAX {
+            let instruction = Instruction::from(opcode);
+            let invalid = matches!(instruction, INVALID { .. });
+            assert_eq!(invalid, !is_valid(opcode), "{:04x} decodes to {}", opcode, instruction);
         }
     }
 }
@@ -0,0 +1,520 @@
+// ----- Imports ----- //
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::cpu::{Batch, CPU, Status};
+use crate::cpu::instructions::Instruction;
+use crate::error::{Error, ErrorKind};
+use crate::memory;
+use crate::memory::address::Address;
+use crate::memory::Memory;
+use crate::platform::Quirks;
+use crate::timing;
+
+// ----- Consts ----- //
+
+/// Most instructions in a single block.
+const MAX_BLOCK: usize = 32;
+
+// ----- Structs ----- //
+
+/// An instruction, translated to a closure with its operands and the quirks
+/// that apply to it already worked out.
+type Operation = Box<dyn Fn(&mut CPU) -> Result<(), ErrorKind> + Send + Sync>;
+
+/// A compiled instruction, along with where it came from.
+struct Op {
+    address: Address,
+    instruction: Instruction,
+    run: Operation,
+}
+
+/// A statically recompiled instruction, as generated by the `recompile` tool.
+pub struct StaticOp {
+    pub address: u16,
+    /// The instruction it was recompiled from.
+    pub opcode: u16,
+    pub run: fn(&mut CPU) -> Result<(), ErrorKind>,
+}
+
+/// Looks up the statically recompiled code starting at an address.
+pub type StaticCode = fn(usize) -> Option<&'static [StaticOp]>;
+
+/// An instruction ready to run, whichever way it was compiled.
+struct Compiled<'a> {
+    address: Address,
+    instruction: Instruction,
+    run: &'a dyn Fn(&mut CPU) -> Result<(), ErrorKind>,
+}
+
+/// A run of instructions with a single entry point, ending with the first
+/// instruction that can go anywhere but the next one.
+struct Block {
+    ops: Vec<Op>,
+}
+
+enum Entry {
+    Unknown,
+    /// The instruction there has to go through the interpreter.
+    Interpret,
+    Compiled(Arc<Block>),
+}
+
+/// Translates basic blocks of instructions to closures, and keeps them until
+/// the memory they came from changes.
+pub struct Recompiler {
+    quirks: Quirks,
+    blocks: Vec<Entry>,  // Indexed by entry address
+}
+
+impl Recompiler {
+    pub fn new(quirks: Quirks) -> Self {
+        let mut blocks = Vec::new();
+        blocks.resize_with(memory::MEMORY_SIZE, || Entry::Unknown);
+        return Recompiler { quirks, blocks };
+    }
+
+    /// Get the block starting at `address`, compiling it first if needed.
+    ///
+    /// returns Nothing when the instruction at `address` has to go through
+    /// the interpreter.
+    fn block(&mut self, memory: &mut Memory, address: Address) -> Option<Arc<Block>> {
+        let start = address.get();
+        match &self.blocks[start] {
+            Entry::Compiled(block) => { return Some(Arc::clone(block)); }
+            Entry::Interpret => { return None; }
+            Entry::Unknown => {}
+        }
+
+        let mut ops = Vec::new();
+        let mut end = start;
+        while ops.len() < MAX_BLOCK {
+            let Some((_, instruction)) = memory.fetch(Address::from(end)).filter(|_| end < memory::MEMORY_SIZE - 1) else {
+                break;
+            };
+            let Some(run) = compile(instruction, &self.quirks) else {
+                break;
+            };
+
+            ops.push(Op { address: Address::from(end), instruction, run });
+            end += 2;
+            if ends_block(&instruction) {
+                break;
+            }
+        }
+
+        // Remember instructions the interpreter has to run as well, so that
+        // they aren't looked at again every time.
+        memory.mark_code(start, end.max(start + 2));
+        if ops.is_empty() {
+            self.blocks[start] = Entry::Interpret;
+            return None;
+        }
+
+        let block = Arc::new(Block { ops });
+        self.blocks[start] = Entry::Compiled(Arc::clone(&block));
+        return Some(block);
+    }
+
+    /// Throw away every block covering one of the `written` addresses.
+    fn invalidate(&mut self, written: &[usize]) {
+        for &address in written {
+            for start in address.saturating_sub(2 * MAX_BLOCK)..=address {
+                let size = match &self.blocks[start] {
+                    Entry::Unknown => { 0 }
+                    Entry::Interpret => { 2 }
+                    Entry::Compiled(block) => { 2 * block.ops.len() }
+                };
+                if address < start + size {
+                    self.blocks[start] = Entry::Unknown;
+                }
+            }
+        }
+    }
+}
+
+impl CPU {
+    /// Run the compiled block starting at the program counter, if there is
+    /// one. The batch stops after `max_instructions`, once it took
+    /// `max_cycles` machine cycles, or when the block writes over compiled
+    /// code.
+    ///
+    /// returns Nothing when the next instruction has to go through the
+    /// interpreter.
+    pub(super) fn run_block(&mut self, max_instructions: u32, max_cycles: i64) -> Option<Result<Batch, Error>> {
+        let block = {
+            let recompiler = self.recompiler.as_mut()?;
+            if self.memory.is_code_written() {
+                recompiler.invalidate(&self.memory.take_written_code());
+            }
+            recompiler.block(&mut self.memory, self.pc.get())?
+        };
+
+        let mut ops = block.ops.iter().map(|op| Compiled { address: op.address, instruction: op.instruction, run: &op.run });
+        return Some(self.run_ops(|_| ops.next(), max_instructions, max_cycles));
+    }
+
+    /// Run the statically recompiled code for the program counter, if there
+    /// is some, for as long as memory still holds the instructions it was
+    /// recompiled from. The batch stops as for `run_block`.
+    ///
+    /// returns Nothing when the next instruction has to go through the
+    /// interpreter.
+    pub(super) fn run_static(&mut self, max_instructions: u32, max_cycles: i64) -> Option<Result<Batch, Error>> {
+        let ops = (self.static_code?)(self.pc.get().get())?;
+
+        // Stop at the first instruction that changed since, and leave it to
+        // the interpreter.
+        let mut ops = ops.iter();
+        let next = |cpu: &mut CPU| {
+            let op = ops.next()?;
+            let address = Address::from(op.address as usize);
+            let (opcode, instruction) = cpu.memory.fetch(address)?;
+            if opcode != op.opcode {
+                return None;
+            }
+            return Some(Compiled { address, instruction, run: &op.run });
+        };
+
+        let batch = self.run_ops(next, max_instructions, max_cycles);
+        if batch.as_ref().is_ok_and(|batch| batch.instructions == 0) {
+            return None;
+        }
+        return Some(batch);
+    }
+
+    /// Run the ops `next` gives one after the other, doing everything the
+    /// interpreter does around each instruction, until one of them goes
+    /// somewhere other than the next.
+    fn run_ops<'a>(&mut self, mut next: impl FnMut(&mut CPU) -> Option<Compiled<'a>>, max_instructions: u32,
+                   max_cycles: i64) -> Result<Batch, Error> {
+        let mut batch = Batch { instructions: 0, cycles: 0, status: Status::Running };
+        while let Some(op) = next(self) {
+            let address = op.address;
+            let next = address.get() + 2;
+            let error = |kind| Error { address, kind };
+
+            // Leave the program counter as the interpreter's fetch would.
+            self.pc.set(address);
+            self.pc.increment().map_err(error)?;
+
+            let mut cycles = timing::vip_cycles(&op.instruction, &self.registers);
+            self.status = Status::Running;
+            (op.run)(self).map_err(error)?;
+            self.store_variables();
+            self.cycles += 1;
+
+            cycles += timing::skip_cycles(&op.instruction, self.pc.get().get() == address.get() + 4);
+            batch.instructions += 1;
+            batch.cycles += cycles;
+            batch.status = self.status;
+
+            if self.status != Status::Running || self.pc.get().get() != next
+                || batch.instructions >= max_instructions || batch.cycles as i64 >= max_cycles
+                || self.memory.is_code_written() {
+                break;
+            }
+        }
+
+        return Ok(batch);
+    }
+}
+
+// ----- Functions ----- //
+
+/// Check whether the `instruction` can go anywhere but the next one.
+fn ends_block(instruction: &Instruction) -> bool {
+    return matches!(instruction,
+        Instruction::JUMP { .. } | Instruction::CALL { .. } | Instruction::RET {} | Instruction::JMPO { .. }
+        | Instruction::SEQ { .. } | Instruction::SNE { .. } | Instruction::SRE { .. } | Instruction::SRNE { .. });
+}
+
+/// Translate the `instruction`, with the given `quirks`.
+///
+/// returns Nothing for instructions left to the interpreter: those drawing,
+/// reading the keypad or timers, or otherwise depending on the world outside.
+fn compile(instruction: Instruction, quirks: &Quirks) -> Option<Operation> {
+    let operation: Operation = match instruction {
+        Instruction::SETI { reg, imm8 } => {
+            Box::new(move |cpu| {
+                cpu.registers.set_variable(reg, imm8);
+                return Ok(());
+            })
+        }
+        Instruction::ADDI { reg, imm8 } => {
+            Box::new(move |cpu| {
+                let value = cpu.registers.get_variable(reg).wrapping_add(imm8);
+                cpu.registers.set_variable(reg, value);
+                return Ok(());
+            })
+        }
+        Instruction::SET { reg_x, reg_y } => {
+            Box::new(move |cpu| {
+                let value = cpu.registers.get_variable(reg_y);
+                cpu.registers.set_variable(reg_x, value);
+                return Ok(());
+            })
+        }
+        Instruction::OR { reg_x, reg_y } => { logic(reg_x, reg_y, quirks.logic, |a, b| a | b) }
+        Instruction::AND { reg_x, reg_y } => { logic(reg_x, reg_y, quirks.logic, |a, b| a & b) }
+        Instruction::XOR { reg_x, reg_y } => { logic(reg_x, reg_y, quirks.logic, |a, b| a ^ b) }
+        Instruction::ADD { reg_x, reg_y } => {
+            Box::new(move |cpu| {
+                let a = cpu.registers.get_variable(reg_x);
+                let b = cpu.registers.get_variable(reg_y);
+                let (result, carry) = a.overflowing_add(b);
+                cpu.registers.set_flag(carry);
+                cpu.registers.set_variable(reg_x, result);
+                return Ok(());
+            })
+        }
+        Instruction::SUB { reg_x, reg_y } => { subtract(reg_x, reg_x, reg_y) }
+        Instruction::NSUB { reg_x, reg_y } => { subtract(reg_x, reg_y, reg_x) }
+        Instruction::SHR { reg_x, reg_y } => {
+            let source = if quirks.shift { reg_x } else { reg_y };
+            Box::new(move |cpu| {
+                let value = cpu.registers.get_variable(source);
+                cpu.registers.set_flag((value & 0x01) > 0);
+                cpu.registers.set_variable(reg_x, value >> 1);
+                return Ok(());
+            })
+        }
+        Instruction::SHL { reg_x, reg_y } => {
+            let source = if quirks.shift { reg_x } else { reg_y };
+            Box::new(move |cpu| {
+                let value = cpu.registers.get_variable(source);
+                cpu.registers.set_flag((value & 0x80) > 0);
+                cpu.registers.set_variable(reg_x, value << 1);
+                return Ok(());
+            })
+        }
+        Instruction::SETN { address } => {
+            Box::new(move |cpu| {
+                cpu.registers.set_index(address);
+                return Ok(());
+            })
+        }
+        Instruction::ADDN { reg } => {
+            Box::new(move |cpu| {
+                let index = cpu.registers.get_index().get() + cpu.registers.get_variable(reg) as usize;
+                cpu.registers.set_index(Address::from(index));
+                return Ok(());
+            })
+        }
+        Instruction::FONT { reg } => {
+            Box::new(move |cpu| {
+                let hex = (cpu.registers.get_variable(reg) & 0x0F) as usize;
+                cpu.registers.set_index(Address::from(memory::FONT_ADDR + memory::FONT_HEIGHT * hex));
+                return Ok(());
+            })
+        }
+        Instruction::SEQ { reg, imm8 } => { skip(move |cpu| cpu.registers.get_variable(reg) == imm8) }
+        Instruction::SNE { reg, imm8 } => { skip(move |cpu| cpu.registers.get_variable(reg) != imm8) }
+        Instruction::SRE { reg_x, reg_y } => {
+            skip(move |cpu| cpu.registers.get_variable(reg_x) == cpu.registers.get_variable(reg_y))
+        }
+        Instruction::SRNE { reg_x, reg_y } => {
+            skip(move |cpu| cpu.registers.get_variable(reg_x) != cpu.registers.get_variable(reg_y))
+        }
+        Instruction::JMPO { address } => {
+            let target = address.get();
+            let reg = if quirks.jump { (target >> 8) & 0x0F } else { 0 };
+            Box::new(move |cpu| {
+                let offset = cpu.registers.get_variable(reg) as usize;
+                cpu.pc.set(Address::from(target + offset));
+                return Ok(());
+            })
+        }
+        // These have more going on (the stack, loop detection, memory that
+        // may hold code), and are left to the interpreter's implementation.
+        Instruction::JUMP { .. } | Instruction::CALL { .. } | Instruction::RET {}
+        | Instruction::BCD { .. } | Instruction::STM { .. } | Instruction::LDM { .. } => {
+            Box::new(move |cpu| cpu.execute(instruction))
+        }
+        _ => { return None; }
+    };
+
+    return Some(operation);
+}
+
+fn logic(reg_x: usize, reg_y: usize, reset_flag: bool, op: fn(u8, u8) -> u8) -> Operation {
+    return Box::new(move |cpu| {
+        let a = cpu.registers.get_variable(reg_x);
+        let b = cpu.registers.get_variable(reg_y);
+        cpu.registers.set_variable(reg_x, op(a, b));
+        if reset_flag {
+            cpu.registers.set_flag(false);
+        }
+        return Ok(());
+    });
+}
+
+/// Store `minuend - subtrahend` in `reg`, with the flag set when there is no
+/// borrow.
+fn subtract(reg: usize, minuend: usize, subtrahend: usize) -> Operation {
+    return Box::new(move |cpu| {
+        let a = cpu.registers.get_variable(minuend);
+        let b = cpu.registers.get_variable(subtrahend);
+        cpu.registers.set_flag(a >= b);
+        cpu.registers.set_variable(reg, a.wrapping_sub(b));
+        return Ok(());
+    });
+}
+
+fn skip(condition: impl Fn(&CPU) -> bool + Send + Sync + 'static) -> Operation {
+    return Box::new(move |cpu| {
+        if condition(cpu) {
+            cpu.pc.increment()?;
+        }
+        return Ok(());
+    });
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Arc;
+
+    use crate::assembler;
+    use crate::config::Settings;
+    use crate::cpu::extensions::Extensions;
+    use crate::cpu::instructions::Field;
+    use crate::keyboard::Keymap;
+    use crate::machine::{Backend, Machine};
+    use crate::platform::Platform;
+    use crate::rom::RomImage;
+
+    const FRAMES: usize = 120;
+    const PLATFORMS: [Platform; 2] = [Platform::Chip8, Platform::CosmacVip];
+
+    const SELF_MODIFYING: &str = "
+               SETI(v0, 61)
+        loop:  ADDI(v1, 01)
+               SETN(patch)
+               STM(v1)
+        patch: SETI(v1, 00)
+               ADD(v2, v1)
+               SHR(v3, v2)
+               SNE(v2, 00)
+               CALL(sub)
+               JUMP(loop)
+        sub:   SETN(300)
+               BCD(v2)
+               LDM(v2)
+               RET()
+    ";
+
+    const JUMPS_OVER: &str = "
+        loop:  ADDI(v0, 01)
+               JUMP(over)
+               ADDI(v1, 01)
+        over:  SNE(v0, 00)
+               ADDI(v2, 01)
+               JUMP(loop)
+    ";
+
+    fn settings(platform: Platform) -> Settings {
+        return Settings {
+            platform,
+            quirks: platform.quirks(),
+            layout: platform.layout(),
+            keymap: Keymap::default(),
+            speed: 50,
+            fast_forward: 1,
+            slow_motion: 1,
+            timing: platform.timing(),
+            generator: platform.generator(),
+            seed: 1,
+            console: None,
+        };
+    }
+
+    /// Run the `program` on both backends side by side, checking they agree
+    /// after every frame.
+    fn lock_step(name: &str, program: &[u8], platform: Platform) {
+        let rom = RomImage::from(program.to_vec());
+        let mut interpreter = Machine::new(&rom, &settings(platform));
+        let mut recompiler = Machine::new(&rom, &settings(platform));
+        recompiler.set_backend(Backend::Recompiler);
+
+        for frame in 0..FRAMES {
+            match (interpreter.run_frame(), recompiler.run_frame()) {
+                (Ok(expected), Ok(actual)) => {
+                    assert!(expected == actual, "{} on {}: status differs in frame {}", name, platform, frame);
+                }
+                (Err(expected), Err(actual)) => {
+                    assert_eq!(expected.to_string(), actual.to_string(), "{} on {}", name, platform);
+                    return;
+                }
+                _ => { panic!("{} on {}: only one backend failed in frame {}", name, platform, frame); }
+            }
+
+            assert_eq!(interpreter.dump_registers(), recompiler.dump_registers(),
+                       "{} on {}: registers differ after frame {}", name, platform, frame);
+            assert_eq!(interpreter.display().dump(), recompiler.display().dump(),
+                       "{} on {}: display differs after frame {}", name, platform, frame);
+            assert_eq!(interpreter.instructions(), recompiler.instructions(),
+                       "{} on {}: instruction count differs after frame {}", name, platform, frame);
+        }
+    }
+
+    #[test]
+    fn recompiler_matches_interpreter_on_test_roms() {
+        let directory = concat!(env!("CARGO_MANIFEST_DIR"), "/test_roms");
+        for entry in fs::read_dir(directory).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            if !name.ends_with(".ch8") {
+                continue;
+            }
+
+            let rom = fs::read(&path).unwrap();
+            for platform in PLATFORMS {
+                lock_step(&name, &rom, platform);
+            }
+        }
+    }
+
+    #[test]
+    fn recompiler_only_counts_taken_skips_as_skips() {
+        let program = assembler::assemble(JUMPS_OVER, None).unwrap();
+        for platform in PLATFORMS {
+            lock_step("jumps over", &program, platform);
+        }
+    }
+
+    #[test]
+    fn recompiler_follows_self_modifying_code() {
+        let program = assembler::assemble(SELF_MODIFYING, None).unwrap();
+        for platform in PLATFORMS {
+            lock_step("self-modifying", &program, platform);
+        }
+    }
+
+    #[test]
+    fn extensions_run_on_both_backends() {
+        let mut extensions = Extensions::new();
+        extensions.register("MUL", 0x9001, 0xF00F, &[Field::X, Field::Y], |context, operands| {
+            let (x, y) = (operands[0] as usize, operands[1] as usize);
+            let product = context.registers.get_variable(x).wrapping_mul(context.registers.get_variable(y));
+            context.registers.set_variable(x, product);
+            return Ok(());
+        }).unwrap();
+        let extensions = Arc::new(extensions);
+
+        let source = "SETI(v0, 03)\nSETI(v1, 05)\nMUL(v0, v1)\nloop: JUMP(loop)\n";
+        let program = assembler::assemble_with(source, None, &extensions).unwrap();
+        let rom = RomImage::from(program);
+        for backend in [Backend::Interpreter, Backend::Recompiler] {
+            let mut machine = Machine::new(&rom, &settings(Platform::Chip8));
+            machine.set_backend(backend);
+            machine.set_extensions(Arc::clone(&extensions));
+            assert!(machine.run_frame().is_ok());
+            assert!(machine.dump_registers().starts_with("v0=0f v1=05"));
+        }
+    }
+}
@@ -0,0 +1,359 @@
+// ----- Imports ----- //
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::fs;
+
+use crate::cpu::instructions::{Field, Instruction};
+use crate::registers::VARIABLE_COUNT;
+
+// ----- Structs ----- //
+
+/// Debugging annotations of a program, kept next to it rather than in it, so
+/// that the program runs the same without them: the labels of its addresses,
+/// where its instructions are in the source, and assertions and breakpoints.
+///
+/// The assembler writes them to a sidecar file, as text, one per line: the
+/// address they apply to, followed by `label` and a name, `line` and a
+/// `file:line` location, `break` for a breakpoint, or `assert` and a
+/// condition. Empty lines and lines starting with `#` are ignored.
+///
+/// ```text
+/// 202 label _l_start
+/// 202 line delay_timer_test.ch8.txt:6
+/// 20a assert v6 < 64
+/// 214 break
+/// ```
+pub struct DebugInfo {
+    labels: BTreeMap<usize, String>,  // By address
+    lines: BTreeMap<usize, Location>,  // By address
+    checks: BTreeMap<usize, Vec<Check>>,  // By address
+}
+
+/// A line of source code.
+#[derive(Clone)]
+pub struct Location {
+    pub file: String,
+    /// Line number, starting at 1.
+    pub line: usize,
+}
+
+/// Something to do when the program counter reaches an instruction, before
+/// running it.
+#[derive(Clone)]
+pub enum Check {
+    /// Fail the run unless the condition holds.
+    Assert(Condition),
+    /// Pause the run.
+    Break,
+}
+
+/// Comparison of two operands, as in `v6 < 64`.
+#[derive(Clone)]
+pub struct Condition {
+    left: Operand,
+    comparison: Comparison,
+    right: Operand,
+    text: String,  // As written in the source
+}
+
+/// A value conditions can look at: a variable register, `I`, one of the
+/// timers, or a number in hexadecimal.
+#[derive(Copy, Clone)]
+pub enum Operand {
+    Variable(usize),
+    Index,
+    Delay,
+    Sound,
+    Number(usize),
+}
+
+#[derive(Copy, Clone)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl DebugInfo {
+    pub fn new() -> Self {
+        return DebugInfo { labels: BTreeMap::new(), lines: BTreeMap::new(), checks: BTreeMap::new() };
+    }
+
+    #[cfg(feature = "std")]
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("Failed to read {}: {}", path, error))?;
+        return DebugInfo::parse(&text);
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut result = DebugInfo::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = || format!("Invalid debug info on line {}: '{}'", number + 1, line);
+            let (address, rest) = line.split_once(char::is_whitespace).ok_or_else(error)?;
+            let address = usize::from_str_radix(address, 16).map_err(|_| error())?;
+            let rest = rest.trim();
+            match rest.split_once(char::is_whitespace) {
+                Some(("label", name)) => { result.add_label(address, name.trim()); }
+                Some(("line", location)) => { result.add_line(address, Location::parse(location.trim()).ok_or_else(error)?); }
+                _ => { result.add(address, Check::parse(rest).ok_or_else(error)?); }
+            }
+        }
+        return Ok(result);
+    }
+
+    /// Describe the annotations in the format `parse` reads.
+    pub fn format(&self) -> String {
+        let mut result = String::new();
+        for (address, label) in self.labels.iter() {
+            result += &format!("{:03x} label {}\n", address, label);
+        }
+        for (address, location) in self.lines.iter() {
+            result += &format!("{:03x} line {}\n", address, location);
+        }
+        for (address, checks) in self.checks.iter() {
+            for check in checks.iter() {
+                result += &format!("{:03x} {}\n", address, check);
+            }
+        }
+        return result;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.labels.is_empty() && self.lines.is_empty() && self.checks.is_empty();
+    }
+
+    /// Name the `address`, unless it already has a label.
+    pub fn add_label(&mut self, address: usize, name: &str) {
+        self.labels.entry(address).or_insert_with(|| name.to_string());
+    }
+
+    /// Record where the instruction at `address` is in the source.
+    pub fn add_line(&mut self, address: usize, location: Location) {
+        self.lines.insert(address, location);
+    }
+
+    /// Get the label of the `address`, if it has one.
+    pub fn label(&self, address: usize) -> Option<&str> {
+        return self.labels.get(&address).map(String::as_str);
+    }
+
+    /// Find the address with the label `name`.
+    pub fn address_of(&self, name: &str) -> Option<usize> {
+        return self.labels.iter().find(|(_, label)| *label == name).map(|(address, _)| *address);
+    }
+
+    /// Get where the instruction at `address` is in the source.
+    pub fn line(&self, address: usize) -> Option<&Location> {
+        return self.lines.get(&address);
+    }
+
+    /// Describe the `instruction` with labels in place of the addresses it
+    /// refers to, as in `JUMP(_l_start)`.
+    ///
+    /// returns Nothing when none of its addresses have a label.
+    pub fn describe(&self, instruction: &Instruction) -> Option<String> {
+        let definition = instruction.definition()?;
+        let mut named = false;
+        let operands: Vec<String> = definition.fields.iter()
+            .zip(instruction.operands())
+            .map(|(field, value)| {
+                match self.label(value as usize).filter(|_| *field == Field::Address) {
+                    Some(label) => {
+                        named = true;
+                        label.to_string()
+                    }
+                    None => { field.format(value) }
+                }
+            })
+            .collect();
+
+        if !named {
+            return None;
+        }
+        return Some(format!("{}({})", definition.name, operands.join(", ")));
+    }
+
+    /// Describe the `address`, relative to the closest label before it, and
+    /// with its source line, as in `222 _p_print+4 (delay_timer_test.ch8.txt:24)`.
+    pub fn locate(&self, address: usize) -> String {
+        let mut result = format!("{:03x}", address);
+        match self.labels.range(..=address).next_back() {
+            Some((&start, label)) if start == address => { result += &format!(" {}", label); }
+            Some((&start, label)) => { result += &format!(" {}+{:x}", label, address - start); }
+            None => {}
+        }
+        if let Some(location) = self.line(address) {
+            result += &format!(" ({})", location);
+        }
+        return result;
+    }
+
+    pub fn add(&mut self, address: usize, check: Check) {
+        self.checks.entry(address).or_default().push(check);
+    }
+
+    /// Get the checks for the instruction at `address`.
+    pub fn checks(&self, address: usize) -> &[Check] {
+        return self.checks.get(&address).map_or(&[], Vec::as_slice);
+    }
+}
+
+impl Location {
+    /// Parse `file:line`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (file, line) = text.rsplit_once(':')?;
+        return Some(Location { file: file.to_string(), line: line.parse().ok()? });
+    }
+}
+
+impl Check {
+    /// Parse `break`, or `assert` followed by a condition.
+    pub fn parse(text: &str) -> Option<Self> {
+        if text == "break" {
+            return Some(Check::Break);
+        }
+        let condition = text.strip_prefix("assert")?;
+        return Some(Check::Assert(Condition::parse(condition.trim())?));
+    }
+}
+
+impl Condition {
+    /// Parse a comparison of two operands, as in `v6 < 64` or `i != 300`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let [left, comparison, right] = words.as_slice() else {
+            return None;
+        };
+
+        let comparison = match *comparison {
+            "==" => { Comparison::Equal }
+            "!=" => { Comparison::NotEqual }
+            "<" => { Comparison::Less }
+            "<=" => { Comparison::LessOrEqual }
+            ">" => { Comparison::Greater }
+            ">=" => { Comparison::GreaterOrEqual }
+            _ => { return None; }
+        };
+        return Some(Condition {
+            left: Operand::parse(left)?,
+            comparison,
+            right: Operand::parse(right)?,
+            text: words.join(" "),
+        });
+    }
+
+    /// Check whether the condition holds, given the `value` of each operand.
+    pub fn holds(&self, value: impl Fn(Operand) -> usize) -> bool {
+        let (left, right) = (value(self.left), value(self.right));
+        match self.comparison {
+            Comparison::Equal => { left == right }
+            Comparison::NotEqual => { left != right }
+            Comparison::Less => { left < right }
+            Comparison::LessOrEqual => { left <= right }
+            Comparison::Greater => { left > right }
+            Comparison::GreaterOrEqual => { left >= right }
+        }
+    }
+}
+
+impl Operand {
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.to_lowercase();
+        let operand = match text.as_str() {
+            "i" => { Operand::Index }
+            "dt" => { Operand::Delay }
+            "st" => { Operand::Sound }
+            _ => {
+                let register = text.strip_prefix('v')
+                    .filter(|digit| digit.len() == 1)
+                    .and_then(|digit| usize::from_str_radix(digit, 16).ok())
+                    .filter(|&index| index < VARIABLE_COUNT);
+                match register {
+                    Some(index) => { Operand::Variable(index) }
+                    None => { Operand::Number(usize::from_str_radix(text.trim_start_matches("0x"), 16).ok()?) }
+                }
+            }
+        };
+        return Some(operand);
+    }
+}
+
+impl core::fmt::Display for Location {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+impl core::fmt::Display for Check {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Check::Assert(condition) => { write!(f, "assert {}", condition) }
+            Check::Break => { write!(f, "break") }
+        }
+    }
+}
+
+impl core::fmt::Display for Condition {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_info_reads_back_what_it_writes() {
+        let text = "202 label _l_start\n214 label _p_print\n202 line test.ch8.txt:6\n20a assert v6 < 64\n20a assert i != 300\n214 break\n";
+        let debug = DebugInfo::parse(&format!("# Comment\n\n{}", text)).unwrap();
+        assert_eq!(debug.format(), text);
+        assert_eq!(debug.address_of("_p_print"), Some(0x214));
+        assert_eq!(debug.locate(0x216), "216 _p_print+2");
+        assert_eq!(debug.checks(0x20a).len(), 2);
+    }
+
+    #[test]
+    fn invalid_debug_info_is_reported_with_its_line() {
+        for text in ["xyz label a", "202 line test.ch8.txt", "202 assert v0", "202 stop"] {
+            let message = DebugInfo::parse(&format!("\n{}", text)).err();
+            assert!(message.is_some_and(|message| message.starts_with("Invalid debug info on line 2")), "{}", text);
+        }
+    }
+
+    #[test]
+    fn conditions_compare_registers_timers_and_numbers() {
+        let condition = Condition::parse("vA >= 0x1f").unwrap();
+        let value = |operand| match operand {
+            Operand::Variable(0xA) => { 0x20 }
+            Operand::Index => { 0x300 }
+            Operand::Number(number) => { number }
+            _ => { 0 }
+        };
+        assert!(condition.holds(value));
+        assert!(!Condition::parse("dt > v0").unwrap().holds(value));
+        assert!(Condition::parse("I == 300").unwrap().holds(value));
+    }
+
+    #[test]
+    fn invalid_conditions_are_rejected() {
+        for text in ["", "v0", "v0 ==", "v0 == 1 2", "v0 =< 1", "vg == 1", "v10 == 1", "v0 == xyz"] {
+            assert!(Condition::parse(text).is_none(), "{}", text);
+        }
+    }
+}
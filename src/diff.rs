@@ -0,0 +1,418 @@
+// ----- Imports ----- //
+
+use std::collections::VecDeque;
+use std::fmt::Formatter;
+use std::fs;
+
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::input::InputScript;
+use crate::machine::Machine;
+use crate::registers::VARIABLE_COUNT;
+use crate::rom::RomImage;
+use crate::trace;
+use crate::trace::{TraceEntry, TraceFilter, Tracer};
+
+// ----- Consts ----- //
+
+/// Instructions shown before the one that diverged.
+const CONTEXT: usize = 8;
+
+const EXIT_MATCH: i32 = 0;
+const EXIT_DIVERGED: i32 = 1;
+const EXIT_FAILED: i32 = 2;
+
+// ----- Structs ----- //
+
+/// One line of a reference trace.
+///
+/// Reference traces are JSON lines, one object per executed instruction,
+/// describing the state right after it ran: the same format `--trace json`
+/// writes. Only `pc` is required, every other field is compared when present.
+///
+/// - `pc`: address of the instruction.
+/// - `opcode`: the instruction, as a 16-bit number.
+/// - `v`: array of the 16 variable registers.
+/// - `i`: the index register.
+/// - `dt`, `st`: the delay and sound timers.
+/// - `memory`: memory from address 0 up, as a hexadecimal string.
+///
+/// Other fields (like `cycle` or `instruction`) are ignored.
+pub struct ReferenceEntry {
+    line: usize,
+    text: String,
+    pc: usize,
+    opcode: Option<u16>,
+    variables: Option<[u8; VARIABLE_COUNT]>,
+    index: Option<usize>,
+    delay: Option<u8>,
+    sound: Option<u8>,
+    memory: Option<Vec<u8>>,
+}
+
+/// The first instruction where the machine stopped following the reference.
+pub struct Divergence {
+    /// Instructions that matched before this one.
+    pub matched: usize,
+    pub reference: String,
+    pub line: usize,
+    /// Description of each field that doesn't match.
+    pub differences: Vec<String>,
+    /// The instructions leading up to this one, and this one.
+    pub context: Vec<String>,
+}
+
+impl ReferenceEntry {
+    fn parse(line: usize, text: &str) -> Result<Self, String> {
+        let error = |what: &str| format!("Invalid reference trace on line {}: {}", line, what);
+
+        let value: Value = serde_json::from_str(text).map_err(|e| error(&e.to_string()))?;
+        let field = |name: &str| -> Result<Option<u64>, String> {
+            match value.get(name) {
+                Some(field) => { field.as_u64().map(Some).ok_or_else(|| error(&format!("bad '{}'", name))) }
+                None => { Ok(None) }
+            }
+        };
+
+        let pc = field("pc")?.ok_or_else(|| error("missing 'pc'"))? as usize;
+        let variables = match value.get("v") {
+            Some(Value::Array(values)) if values.len() == VARIABLE_COUNT => {
+                let mut variables = [0; VARIABLE_COUNT];
+                for (variable, value) in variables.iter_mut().zip(values) {
+                    *variable = value.as_u64().ok_or_else(|| error("bad 'v'"))? as u8;
+                }
+                Some(variables)
+            }
+            Some(_) => { return Err(error("'v' must hold 16 numbers")); }
+            None => { None }
+        };
+        let memory = match value.get("memory") {
+            Some(Value::String(hex)) => { Some(from_hex(hex).ok_or_else(|| error("bad 'memory'"))?) }
+            Some(_) => { return Err(error("'memory' must be a string")); }
+            None => { None }
+        };
+
+        return Ok(ReferenceEntry {
+            line,
+            text: text.to_string(),
+            pc,
+            opcode: field("opcode")?.map(|value| value as u16),
+            variables,
+            index: field("i")?.map(|value| value as usize),
+            delay: field("dt")?.map(|value| value as u8),
+            sound: field("st")?.map(|value| value as u8),
+            memory,
+        });
+    }
+
+    /// Describe every field of the `entry` that doesn't match this one.
+    fn compare(&self, entry: &TraceEntry) -> Vec<String> {
+        let mut result = Vec::new();
+
+        if self.pc != entry.pc {
+            result.push(format!("PC: expected {:03x}, got {:03x}", self.pc, entry.pc));
+        }
+        if let Some(opcode) = self.opcode.filter(|&opcode| opcode != entry.opcode) {
+            result.push(format!("opcode: expected {:04x}, got {:04x}", opcode, entry.opcode));
+        }
+        if let Some(variables) = &self.variables {
+            for (i, (expected, actual)) in variables.iter().zip(entry.variables.iter()).enumerate() {
+                if expected != actual {
+                    result.push(format!("v{:x}: expected {:02x}, got {:02x}", i, expected, actual));
+                }
+            }
+        }
+        if let Some(index) = self.index.filter(|&index| index != entry.index) {
+            result.push(format!("I: expected {:03x}, got {:03x}", index, entry.index));
+        }
+        if let Some(delay) = self.delay.filter(|&delay| delay != entry.delay) {
+            result.push(format!("DT: expected {:02x}, got {:02x}", delay, entry.delay));
+        }
+        if let Some(sound) = self.sound.filter(|&sound| sound != entry.sound) {
+            result.push(format!("ST: expected {:02x}, got {:02x}", sound, entry.sound));
+        }
+        if let (Some(expected), Some(actual)) = (&self.memory, &entry.memory) {
+            let address = expected.iter().zip(actual.iter()).position(|(a, b)| a != b);
+            if let Some(address) = address {
+                result.push(format!("memory at {:03x}: expected {:02x}, got {:02x}",
+                                    address, expected[address], actual[address]));
+            }
+        }
+
+        return result;
+    }
+}
+
+impl Divergence {
+    /// Describe a divergence at the `expected` reference entry, after
+    /// `matched` entries matched.
+    fn new(expected: &ReferenceEntry, matched: usize, differences: Vec<String>, context: VecDeque<String>) -> Self {
+        return Divergence {
+            matched,
+            reference: expected.text.clone(),
+            line: expected.line,
+            differences,
+            context: context.into(),
+        };
+    }
+}
+
+impl std::fmt::Display for Divergence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Diverged after {} matching instructions (reference line {}):", self.matched, self.line)?;
+        for difference in self.differences.iter() {
+            writeln!(f, "  {}", difference)?;
+        }
+        writeln!(f, "Context:")?;
+        for line in self.context.iter() {
+            writeln!(f, "  {}", line)?;
+        }
+        write!(f, "Reference:\n  {}", self.reference)
+    }
+}
+
+// ----- Functions ----- //
+
+/// Load a reference trace from the file at `path`.
+pub fn load(path: &str) -> Result<Vec<ReferenceEntry>, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|error| format!("Failed to read {}: {}", path, error))?;
+
+    let mut result = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        if !line.trim().is_empty() && !is_header(line) {
+            result.push(ReferenceEntry::parse(number + 1, line.trim())?);
+        }
+    }
+    return Ok(result);
+}
+
+/// Run the `machine` alongside the `reference` trace, pressing keys from the
+/// `input` script, for at most `frames` frames. Errors are divergences too,
+/// found once the instructions that ran before them are checked.
+///
+/// returns The first divergence, if any.
+pub fn run(machine: &mut Machine, reference: &[ReferenceEntry], input: Option<&InputScript>,
+           frames: Option<u64>) -> Option<Divergence> {
+    let mut tracer = Tracer::buffered(TraceFilter::default());
+    if reference.iter().any(|entry| entry.memory.is_some()) {
+        tracer = tracer.with_memory();
+    }
+    machine.set_tracer(tracer);
+
+    let mut matched = 0;
+    let mut context: VecDeque<String> = VecDeque::new();
+    let mut frame = 0;
+
+    while matched < reference.len() {
+        if frames.is_some_and(|frames| frame >= frames) {
+            let differences = vec![format!("Ran out of frames after {}", frame)];
+            return Some(Divergence::new(&reference[matched], matched, differences, context));
+        }
+
+        if let Some(input) = input {
+            machine.keypad_mut().set(input.keys_at(frame));
+        }
+        let result = machine.run_frame();
+        frame += 1;
+
+        for entry in machine.take_trace() {
+            let Some(expected) = reference.get(matched) else {
+                break;
+            };
+
+            let line = trace::format_human(&entry);
+            let differences = expected.compare(&entry);
+            if !differences.is_empty() {
+                context.push_back(format!("> {}", line));
+                return Some(Divergence::new(expected, matched, differences, context));
+            }
+
+            context.push_back(format!("  {}", line));
+            if context.len() > CONTEXT {
+                context.pop_front();
+            }
+            matched += 1;
+        }
+
+        if let (Err(error), Some(expected)) = (result, reference.get(matched)) {
+            let differences = vec![format!("Failed: {}", error)];
+            return Some(Divergence::new(expected, matched, differences, context));
+        }
+    }
+
+    return None;
+}
+
+/// Entry point of the `trace-diff` tool, given the arguments after its name:
+/// the reference trace, followed by the usual options and ROM.
+///
+/// returns The exit code.
+pub fn command(args: &[String]) -> i32 {
+    let Some(reference_path) = args.first() else {
+        eprintln!("Usage: trace-diff REFERENCE [OPTIONS] ROM");
+        return EXIT_FAILED;
+    };
+
+    let setup = || -> Result<(Machine, Vec<ReferenceEntry>, Option<InputScript>, Config), String> {
+        let config = Config::from_args(&args[1..])?;
+        let rom = RomImage::load(&config.rom_path)
+            .map_err(|error| format!("Failed to read {}: {}", config.rom_path, error))?;
+        let mut machine = Machine::new(&rom, &config.settings(&rom));
+        if let Some(code) = config.load_rng_code()? {
+            machine.load_rng_code(&code);
+        }
+        let reference = load(reference_path)?;
+        let input = match &config.input {
+            Some(path) => { Some(InputScript::load(path)?) }
+            None => { None }
+        };
+        return Ok((machine, reference, input, config));
+    };
+    let (mut machine, reference, input, config) = match setup() {
+        Ok(setup) => { setup }
+        Err(message) => {
+            eprintln!("{}", message);
+            return EXIT_FAILED;
+        }
+    };
+
+    match run(&mut machine, &reference, input.as_ref(), config.frames) {
+        None => {
+            println!("All {} instructions match the reference.", reference.len());
+            return EXIT_MATCH;
+        }
+        Some(divergence) => {
+            println!("{}", divergence);
+            return EXIT_DIVERGED;
+        }
+    }
+}
+
+/// Check whether the `line` is the header of a JSON trace, which only holds
+/// the seed of the run.
+fn is_header(line: &str) -> bool {
+    let value: Result<Value, _> = serde_json::from_str(line);
+    return value.is_ok_and(|value| value.get("seed").is_some() && value.get("pc").is_none());
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    return (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect();
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pc: usize) -> TraceEntry {
+        let mut variables = [0; VARIABLE_COUNT];
+        variables[3] = 0xAB;
+        return TraceEntry {
+            cycle: 12,
+            pc,
+            opcode: 0x63AB,
+            instruction: String::from("SETI(v3, ab)"),
+            source: None,
+            variables,
+            index: 0x345,
+            delay: 7,
+            sound: 0,
+            memory: Some(vec![0x00, 0xFF, 0x12]),
+        };
+    }
+
+    #[test]
+    fn json_traces_read_back_as_references() {
+        let mut traced = entry(0x200);
+        traced.instruction = String::from("CALL(\"quoted\" \\ name)");
+        traced.source = Some(String::from("C:\\roms\\\"odd\".asm:3"));
+        let line = trace::format_json(&traced);
+
+        let value: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["instruction"], "CALL(\"quoted\" \\ name)");
+        assert_eq!(value["source"], "C:\\roms\\\"odd\".asm:3");
+        let reference = ReferenceEntry::parse(1, &line).unwrap();
+        assert!(reference.compare(&traced).is_empty());
+        assert_eq!(reference.memory, Some(vec![0x00, 0xFF, 0x12]));
+    }
+
+    /// Follow the `reference` lines running `program`, for up to 10 frames.
+    fn follow(program: &[u8], reference: &[&str]) -> Option<Divergence> {
+        let args: Vec<String> = ["test.ch8", "--seed", "0"].iter().map(|arg| arg.to_string()).collect();
+        let config = Config::from_args(&args).unwrap();
+        let rom = RomImage::from(program.to_vec());
+        let mut machine = Machine::new(&rom, &config.settings(&rom));
+        let reference: Vec<ReferenceEntry> = reference.iter().enumerate()
+            .map(|(index, line)| ReferenceEntry::parse(index + 1, line).unwrap())
+            .collect();
+        return run(&mut machine, &reference, None, Some(10));
+    }
+
+    #[test]
+    fn references_only_need_the_pc() {
+        let reference = ReferenceEntry::parse(1, r#"{"pc": 512, "cycle": 3}"#).unwrap();
+        assert_eq!(reference.pc, 0x200);
+        assert!(reference.opcode.is_none() && reference.variables.is_none() && reference.memory.is_none());
+        assert!(reference.compare(&entry(0x200)).is_empty());
+
+        let missing = ReferenceEntry::parse(4, r#"{"opcode": 25515}"#).err().unwrap();
+        assert_eq!(missing, "Invalid reference trace on line 4: missing 'pc'");
+        assert!(ReferenceEntry::parse(1, "pc=512").is_err());
+        assert!(ReferenceEntry::parse(1, r#"{"pc": "200"}"#).is_err());
+        assert!(ReferenceEntry::parse(1, r#"{"pc": 512, "v": [1, 2]}"#).is_err());
+        assert!(ReferenceEntry::parse(1, r#"{"pc": 512, "memory": "0g"}"#).is_err());
+
+        assert!(is_header(r#"{"seed": 5}"#));
+        assert!(!is_header(r#"{"pc": 512, "seed": 5}"#));
+    }
+
+    #[test]
+    fn compare_lists_every_difference() {
+        let text = r#"{"pc": 514, "opcode": 25515, "v": [0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0], "i": 837, "dt": 7, "memory": "00ff13"}"#;
+        let reference = ReferenceEntry::parse(1, text).unwrap();
+        assert_eq!(reference.compare(&entry(0x200)), vec![
+            "PC: expected 202, got 200",
+            "v3: expected 00, got ab",
+            "memory at 002: expected 13, got 12",
+        ]);
+    }
+
+    #[test]
+    fn matching_runs_have_no_divergence() {
+        let program = [0x60, 0x05, 0x70, 0x01, 0x12, 0x04];
+        let reference = [r#"{"pc": 512, "opcode": 24581}"#, r#"{"pc": 514}"#, r#"{"pc": 516}"#, r#"{"pc": 516}"#];
+        assert!(follow(&program, &reference).is_none());
+    }
+
+    #[test]
+    fn the_first_divergence_is_reported_with_its_context() {
+        let program = [0x60, 0x05, 0x70, 0x01, 0x12, 0x04];
+        let reference = [r#"{"pc": 512}"#, r#"{"pc": 514, "i": 1}"#, r#"{"pc": 516}"#];
+        let divergence = follow(&program, &reference).unwrap();
+        assert_eq!(divergence.matched, 1);
+        assert_eq!(divergence.line, 2);
+        assert_eq!(divergence.differences, vec!["I: expected 001, got 000"]);
+        assert_eq!(divergence.context.len(), 2);
+        assert!(divergence.context[1].starts_with("> "));
+    }
+
+    #[test]
+    fn errors_are_divergences_after_the_instructions_before_them() {
+        let program = [0x60, 0x05, 0x70, 0x01];
+        let reference = [r#"{"pc": 512}"#, r#"{"pc": 514}"#, r#"{"pc": 516}"#];
+        let divergence = follow(&program, &reference).unwrap();
+        assert_eq!(divergence.matched, 2);
+        assert_eq!(divergence.line, 3);
+        assert_eq!(divergence.context.len(), 2);
+        assert!(divergence.differences[0].starts_with("Failed: Ran into empty memory"));
+    }
+}
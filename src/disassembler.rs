@@ -0,0 +1,122 @@
+// ----- Imports ----- //
+
+use std::fs;
+
+use crate::assembler;
+use crate::cpu::extensions::Extensions;
+use crate::cpu::instructions::{Field, Instruction};
+use crate::debug::DebugInfo;
+use crate::platform::Platform;
+
+// ----- Consts ----- //
+
+/// Programs are loaded at this address.
+const ORIGIN: usize = 0x200;
+
+// ----- Functions ----- //
+
+/// Disassemble the program in `data`, one instruction per line, in the syntax
+/// the assembler reads. Each line ends with a comment giving the address and
+/// opcode, and noting instructions that don't exist on the `platform`.
+pub fn disassemble(data: &[u8], platform: Option<Platform>) -> String {
+    return disassemble_with(data, platform, &Extensions::new(), &DebugInfo::new());
+}
+
+/// Disassemble the program in `data`, as `disassemble` does, writing the
+/// custom instructions in `extensions` by name. The labels in `debug` are
+/// written where they are, and in place of the addresses instructions refer
+/// to, and comments give the source line of each instruction.
+pub fn disassemble_with(data: &[u8], platform: Option<Platform>, extensions: &Extensions, debug: &DebugInfo) -> String {
+    let mut result = String::new();
+
+    // Labels can only be written at the start of a line, so any other label
+    // can't be referred to either.
+    let is_written = |address: usize| address >= ORIGIN && address < ORIGIN + data.len() && (address - ORIGIN).is_multiple_of(2);
+
+    for (i, chunk) in data.chunks(2).enumerate() {
+        let address = ORIGIN + 2 * i;
+        if let Some(label) = debug.label(address) {
+            result += &format!("{}:\n", label);
+        }
+
+        let line = match chunk {
+            [high, low] => {
+                let opcode = ((*high as u16) << 8) | *low as u16;
+                let instruction = Instruction::from(opcode);
+                let mut comment = format!("{:03x}: {:04x}", address, opcode);
+                if let Some(platform) = platform {
+                    if instruction.definition().is_some() && !instruction.is_available(platform) {
+                        comment += &format!(" (not on {})", platform);
+                    }
+                }
+                if let Some(location) = debug.line(address) {
+                    comment += &format!(" {}", location);
+                }
+
+                let target = instruction.definition()
+                    .and_then(|definition| definition.fields.iter().position(|field| *field == Field::Address))
+                    .map(|index| instruction.operands()[index] as usize);
+                let text = match debug.describe(&instruction) {
+                    Some(text) if target.is_some_and(is_written) => { text }
+                    _ => { extensions.format(&instruction) }
+                };
+                format!("    {:<20} ; {}\n", text, comment)
+            }
+            _ => { format!("    {:<20} ; {:03x}: {:02x}\n", format!("BYTE({:02x})", chunk[0]), address, chunk[0]) }
+        };
+        result += &line;
+    }
+
+    return result;
+}
+
+/// Entry point of the `disasm` tool, given the arguments after its name: the
+/// ROM to disassemble, and optionally `--platform NAME` and `--debug FILE`, the
+/// debug info the assembler wrote for it.
+///
+/// returns The exit code.
+pub fn command(args: &[String]) -> i32 {
+    let mut debug = DebugInfo::new();
+    let mut options = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--debug" {
+            let Some(path) = args.next() else {
+                eprintln!("Missing value for --debug");
+                return 1;
+            };
+            match DebugInfo::load(path) {
+                Ok(loaded) => { debug = loaded; }
+                Err(message) => {
+                    eprintln!("{}", message);
+                    return 1;
+                }
+            }
+        } else {
+            options.push(arg.clone());
+        }
+    }
+
+    let (paths, platform) = match assembler::parse_args(&options) {
+        Ok(parsed) => { parsed }
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    };
+    let [path] = paths.as_slice() else {
+        eprintln!("Usage: disasm ROM [--platform NAME] [--debug FILE]");
+        return 1;
+    };
+
+    match fs::read(path) {
+        Ok(data) => {
+            print!("{}", disassemble_with(&data, platform, &Extensions::new(), &debug));
+            return 0;
+        }
+        Err(error) => {
+            eprintln!("Failed to read {}: {}", path, error);
+            return 1;
+        }
+    }
+}
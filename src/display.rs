@@ -1,73 +1,145 @@
 // ----- Imports ----- //
 
-use std::io::stdout;
-use crossterm::{cursor, execute, style, terminal};
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::memory::address::Address;
+use crate::memory::Memory;
 
 // ----- Consts ----- //
 
-const DISPLAY_WIDTH: usize = 64;
-const DISPLAY_HEIGHT: usize = 32;
+pub const DISPLAY_WIDTH: usize = 64;
+pub const DISPLAY_HEIGHT: usize = 32;
+
+/// Size of a memory-mapped framebuffer, with one bit per pixel.
+pub const FRAMEBUFFER_SIZE: usize = DISPLAY_WIDTH * DISPLAY_HEIGHT / 8;
 
 const SPRITE_WIDTH: usize = 8;
 const SPRITE_HEIGHT: usize = 15;
 
 // ----- Structs ----- //
 
-pub struct Display {
-    grid: [[bool; DISPLAY_WIDTH]; DISPLAY_HEIGHT],
+/// Storage for the pixels on the display.
+pub enum Framebuffer {
+    Grid(Box<[[bool; DISPLAY_WIDTH]; DISPLAY_HEIGHT]>),
+    /// Pixels are kept in memory starting at `base`, 8 pixels per byte, with
+    /// the most significant bit on the left.
+    Mapped { base: usize },
+}
+
+/// The display as frontends see it: a framebuffer, along with the memory its
+/// pixels may live in.
+pub struct Display<'a> {
+    framebuffer: &'a Framebuffer,
+    memory: &'a Memory,
 }
 
 pub struct Sprite {
     pixels: [[bool; SPRITE_WIDTH]; SPRITE_HEIGHT],
 }
 
-impl Display {
+impl Framebuffer {
     pub fn new() -> Self {
-        return Display {
-            grid: [[false; DISPLAY_WIDTH]; DISPLAY_HEIGHT],
-        };
+        return Framebuffer::Grid(Box::new([[false; DISPLAY_WIDTH]; DISPLAY_HEIGHT]));
+    }
+
+    /// Create a framebuffer whose pixels live in memory, at the given `base`
+    /// address, so that the program can access them directly.
+    pub fn new_mapped(base: usize) -> Self {
+        return Framebuffer::Mapped { base };
     }
 
     /// Clear the display, turning all pixels off.
-    pub fn clear(&mut self) {
-        let _ = execute!(stdout(), terminal::Clear(terminal::ClearType::All));
-        self.grid = [[false; DISPLAY_WIDTH]; DISPLAY_HEIGHT];
+    pub fn clear(&mut self, memory: &mut Memory) {
+        match self {
+            Framebuffer::Grid(grid) => { **grid = [[false; DISPLAY_WIDTH]; DISPLAY_HEIGHT]; }
+            Framebuffer::Mapped { base } => {
+                memory.write(Address::from(*base), &[0; FRAMEBUFFER_SIZE]);
+            }
+        }
+    }
+
+    pub fn get_pixel(&self, memory: &Memory, x: usize, y: usize) -> bool {
+        match self {
+            Framebuffer::Grid(grid) => { grid[y][x] }
+            Framebuffer::Mapped { base } => {
+                let (address, mask) = pixel_location(*base, x, y);
+                let byte = memory.read(address, 1)[0];
+                byte & mask > 0
+            }
+        }
+    }
+
+    fn set_pixel(&mut self, memory: &mut Memory, x: usize, y: usize, value: bool) {
+        match self {
+            Framebuffer::Grid(grid) => { grid[y][x] = value; }
+            Framebuffer::Mapped { base } => {
+                let (address, mask) = pixel_location(*base, x, y);
+                let mut byte = memory.read(address, 1)[0];
+                if value {
+                    byte |= mask;
+                } else {
+                    byte &= !mask;
+                }
+                memory.write(address, &[byte]);
+            }
+        }
     }
 
     /// Add the given sprite to the display.
     /// Pixels from the given `sprite` are xorred with existing pixels, so that
-    /// 1s switch a pixel, and 0s have no effect.
+    /// 1s switch a pixel, and 0s have no effect. Pixels that fall off the edge
+    /// of the screen are dropped, unless `wrap` is set, in which case they are
+    /// drawn on the opposite edge.
     ///
     /// Returns `true` if any operation resulted in a pixel getting turned off,
     /// `false` otherwise.
-    pub fn add_sprite(&mut self, sprite: &Sprite, mut x: usize, mut y: usize) -> bool {
-        x = x % DISPLAY_WIDTH;
-        y = y % DISPLAY_HEIGHT;
+    pub fn add_sprite(&mut self, memory: &mut Memory, sprite: &Sprite, mut x: usize, mut y: usize, wrap: bool) -> bool {
+        x %= DISPLAY_WIDTH;
+        y %= DISPLAY_HEIGHT;
         let mut result = false;
 
         for i in 0..SPRITE_WIDTH {
-            if x + i >= DISPLAY_WIDTH { break; }
+            if !wrap && x + i >= DISPLAY_WIDTH { break; }
+            let column = (x + i) % DISPLAY_WIDTH;
 
             for j in 0..SPRITE_HEIGHT {
-                if y + j >= DISPLAY_HEIGHT { break; }
+                if !wrap && y + j >= DISPLAY_HEIGHT { break; }
+                let row = (y + j) % DISPLAY_HEIGHT;
 
-                let current = self.grid[y + j][x + i];
+                let current = self.get_pixel(memory, column, row);
                 let pixel = sprite.get_pixel(i, j);
                 result |= current & pixel;
-                self.grid[y + j][x + i] = current ^ pixel;
-
-                if pixel {
-                    let mut s = String::from("█");
-                    if !current ^ pixel {
-                        s = String::from(" ");
-                    }
-                    let moveto = cursor::MoveTo((x + i) as u16, (y + j) as u16);
-                    let _ = execute!(stdout(), moveto, style::Print(s));
-                }
+                self.set_pixel(memory, column, row, current ^ pixel);
+            }
+        }
+        return result;
+    }
+}
+
+impl<'a> Display<'a> {
+    pub fn new(framebuffer: &'a Framebuffer, memory: &'a Memory) -> Self {
+        return Display { framebuffer, memory };
+    }
+
+    /// Describe the display as text, one line per row.
+    pub fn dump(&self) -> String {
+        let mut result = String::new();
+
+        for row in 0..DISPLAY_HEIGHT {
+            for column in 0..DISPLAY_WIDTH {
+                result.push(if self.get_pixel(column, row) { '█' } else { '.' });
             }
+            result.push('\n');
         }
+
         return result;
     }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> bool {
+        return self.framebuffer.get_pixel(self.memory, x, y);
+    }
 }
 
 impl Sprite {
@@ -102,3 +174,53 @@ impl From<Vec<u8>> for Sprite {
         };
     }
 }
+
+/// Get the address and bit mask of the pixel at (`x`, `y`) in a framebuffer
+/// that starts at `base`.
+fn pixel_location(base: usize, x: usize, y: usize) -> (Address, u8) {
+    let address = Address::from(base + (y * DISPLAY_WIDTH + x) / 8);
+    let mask = 0x80 >> (x % 8);
+    return (address, mask);
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const BASE: usize = 0xF00;
+
+    #[test]
+    fn mapped_pixels_live_in_memory() {
+        let mut memory = Memory::new(&[]);
+        let mut framebuffer = Framebuffer::new_mapped(BASE);
+        let sprite = Sprite::from(vec![0b1100_0001]);
+
+        // Drawn pixels show up as bits, most significant on the left, one row
+        // of 8 bytes after another.
+        assert!(!framebuffer.add_sprite(&mut memory, &sprite, 12, 1, false));
+        assert_eq!(memory.read(Address::from(BASE + 8 + 1), 2), vec![0b0000_1100, 0b0001_0000]);
+
+        // And bits written by the program show up as pixels.
+        memory.write(Address::from(BASE + 8 * 31 + 7), &[0x01]);
+        assert!(framebuffer.get_pixel(&memory, 63, 31));
+        assert!(!framebuffer.get_pixel(&memory, 62, 31));
+
+        assert!(framebuffer.add_sprite(&mut memory, &sprite, 12, 1, false));
+        assert_eq!(memory.read(Address::from(BASE + 8 + 1), 2), vec![0, 0]);
+    }
+
+    #[test]
+    fn clearing_a_mapped_display_only_clears_its_memory() {
+        let mut memory = Memory::new(&[]);
+        memory.write(Address::from(BASE - 1), &[0xAA; FRAMEBUFFER_SIZE + 1]);
+        let mut framebuffer = Framebuffer::new_mapped(BASE);
+        assert!(Display::new(&framebuffer, &memory).dump().starts_with("█.█.█.█."));
+
+        framebuffer.clear(&mut memory);
+        assert_eq!(memory.read(Address::from(BASE - 1), 2), vec![0xAA, 0x00]);
+        assert!(!Display::new(&framebuffer, &memory).dump().contains('█'));
+    }
+}
@@ -0,0 +1,54 @@
+// ----- Imports ----- //
+
+use alloc::string::String;
+use core::fmt::Formatter;
+
+use crate::memory::address::Address;
+
+// ----- Structs ----- //
+
+/// Something went wrong running the instruction at `address`.
+pub struct Error {
+    pub address: Address,
+    pub kind: ErrorKind,
+}
+
+pub enum ErrorKind {
+    InvalidInstruction { opcode: u16 },
+    StackOverflow,
+    StackUnderflow,
+    EndOfMemory,
+    /// The program ran into memory that was never written to.
+    EmptyMemory,
+    /// Machine code routines need the variable registers in memory.
+    NoMachineCode,
+    /// A machine code routine ran for too long without returning.
+    MachineCodeTimeout,
+    /// A machine code routine waits for an interrupt (`IDL`), which only the
+    /// full VIP emulation raises.
+    MachineCodeIdle,
+    /// A debugging assertion didn't hold.
+    AssertionFailed { condition: String },
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} (at {})", self.kind, self.address)
+    }
+}
+
+impl core::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ErrorKind::InvalidInstruction { opcode } => { write!(f, "Invalid instruction {:04x}", opcode) }
+            ErrorKind::StackOverflow => { write!(f, "Stack overflow! Call stack is full.") }
+            ErrorKind::StackUnderflow => { write!(f, "Call stack is empty.") }
+            ErrorKind::EndOfMemory => { write!(f, "Reached end of memory") }
+            ErrorKind::EmptyMemory => { write!(f, "Ran into empty memory (0000)") }
+            ErrorKind::NoMachineCode => { write!(f, "Machine code routines need the VIP memory layout") }
+            ErrorKind::MachineCodeTimeout => { write!(f, "Machine code routine did not return") }
+            ErrorKind::MachineCodeIdle => { write!(f, "Machine code routine waits for an interrupt (IDL), which 0NNN calls don't get") }
+            ErrorKind::AssertionFailed { condition } => { write!(f, "Assertion failed: {}", condition) }
+        }
+    }
+}
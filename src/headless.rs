@@ -0,0 +1,87 @@
+// ----- Imports ----- //
+
+use alloc::format;
+
+use crate::cpu::Status;
+use crate::error::Error;
+use crate::input::InputScript;
+use crate::machine::Machine;
+
+// ----- Functions ----- //
+
+/// Run the `machine` as fast as possible, without showing it or reading the
+/// keyboard, until it halts or `frames` frames have run. Keys are pressed
+/// from the `input` script instead. The output of the debug console is handed
+/// to `console` after every frame, along with the registers at each
+/// breakpoint, which the run then goes past.
+///
+/// returns The status of the last instruction to run.
+pub fn run(machine: &mut Machine, frames: Option<u64>, input: Option<&InputScript>,
+           console: &mut dyn FnMut(&str)) -> Result<Status, Error> {
+    let mut count = 0;
+
+    loop {
+        if frames.is_some_and(|frames| count >= frames) {
+            return Ok(Status::Running);
+        }
+
+        if let Some(input) = input {
+            machine.keypad_mut().set(input.keys_at(count));
+        }
+
+        let status = machine.run_frame();
+        console(&machine.take_console());
+        let status = status?;
+        if status == Status::Breakpoint {
+            console(&format!("breakpoint at {}:\n{}", machine.location(), machine.dump_registers()));
+            continue;
+        }
+        if status == Status::Halted {
+            return Ok(status);
+        }
+        count += 1;
+    }
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use crate::config::Config;
+    use crate::error::ErrorKind;
+    use crate::rom::RomImage;
+
+    /// Run `program` for up to 10 frames, and get the exit code for the run.
+    fn exit_code(program: &[u8], args: &[&str]) -> (i32, Result<Status, Error>) {
+        let args: Vec<String> = ["test.ch8", "--headless", "--seed", "0"].iter().chain(args).map(|arg| arg.to_string()).collect();
+        let config = Config::from_args(&args).unwrap();
+        let rom = RomImage::from(program.to_vec());
+        let mut machine = Machine::new(&rom, &config.settings(&rom));
+        let result = run(&mut machine, Some(10), None, &mut |_| {});
+        return (config.exit_code(&result), result);
+    }
+
+    #[test]
+    fn halting_programs_exit_successfully() {
+        assert_eq!(exit_code(&[0x60, 0x01, 0x12, 0x02], &[]).0, 0);
+        assert_eq!(exit_code(&[0x12, 0x00], &["--exit-on-halt", "3"]).0, 3);
+    }
+
+    #[test]
+    fn programs_still_running_time_out() {
+        assert_eq!(exit_code(&[0x70, 0x01, 0x12, 0x00], &[]).0, 124);
+        assert_eq!(exit_code(&[0x70, 0x01, 0x12, 0x00], &["--exit-on-timeout", "0"]).0, 0);
+    }
+
+    #[test]
+    fn failing_programs_exit_with_an_error() {
+        let (code, result) = exit_code(&[0x60, 0x01], &[]);
+        assert_eq!(code, 1);
+        assert!(matches!(result, Err(Error { kind: ErrorKind::EmptyMemory, .. })));
+        assert!(result.is_err_and(|error| error.to_string().starts_with("Ran into empty memory (0000) (at Address:0x202)")));
+        assert_eq!(exit_code(&[0x00, 0xEE], &["--exit-on-error", "7"]).0, 7);
+    }
+}
@@ -0,0 +1,105 @@
+// ----- Imports ----- //
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::fs;
+
+// ----- Structs ----- //
+
+/// Keys to hold on the CHIP-8 keypad over the course of a run, for runs
+/// without a keyboard.
+///
+/// Scripts are text, one change per line: the frame the change happens on,
+/// followed by the keys held from then on, as hexadecimal digits (or `-` for
+/// none). Empty lines and lines starting with `#` are ignored.
+///
+/// ```text
+/// # Hold 5 for two frames, then 5 and 6 together.
+/// 30 5
+/// 32 56
+/// 40 -
+/// ```
+pub struct InputScript {
+    changes: Vec<(u64, [bool; 0x10])>,  // Sorted by frame
+}
+
+impl InputScript {
+    #[cfg(feature = "std")]
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("Failed to read {}: {}", path, error))?;
+        return InputScript::parse(&text);
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut changes: Vec<(u64, [bool; 0x10])> = Vec::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = || format!("Invalid input on line {}: '{}'", number + 1, line);
+            let (frame, keys) = line.split_once(char::is_whitespace).ok_or_else(error)?;
+            let frame = frame.parse::<u64>().map_err(|_| error())?;
+
+            let mut pressed = [false; 0x10];
+            let keys = keys.trim();
+            if keys != "-" {
+                for digit in keys.chars() {
+                    let key = digit.to_digit(16).ok_or_else(error)? as usize;
+                    pressed[key] = true;
+                }
+            }
+
+            if changes.last().is_some_and(|(last, _)| *last >= frame) {
+                return Err(format!("Frames must increase, on line {}", number + 1));
+            }
+            changes.push((frame, pressed));
+        }
+
+        return Ok(InputScript { changes });
+    }
+
+    /// Get the keys held during the given `frame`.
+    pub fn keys_at(&self, frame: u64) -> [bool; 0x10] {
+        return self.changes.iter()
+            .rev()
+            .find(|(start, _)| *start <= frame)
+            .map(|(_, pressed)| *pressed)
+            .unwrap_or([false; 0x10]);
+    }
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn held(script: &InputScript, frame: u64) -> Vec<usize> {
+        return script.keys_at(frame).iter().enumerate().filter(|(_, &held)| held).map(|(key, _)| key).collect();
+    }
+
+    #[test]
+    fn keys_are_held_until_the_next_change() {
+        let script = InputScript::parse("# Comment\n\n30 5\n32 5a\n40 -\n").unwrap();
+        assert!(held(&script, 0).is_empty());
+        assert_eq!(held(&script, 30), vec![5]);
+        assert_eq!(held(&script, 31), vec![5]);
+        assert_eq!(held(&script, 39), vec![5, 0xA]);
+        assert!(held(&script, 40).is_empty());
+    }
+
+    #[test]
+    fn invalid_scripts_are_rejected() {
+        assert!(InputScript::parse("30").is_err());
+        assert!(InputScript::parse("x 5").is_err());
+        assert!(InputScript::parse("30 g").is_err());
+        assert!(InputScript::parse("30 5\n30 6").is_err());
+    }
+}
@@ -1,30 +1,18 @@
 // ----- Imports ----- //
 
-use std::fmt::{Display, Formatter};
-use device_query::{DeviceQuery, DeviceState, Keycode};
+use core::fmt::{Display, Formatter};
 
 // ----- Consts ----- //
 
 pub const MAX_KEY: u8 = 0x0F;
 pub const INVALID_KEY: u8 = 0xFF;
 
-pub const VALID_KEYS: [Keycode; 0x10] = [
-    Keycode::Key1,
-    Keycode::Key2,
-    Keycode::Key3,
-    Keycode::Key4,
-    Keycode::Q,
-    Keycode::W,
-    Keycode::E,
-    Keycode::R,
-    Keycode::A,
-    Keycode::S,
-    Keycode::D,
-    Keycode::F,
-    Keycode::Z,
-    Keycode::X,
-    Keycode::C,
-    Keycode::V,
+/// Host keys for each CHIP-8 key, indexed by the CHIP-8 key value.
+const DEFAULT_KEYS: [char; 0x10] = [
+    'x', '1', '2', '3',
+    'q', 'w', 'e', 'a',
+    's', 'd', 'z', 'c',
+    '4', 'r', 'f', 'v',
 ];
 
 // ----- Structs ----- //
@@ -34,6 +22,20 @@ pub struct Key {
     value: u8,
 }
 
+/// State of the 16 keys of the CHIP-8 keypad.
+pub struct Keypad {
+    pressed: [bool; 0x10],
+    last: Option<u8>,  // Last key to be pressed
+}
+
+/// Mapping between host keys and the 16 keys of the CHIP-8 keypad. Host keys
+/// are named by the lowercase character they type, so that frontends can map
+/// them from whatever their input library reports.
+#[derive(Copy, Clone)]
+pub struct Keymap {
+    keys: [char; 0x10],
+}
+
 impl Key {
     pub fn get(&self) -> u8 {
         return self.value;
@@ -49,32 +51,73 @@ impl From<u8> for Key {
     }
 }
 
-impl From<Keycode> for Key {
-    fn from(value: Keycode) -> Self {
-        match value {
-            Keycode::Key1 => { Key { value: 0x1 } }
-            Keycode::Key2 => { Key { value: 0x2 } }
-            Keycode::Key3 => { Key { value: 0x3 } }
-            Keycode::Key4 => { Key { value: 0xC } }
-            Keycode::Q => { Key { value: 0x4 } }
-            Keycode::W => { Key { value: 0x5 } }
-            Keycode::E => { Key { value: 0x6 } }
-            Keycode::R => { Key { value: 0xD } }
-            Keycode::A => { Key { value: 0x7 } }
-            Keycode::S => { Key { value: 0x8 } }
-            Keycode::D => { Key { value: 0x9 } }
-            Keycode::F => { Key { value: 0xE } }
-            Keycode::Z => { Key { value: 0xA } }
-            Keycode::X => { Key { value: 0x0 } }
-            Keycode::C => { Key { value: 0xB } }
-            Keycode::V => { Key { value: 0xF } }
-            _ => { Key { value: INVALID_KEY } }
+impl Keymap {
+    pub const fn new(keys: [char; 0x10]) -> Self {
+        return Keymap { keys };
+    }
+
+    /// Get the CHIP-8 key mapped to the given host key, if there is one.
+    pub fn key(&self, host_key: char) -> Option<Key> {
+        let value = self.keys.iter().position(|&key| key == host_key)?;
+        return Some(Key { value: value as u8 });
+    }
+}
+
+impl Keypad {
+    pub fn new() -> Self {
+        return Keypad {
+            pressed: [false; 0x10],
+            last: None,
+        };
+    }
+
+    pub fn is_pressed(&self, key: &Key) -> bool {
+        if key.value > MAX_KEY {
+            return false;
+        }
+        return self.pressed[key.value as usize];
+    }
+
+    /// Get the key currently held, preferring the last one to be pressed.
+    pub fn get_pressed(&self) -> Option<Key> {
+        if let Some(value) = self.last {
+            if self.pressed[value as usize] {
+                return Some(Key { value });
+            }
         }
+
+        let value = self.pressed.iter().position(|&pressed| pressed)?;
+        return Some(Key { value: value as u8 });
+    }
+
+    /// Set the state of every key from the host keys currently held.
+    pub fn update(&mut self, keymap: &Keymap, keys: &[char]) {
+        let mut pressed = [false; 0x10];
+        for key in keys.iter().filter_map(|&host_key| keymap.key(host_key)) {
+            pressed[key.value as usize] = true;
+        }
+        self.set(pressed);
+    }
+
+    /// Set the state of every key, indexed by the CHIP-8 key value.
+    pub fn set(&mut self, pressed: [bool; 0x10]) {
+        for (value, &down) in pressed.iter().enumerate() {
+            if down && !self.pressed[value] {
+                self.last = Some(value as u8);
+            }
+        }
+        self.pressed = pressed;
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        return Keymap::new(DEFAULT_KEYS);
     }
 }
 
 impl Display for Key {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         if self.value > MAX_KEY {
             write!(f, "Key:{}", self.value)
         } else {
@@ -83,14 +126,3 @@ impl Display for Key {
     }
 }
 
-pub fn get_key() -> Option<Key> {
-    let ds = DeviceState::new();
-    let keys = ds.get_keys();
-    let filtered = keys.iter().filter(|key| VALID_KEYS.contains(key));
-
-    if let Some(code) = filtered.last() {
-        return Some(Key::from(*code));
-    }
-
-    None
-}
@@ -0,0 +1,44 @@
+#![allow(clippy::needless_return, clippy::upper_case_acronyms, clippy::new_without_default)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+// ----- Modules ----- //
+
+#[cfg(feature = "std")]
+pub mod assembler;
+#[cfg(feature = "std")]
+pub mod batch;
+pub mod cdp1802;
+pub mod config;
+pub mod console;
+pub mod cpu;
+pub mod debug;
+#[cfg(feature = "std")]
+pub mod diff;
+#[cfg(feature = "std")]
+pub mod disassembler;
+pub mod display;
+pub mod error;
+pub mod headless;
+pub mod input;
+pub mod keyboard;
+pub mod machine;
+pub mod memory;
+pub mod platform;
+pub mod random;
+pub mod registers;
+pub mod rom;
+pub mod speed;
+pub mod stack;
+#[cfg(feature = "std")]
+pub mod static_recompiler;
+#[cfg(feature = "std")]
+pub mod terminal;
+pub mod timers;
+pub mod timing;
+pub mod trace;
+#[cfg(feature = "std")]
+pub mod unit;
+#[cfg(feature = "std")]
+pub mod vip;
@@ -0,0 +1,230 @@
+// ----- Imports ----- //
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::sync::Arc;
+
+use crate::config::Settings;
+use crate::cpu::{CPU, Status};
+use crate::cpu::extensions::Extensions;
+use crate::cpu::recompiler::StaticCode;
+use crate::debug::DebugInfo;
+use crate::display::Display;
+use crate::error::Error;
+use crate::keyboard::Keypad;
+use crate::random::Random;
+use crate::rom::RomImage;
+use crate::speed::Speed;
+use crate::timing;
+use crate::timing::Timing;
+use crate::trace::{TraceEntry, Tracer};
+
+// ----- Consts ----- //
+
+/// Machine cycles the interpreter gets in each frame on the VIP.
+const VIP_FRAME_BUDGET: i64 = (timing::CYCLES_PER_FRAME - timing::VBLANK_CYCLES) as i64;
+
+// ----- Structs ----- //
+
+/// How instructions are run.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Backend {
+    /// Decode and run one instruction at a time.
+    Interpreter,
+    /// Translate basic blocks to closures, and run those instead where
+    /// possible.
+    Recompiler,
+}
+
+/// A CHIP-8 machine, run one 60Hz frame at a time.
+pub struct Machine {
+    cpu: CPU,
+    speed: Speed,
+    timing: Timing,
+    overrun: i64,  // Machine cycles the last frame ran over its budget
+    resume: Option<i64>,  // What's left of the frame a breakpoint interrupted
+}
+
+impl Backend {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "interpreter" => { Some(Backend::Interpreter) }
+            "recompiler" => { Some(Backend::Recompiler) }
+            _ => { None }
+        }
+    }
+}
+
+impl Machine {
+    pub fn new(rom: &RomImage, settings: &Settings) -> Self {
+        return Machine {
+            cpu: CPU::new(rom, settings),
+            speed: Speed::new(settings.speed, settings.fast_forward, settings.slow_motion),
+            timing: settings.timing,
+            overrun: 0,
+            resume: None,
+        };
+    }
+
+    pub fn display(&self) -> Display<'_> {
+        return self.cpu.display();
+    }
+
+    pub fn keypad_mut(&mut self) -> &mut Keypad {
+        return self.cpu.keypad_mut();
+    }
+
+    pub fn speed(&self) -> &Speed {
+        return &self.speed;
+    }
+
+    pub fn speed_mut(&mut self) -> &mut Speed {
+        return &mut self.speed;
+    }
+
+    pub fn random(&self) -> &Random {
+        return self.cpu.random();
+    }
+
+    /// Put the code page of the VIP `interpreter` image in place, for the VIP
+    /// random number generator to read.
+    pub fn load_rng_code(&mut self, interpreter: &[u8]) {
+        self.cpu.load_rng_code(interpreter);
+    }
+
+    /// Replace the random number generator, and its state.
+    pub fn set_random(&mut self, random: Random) {
+        self.cpu.set_random(random);
+    }
+
+    pub fn set_tracer(&mut self, tracer: Tracer) {
+        self.cpu.set_tracer(tracer);
+    }
+
+    /// Get the amount of instructions run so far.
+    pub fn instructions(&self) -> u64 {
+        return self.cpu.instructions();
+    }
+
+    pub fn set_backend(&mut self, backend: Backend) {
+        self.cpu.set_recompiler(backend == Backend::Recompiler);
+    }
+
+    /// Run statically recompiled code, as generated by the `recompile` tool
+    /// for this machine's ROM, wherever `lookup` has some.
+    pub fn set_static_code(&mut self, lookup: StaticCode) {
+        self.cpu.set_static_code(lookup);
+    }
+
+    /// Run the custom instructions in `extensions` in place of invalid ones.
+    pub fn set_extensions(&mut self, extensions: Arc<Extensions>) {
+        self.cpu.set_extensions(extensions);
+    }
+
+    /// Check the assertions, and stop at the breakpoints, in `debug`.
+    pub fn set_debug(&mut self, debug: Arc<DebugInfo>) {
+        self.cpu.set_debug(debug);
+    }
+
+    /// Describe where the program counter is, with the label and source line
+    /// there when the debug info knows them.
+    pub fn location(&self) -> String {
+        return self.cpu.location();
+    }
+
+    /// Take the output of the debug console since the last call.
+    pub fn take_console(&mut self) -> String {
+        return self.cpu.take_console();
+    }
+
+    /// Turn the decoded instruction cache on or off. It is on by default, and
+    /// only worth turning off to measure what it brings.
+    pub fn set_decode_cache(&mut self, enabled: bool) {
+        self.cpu.set_decode_cache(enabled);
+    }
+
+    /// Take the entries a buffered tracer recorded since the last call.
+    pub fn take_trace(&mut self) -> Vec<TraceEntry> {
+        return self.cpu.take_trace();
+    }
+
+    /// Run a single frame: a batch of instructions, followed by the vblank,
+    /// which ticks the timers. The batch is cut short by instructions that
+    /// wait for the vblank, and once the program is found waiting for a key
+    /// or spinning idle, since nothing can change before the next frame.
+    ///
+    /// A breakpoint interrupts the frame, before the vblank, and the next call
+    /// picks it up where it stopped.
+    ///
+    /// returns The status of the last instruction to run.
+    pub fn run_frame(&mut self) -> Result<Status, Error> {
+        let mut status = Status::Running;
+
+        match self.timing {
+            Timing::Fixed => {
+                let mut remaining = self.resume.take().map_or(self.speed.instructions_per_frame(), |left| left as u32);
+                while remaining > 0 {
+                    let batch = self.cpu.run_batch(remaining, i64::MAX)?;
+                    status = batch.status;
+                    if status == Status::Breakpoint {
+                        self.resume = Some(remaining as i64);
+                        return Ok(status);
+                    }
+                    remaining -= batch.instructions;
+                    if status != Status::Running {
+                        break;
+                    }
+                }
+            }
+            Timing::Vip => {
+                // Cycles over the budget are taken out of the next frame.
+                let mut budget = self.resume.take().unwrap_or(VIP_FRAME_BUDGET - self.overrun);
+                while budget > 0 {
+                    let batch = self.cpu.run_batch(u32::MAX, budget)?;
+                    budget -= batch.cycles as i64;
+                    status = batch.status;
+                    if status == Status::Breakpoint {
+                        self.resume = Some(budget);
+                        return Ok(status);
+                    }
+                    if status != Status::Running {
+                        budget = budget.min(0);
+                        break;
+                    }
+                }
+                self.overrun = -budget;
+            }
+        }
+
+        self.cpu.tick_timers();
+        return Ok(status);
+    }
+
+    /// Describe the state of the registers, for dumps.
+    pub fn dump_registers(&self) -> String {
+        return self.cpu.dump_registers();
+    }
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn breakpoints_stop_the_frame_and_resume_past_it_once() {
+        // Count up in v0 forever, with a breakpoint on the addition.
+        let rom = RomImage::from(vec![0x60, 0x00, 0x70, 0x01, 0x12, 0x02]);
+        let args: Vec<String> = ["test.ch8", "--seed", "0"].iter().map(|arg| arg.to_string()).collect();
+        let mut machine = Machine::new(&rom, &Config::from_args(&args).unwrap().settings(&rom));
+        machine.set_debug(Arc::new(DebugInfo::parse("202 break").unwrap()));
+
+        assert!(machine.run_frame().ok() == Some(Status::Breakpoint));
+        assert!(machine.dump_registers().starts_with("v0=00 "));
+        assert!(machine.run_frame().ok() == Some(Status::Breakpoint));
+        assert!(machine.dump_registers().starts_with("v0=01 "));
+        assert!(machine.dump_registers().contains("PC=202"));
+    }
+}
@@ -1,29 +1,153 @@
-// ----- Modules ----- //
-
-mod cpu;
-mod display;
-mod keyboard;
-mod memory;
-mod registers;
-mod stack;
-mod timers;
+#![allow(clippy::needless_return, clippy::upper_case_acronyms)]
 
 // ----- Imports ----- //
 
-use std::env;
-use std::io::stdout;
-use crossterm::{execute, terminal};
+use std::{env, fs, io, process};
+use std::io::{BufWriter, Write};
+use std::sync::Arc;
+
+use chip_8::{assembler, batch, diff, disassembler, headless, static_recompiler, unit};
+use chip_8::config;
+use chip_8::config::Config;
+use chip_8::debug::DebugInfo;
+use chip_8::input::InputScript;
+use chip_8::machine::Machine;
+use chip_8::rom::RomImage;
+use chip_8::terminal::Terminal;
+use chip_8::trace::{TraceFilter, Tracer};
+use chip_8::vip::Vip;
 
 // ----- Main Entry Point ----- //
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        println!("Missing target executable!");
+    match args.get(1).map(String::as_str) {
+        Some("trace-diff") => { process::exit(diff::command(&args[2..])); }
+        Some("asm") => { process::exit(assembler::command(&args[2..])); }
+        Some("disasm") => { process::exit(disassembler::command(&args[2..])); }
+        Some("batch") => { process::exit(batch::command(&args[2..])); }
+        Some("recompile") => { process::exit(static_recompiler::command(&args[2..])); }
+        Some("test") => { process::exit(unit::command(&args[2..])); }
+        _ => {}
+    }
+
+    let config = match Config::from_args(&args[1..]) {
+        Ok(config) => { config }
+        Err(message) => { fail(&message, config::DEFAULT_ERROR_EXIT); }
+    };
+
+    let rom = match RomImage::load(&config.rom_path) {
+        Ok(rom) => { rom }
+        Err(error) => { fail(&format!("Failed to read {}: {}", config.rom_path, error), config.exit_on_error); }
+    };
+    let settings = config.settings(&rom);
+
+    let title = match rom.info() {
+        Some(info) => {
+            match info.author {
+                Some(author) => { format!("{} by {} ({})", info.title, author, settings.platform) }
+                None => { format!("{} ({})", info.title, settings.platform) }
+            }
+        }
+        None => { format!("{} ({})", rom.sha1(), settings.platform) }
+    };
+
+    if let Some(interpreter_path) = &config.vip_interpreter {
+        let interpreter = match fs::read(interpreter_path) {
+            Ok(data) => { data }
+            Err(error) => { fail(&format!("Failed to read {}: {}", interpreter_path, error), config.exit_on_error); }
+        };
+        let monitor = match &config.vip_monitor {
+            Some(path) => {
+                match fs::read(path) {
+                    Ok(data) => { Some(data) }
+                    Err(error) => { fail(&format!("Failed to read {}: {}", path, error), config.exit_on_error); }
+                }
+            }
+            None => { None }
+        };
+
+        let mut terminal = Terminal::new(&title, settings.keymap);
+        let mut vip = Vip::new(&interpreter, monitor, &rom);
+        vip.run(&mut terminal);
         return;
     }
 
-    let _ = execute!(stdout(), terminal::Clear(terminal::ClearType::All));
-    let mut chip8 = cpu::CPU::new(&args[1]);
-    chip8.run();
+    if config.seed.is_none() {
+        eprintln!("Random seed: {:#x} (replay with --seed {:#x})", settings.seed, settings.seed);
+    }
+    let mut machine = Machine::new(&rom, &settings);
+    machine.set_backend(config.backend);
+    match config.load_rng_code() {
+        Ok(Some(code)) => { machine.load_rng_code(&code); }
+        Ok(None) => {}
+        Err(message) => { fail(&message, config.exit_on_error); }
+    }
+    if let Some(path) = &config.debug {
+        match DebugInfo::load(path) {
+            Ok(debug) => { machine.set_debug(Arc::new(debug)); }
+            Err(message) => { fail(&message, config.exit_on_error); }
+        }
+    }
+    if let Some(format) = config.trace {
+        let out: Box<dyn Write + Send> = match &config.trace_file {
+            Some(path) => {
+                match fs::File::create(path) {
+                    Ok(file) => { Box::new(BufWriter::new(file)) }
+                    Err(error) => { fail(&format!("Failed to create {}: {}", path, error), config.exit_on_error); }
+                }
+            }
+            None => { Box::new(io::stderr()) }
+        };
+        let filter = TraceFilter {
+            range: config.trace_range.clone(),
+            kinds: config.trace_kinds.clone(),
+        };
+        let mut tracer = Tracer::new(format, filter, out);
+        tracer.header(settings.seed);
+        if config.trace_memory {
+            tracer = tracer.with_memory();
+        }
+        machine.set_tracer(tracer);
+    }
+    let stop_on_halt = config.headless || config.exit_on_halt.is_some();
+    let result = if config.headless {
+        let input = match &config.input {
+            Some(path) => {
+                match InputScript::load(path) {
+                    Ok(input) => { Some(input) }
+                    Err(message) => { fail(&message, config.exit_on_error); }
+                }
+            }
+            None => { None }
+        };
+        headless::run(&mut machine, config.frames, input.as_ref(), &mut |output| eprint!("{}", output))
+    } else {
+        let mut terminal = Terminal::new(&title, settings.keymap);
+        let result = terminal.run(&mut machine, config.frames, stop_on_halt);
+        terminal.finish();
+        result
+    };
+
+    if let Err(error) = &result {
+        eprintln!("{}", error);
+    }
+    let code = config.exit_code(&result);
+
+    if config.dump_screen {
+        print!("{}", machine.display().dump());
+    }
+    if config.dump_registers {
+        print!("{}", machine.dump_registers());
+    }
+
+    // Exiting skips destructors, and the trace needs flushing.
+    drop(machine);
+    process::exit(code);
+}
+
+/// Report the `message`, and exit with `code`.
+fn fail(message: &str, code: i32) -> ! {
+    eprintln!("{}", message);
+    process::exit(code);
 }
@@ -1,16 +1,20 @@
 // ----- Modules ----- //
 
 pub mod address;
+mod cache;
 
 // ----- Imports ----- //
 
-use std::fs;
+use alloc::vec::Vec;
 use address::Address;
+use cache::{CodeMap, DecodeCache};
+
+use crate::cpu::instructions::Instruction;
 
 // ----- Consts ----- //
 
-const MEMORY_SIZE: usize = 0x1000;  // 2 ** 12
-const FONT_PATH: &str = r"resources\font.bin";
+pub const MEMORY_SIZE: usize = 0x1000;  // 2 ** 12
+const FONT: &[u8] = include_bytes!("../resources/font.bin");
 pub const FONT_ADDR: usize = 0x50;
 pub const FONT_HEIGHT: usize = 5;
 const EXE_ADDR: usize = 0x200;
@@ -19,16 +23,24 @@ const EXE_ADDR: usize = 0x200;
 
 pub struct Memory {
     buffer: [u8; MEMORY_SIZE],
+    cache: DecodeCache,
+    code: CodeMap,
+    port: Option<usize>,
+    port_writes: Vec<u8>,
 }
 
 impl Memory {
-    pub fn new(exe_path: &str) -> Self {
+    pub fn new(program: &[u8]) -> Self {
         let mut result = Memory {
-            buffer: [0; MEMORY_SIZE]
+            buffer: [0; MEMORY_SIZE],
+            cache: DecodeCache::new(MEMORY_SIZE),
+            code: CodeMap::new(MEMORY_SIZE),
+            port: None,
+            port_writes: Vec::new(),
         };
 
-        result.load_file(Address::from(FONT_ADDR), FONT_PATH);
-        result.load_file(Address::from(EXE_ADDR), exe_path);
+        result.write(Address::from(FONT_ADDR), FONT);
+        result.write(Address::from(EXE_ADDR), program);
 
         return result;
     }
@@ -43,7 +55,7 @@ impl Memory {
         let index = address.get();
 
         for i in 0..len {
-            if index + i > MEMORY_SIZE {
+            if index + i >= MEMORY_SIZE {
                 break;
             }
             result.push(self.buffer[index + i]);
@@ -52,6 +64,11 @@ impl Memory {
         return result;
     }
 
+    /// Read the byte at the given `address`.
+    pub fn read_byte(&self, address: Address) -> u8 {
+        return self.buffer[address.get()];
+    }
+
     /// Write the given `data` to memory, at the given `address`. If too much
     /// data is given (i.e. last address is oob), write as much as possible and
     /// stop there.
@@ -72,12 +89,64 @@ impl Memory {
             i += 1;
         }
 
+        self.cache.invalidate(base_address, i);
+        self.code.write(base_address, i);
+        if let Some(port) = self.port.filter(|&port| base_address <= port && port < base_address + i) {
+            self.port_writes.push(self.buffer[port]);
+        }
         return i;
     }
 
-    pub fn load_file(&mut self, address: Address, file_path: &str) {
-        let data =  fs::read(file_path).expect("Failed to read data.");
+    /// Fetch the instruction at the given `address`, decoding it only if it
+    /// isn't in the cache already.
+    ///
+    /// returns The opcode and the instruction, or nothing past the end of
+    /// memory.
+    pub fn fetch(&mut self, address: Address) -> Option<(u16, Instruction)> {
+        let index = address.get();
+        if index + 1 >= MEMORY_SIZE {
+            return None;
+        }
+        if let Some(entry) = self.cache.get(index) {
+            return Some(entry);
+        }
+
+        let opcode = ((self.buffer[index] as u16) << 8) | (self.buffer[index + 1] as u16);
+        let instruction = Instruction::from(opcode);
+        self.cache.insert(index, opcode, instruction);
+        return Some((opcode, instruction));
+    }
+
+    /// Mark the addresses from `start` up to (not including) `end` as holding
+    /// compiled code, to be told when they change.
+    pub fn mark_code(&mut self, start: usize, end: usize) {
+        self.code.mark(start, end);
+    }
+
+    /// Check whether any compiled code was written to since the last call to
+    /// `take_written_code`.
+    pub fn is_code_written(&self) -> bool {
+        return self.code.is_written();
+    }
+
+    /// Take the addresses of compiled code written to since the last call.
+    pub fn take_written_code(&mut self) -> Vec<usize> {
+        return self.code.take_written();
+    }
+
+    /// Watch the console port at `address`: every byte written to it, by any
+    /// part of the machine, is kept until taken.
+    pub fn set_port(&mut self, address: usize) {
+        self.port = Some(address);
+    }
+
+    /// Take the bytes written to the console port since the last call.
+    pub fn take_port_writes(&mut self) -> Vec<u8> {
+        return core::mem::take(&mut self.port_writes);
+    }
 
-        self.write(address, &data);
+    /// Turn the decoded instruction cache on or off.
+    pub fn set_decode_cache(&mut self, enabled: bool) {
+        self.cache.set_enabled(enabled);
     }
 }
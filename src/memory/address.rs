@@ -1,6 +1,6 @@
 // ----- Consts ----- //
 
-use std::fmt::Formatter;
+use core::fmt::Formatter;
 
 pub const MAX_ADDRESS: usize = 0x0FFF;
 
@@ -21,12 +21,12 @@ impl From<usize> for Address {
     /// Get address from the given `addr` (as in, u16). Dismiss the upper nibble
     /// and keep the 12-bit value as a valid address.
     fn from(addr: usize) -> Self {
-        return Address { value: (addr & 0x0FFF) as usize };
+        return Address { value: addr & 0x0FFF };
     }
 }
 
-impl std::fmt::Display for Address {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for Address {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(f, "Address:0x{:03x}", self.value)
     }
 }
@@ -0,0 +1,92 @@
+// ----- Imports ----- //
+
+use alloc::vec;
+use alloc::vec::Vec;
+use crate::cpu::instructions::Instruction;
+
+// ----- Structs ----- //
+
+/// Instructions decoded from memory, indexed by their address, so that each
+/// one is only decoded once until the memory under it changes.
+pub struct DecodeCache {
+    entries: Vec<Option<(u16, Instruction)>>,  // Opcode and instruction at each address
+    enabled: bool,
+}
+
+impl DecodeCache {
+    pub fn new(size: usize) -> Self {
+        return DecodeCache {
+            entries: vec![None; size],
+            enabled: true,
+        };
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.entries.fill(None);
+    }
+
+    pub fn get(&self, address: usize) -> Option<(u16, Instruction)> {
+        return self.entries[address];
+    }
+
+    pub fn insert(&mut self, address: usize, opcode: u16, instruction: Instruction) {
+        if self.enabled {
+            self.entries[address] = Some((opcode, instruction));
+        }
+    }
+
+    /// Forget the instructions overlapping the `len` bytes written at
+    /// `address`, including the one starting on the byte before.
+    pub fn invalidate(&mut self, address: usize, len: usize) {
+        let start = address.saturating_sub(1);
+        let end = (address + len).min(self.entries.len());
+        if start < end {
+            self.entries[start..end].fill(None);
+        }
+    }
+}
+
+/// Addresses holding compiled code, and the ones written to since they were
+/// compiled, so that the compiled code can be thrown away.
+pub struct CodeMap {
+    code: Vec<bool>,
+    written: Vec<usize>,
+}
+
+impl CodeMap {
+    pub fn new(size: usize) -> Self {
+        return CodeMap {
+            code: vec![false; size],
+            written: Vec::new(),
+        };
+    }
+
+    /// Mark the addresses from `start` up to (not including) `end` as code.
+    pub fn mark(&mut self, start: usize, end: usize) {
+        let end = end.min(self.code.len());
+        if start < end {
+            self.code[start..end].fill(true);
+        }
+    }
+
+    /// Note the `len` bytes written at `address`.
+    pub fn write(&mut self, address: usize, len: usize) {
+        let end = (address + len).min(self.code.len());
+        for address in address..end {
+            if self.code[address] {
+                self.code[address] = false;
+                self.written.push(address);
+            }
+        }
+    }
+
+    pub fn is_written(&self) -> bool {
+        return !self.written.is_empty();
+    }
+
+    /// Take the code addresses written since the last call.
+    pub fn take_written(&mut self) -> Vec<usize> {
+        return core::mem::take(&mut self.written);
+    }
+}
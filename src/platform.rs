@@ -0,0 +1,209 @@
+// ----- Imports ----- //
+
+use core::fmt::Formatter;
+
+use crate::random::Generator;
+use crate::timing::Timing;
+
+// ----- Structs ----- //
+
+/// The machine a ROM was written for. Each platform comes with its own set of
+/// quirks, which decide how ambiguous instructions behave.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Platform {
+    Chip8,
+    CosmacVip,
+    SuperChip,
+    XoChip,
+}
+
+/// Where and how the call stack is kept.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct StackLayout {
+    /// Maximal amount of nested calls.
+    pub depth: usize,
+    /// Address of the stack in memory, or `None` to keep it outside of the
+    /// addressable memory.
+    pub base: Option<usize>,
+}
+
+/// Where the interpreter keeps its own data.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct MemoryLayout {
+    pub stack: StackLayout,
+    /// Address of the copy of the variable registers in the interpreter work
+    /// area, if they are kept in memory.
+    pub variables: Option<usize>,
+    /// Address of the framebuffer, if the display is backed by memory.
+    pub display: Option<usize>,
+}
+
+/// Behaviour switches for instructions that differ between interpreters.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct Quirks {
+    /// `SHR`/`SHL` shift vX in place, ignoring vY.
+    pub shift: bool,
+    /// `STM`/`LDM` leave I pointing right after the last register.
+    pub memory_increment: bool,
+    /// `JMPO` adds vX (the upper nibble of the address) instead of v0.
+    pub jump: bool,
+    /// `OR`/`AND`/`XOR` reset vF to 0.
+    pub logic: bool,
+    /// Sprites wrap around the edges of the screen instead of being clipped.
+    pub wrap: bool,
+    /// `DRAW` waits for the next vblank before the program goes on.
+    pub display_wait: bool,
+}
+
+impl Platform {
+    /// Get the platform matching the given `name`. Both the short names used
+    /// on the command line and the identifiers of the community CHIP-8
+    /// database are accepted.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "chip8" | "chip-8" | "modernchip8" => { Some(Platform::Chip8) }
+            "vip" | "cosmacvip" | "originalchip8" => { Some(Platform::CosmacVip) }
+            "schip" | "superchip" | "superchip1" => { Some(Platform::SuperChip) }
+            "xochip" | "xo-chip" => { Some(Platform::XoChip) }
+            _ => { None }
+        }
+    }
+
+    /// Get the quirks the platform's interpreter is known for.
+    pub fn quirks(&self) -> Quirks {
+        match self {
+            Platform::Chip8 => {
+                Quirks {
+                    shift: false,
+                    memory_increment: false,
+                    jump: false,
+                    logic: false,
+                    wrap: false,
+                    display_wait: false,
+                }
+            }
+            Platform::CosmacVip => {
+                Quirks {
+                    shift: false,
+                    memory_increment: true,
+                    jump: false,
+                    logic: true,
+                    wrap: false,
+                    display_wait: true,
+                }
+            }
+            Platform::SuperChip => {
+                Quirks {
+                    shift: true,
+                    memory_increment: false,
+                    jump: true,
+                    logic: false,
+                    wrap: false,
+                    display_wait: false,
+                }
+            }
+            Platform::XoChip => {
+                Quirks {
+                    shift: false,
+                    memory_increment: true,
+                    jump: false,
+                    logic: false,
+                    wrap: true,
+                    display_wait: false,
+                }
+            }
+        }
+    }
+
+    /// Get the instruction timing of the platform's interpreter. Only the VIP
+    /// has well-known timings; elsewhere the speed decides.
+    pub fn timing(&self) -> Timing {
+        match self {
+            Platform::CosmacVip => { Timing::Vip }
+            _ => { Timing::Fixed }
+        }
+    }
+
+    /// Get the random number generator of the platform's interpreter.
+    pub fn generator(&self) -> Generator {
+        match self {
+            Platform::CosmacVip => { Generator::Vip }
+            _ => { Generator::Xorshift }
+        }
+    }
+
+    /// Get the memory layout of the platform's interpreter.
+    pub fn layout(&self) -> MemoryLayout {
+        match self {
+            Platform::CosmacVip => { MemoryLayout::VIP }
+            _ => { MemoryLayout::FLAT }
+        }
+    }
+}
+
+impl MemoryLayout {
+    /// Everything but the program is kept outside of the addressable memory.
+    pub const FLAT: MemoryLayout = MemoryLayout {
+        stack: StackLayout { depth: 16, base: None },
+        variables: None,
+        display: None,
+    };
+
+    /// The layout of the original COSMAC VIP interpreter, at the top of its 4K
+    /// of RAM: the stack at 0xEA0, the interpreter work area (ending with the
+    /// variable registers) at 0xED0 and the framebuffer at 0xF00.
+    pub const VIP: MemoryLayout = MemoryLayout {
+        stack: StackLayout { depth: 12, base: Some(0xEA0) },
+        variables: Some(0xEF0),
+        display: Some(0xF00),
+    };
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "flat" => { Some(MemoryLayout::FLAT) }
+            "vip" => { Some(MemoryLayout::VIP) }
+            _ => { None }
+        }
+    }
+}
+
+impl Default for Platform {
+    fn default() -> Self {
+        return Platform::Chip8;
+    }
+}
+
+impl core::fmt::Display for Platform {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Platform::Chip8 => { write!(f, "chip8") }
+            Platform::CosmacVip => { write!(f, "vip") }
+            Platform::SuperChip => { write!(f, "schip") }
+            Platform::XoChip => { write!(f, "xochip") }
+        }
+    }
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        return Platform::default().quirks();
+    }
+}
+
+impl Quirks {
+    /// Set the quirk called `name` to `value`.
+    ///
+    /// returns `false` if there is no quirk with that name.
+    pub fn set(&mut self, name: &str, value: bool) -> bool {
+        match name {
+            "shift" => { self.shift = value; }
+            "memory" | "memory_increment" => { self.memory_increment = value; }
+            "jump" => { self.jump = value; }
+            "logic" => { self.logic = value; }
+            "wrap" => { self.wrap = value; }
+            "vblank" | "display_wait" => { self.display_wait = value; }
+            _ => { return false; }
+        }
+        return true;
+    }
+}
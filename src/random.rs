@@ -0,0 +1,216 @@
+// ----- Imports ----- //
+
+use crate::memory::address::Address;
+use crate::memory::Memory;
+
+// ----- Consts ----- //
+
+/// Seed for runs that weren't given one, when there is no OS generator to pick
+/// one instead.
+#[cfg(not(feature = "std"))]
+const DEFAULT_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+/// The page of memory the VIP interpreter's code is in, past its first page,
+/// which its generator reads as a table.
+pub const VIP_CODE_PAGE: usize = 0x100;
+pub const VIP_PAGE_SIZE: usize = 0x100;
+
+// ----- Structs ----- //
+
+/// Algorithm `RAND` gets its random bytes from.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Generator {
+    /// A xorshift generator.
+    Xorshift,
+    /// The routine of the COSMAC VIP interpreter, which reads the
+    /// interpreter's own code as a table: it needs the interpreter image in
+    /// memory to give a VIP's values.
+    Vip,
+}
+
+/// Source of the random bytes `RAND` reads. It is part of the machine state,
+/// so that runs with the same seed (and the same input) play out the same.
+#[derive(Clone)]
+pub struct Random {
+    seed: u64,
+    state: State,
+}
+
+#[derive(Clone)]
+enum State {
+    Xorshift(u64),
+    /// The VIP interpreter's R9: the low byte counts frames and calls, and
+    /// indexes the code page; the high byte holds the last value.
+    Vip(u16),
+}
+
+impl Generator {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "xorshift" => { Some(Generator::Xorshift) }
+            "vip" => { Some(Generator::Vip) }
+            _ => { None }
+        }
+    }
+}
+
+impl Random {
+    /// Create a generator starting from the `seed`. The VIP generator starts
+    /// with its low 16 bits in R9.
+    pub fn new(generator: Generator, seed: u64) -> Self {
+        let state = match generator {
+            Generator::Xorshift => {
+                // Spread the seed over every bit, as xorshift takes a while to
+                // get going from small states, and gets stuck on 0.
+                let mut spread = (seed ^ (seed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+                spread = (spread ^ (spread >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+                spread ^= spread >> 31;
+                State::Xorshift(if spread == 0 { u64::MAX } else { spread })
+            }
+            Generator::Vip => { State::Vip(seed as u16) }
+        };
+        return Random { seed, state };
+    }
+
+    /// Get the seed the generator started from, to replay a run.
+    pub fn seed(&self) -> u64 {
+        return self.seed;
+    }
+
+    /// Get the next random byte. The VIP generator reads the code page of
+    /// `memory`, as the interpreter does: it increments R9, adds the byte
+    /// R9.0 points to there to R9.1, and adds that sum to itself rotated right
+    /// through the carry, giving the new R9.1.
+    pub fn next_byte(&mut self, memory: &Memory) -> u8 {
+        match &mut self.state {
+            State::Xorshift(state) => { xorshift(state) }
+            State::Vip(r9) => {
+                let [high, low] = r9.wrapping_add(1).to_be_bytes();
+                let code = memory.read_byte(Address::from(VIP_CODE_PAGE + low as usize));
+                let (sum, carry) = high.overflowing_add(code);
+                let value = (((carry as u8) << 7) | (sum >> 1)).wrapping_add(sum);
+                *r9 = u16::from_be_bytes([value, low]);
+                value
+            }
+        }
+    }
+
+    /// Advance by a frame. Only the VIP generator depends on time: its
+    /// counter is also incremented by the vblank interrupt.
+    pub fn tick(&mut self) {
+        if let State::Vip(r9) = &mut self.state {
+            *r9 = r9.wrapping_add(1);
+        }
+    }
+}
+
+// ----- Functions ----- //
+
+/// Pick a seed for runs that weren't given one, from the OS generator.
+#[cfg(feature = "std")]
+pub fn default_seed() -> u64 {
+    return rand::random();
+}
+
+/// Pick a seed for runs that weren't given one. Without an OS generator, this
+/// is always the same.
+#[cfg(not(feature = "std"))]
+pub fn default_seed() -> u64 {
+    return DEFAULT_SEED;
+}
+
+/// Step the xorshift64 generator in `state`, returning its top byte.
+fn xorshift(state: &mut u64) -> u8 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    return (*state >> 56) as u8;
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use crate::cdp1802::Cdp1802;
+
+    /// The `RAND` routine of the VIP interpreter, with the mask at R5 and the
+    /// variable register at R6.
+    const VIP_ROUTINE: [u8; 18] = [
+        0x19,        // INC R9
+        0x89, 0xAE,  // GLO R9, PLO RE
+        0x93, 0xBE,  // GHI R3, PHI RE: RE points into the code page
+        0x99, 0xEE,  // GHI R9, SEX RE
+        0xF4, 0x56,  // ADD, STR R6
+        0x76,        // SHRC
+        0xE6, 0xF4,  // SEX R6, ADD
+        0xB9, 0x56,  // PHI R9, STR R6
+        0x45, 0xF2,  // LDA R5, AND
+        0x56, 0xD4,  // STR R6, SEP R4
+    ];
+    const ROUTINE_ADDR: usize = 0x1E0;
+
+    /// Memory with a made up code page, holding the routine at the end like
+    /// the interpreter does.
+    fn code_page() -> Memory {
+        let mut memory = Memory::new(&[]);
+        let page: Vec<u8> = (0..VIP_PAGE_SIZE).map(|i| (i * 37 + 11) as u8).collect();
+        memory.write(Address::from(VIP_CODE_PAGE), &page);
+        memory.write(Address::from(ROUTINE_ADDR), &VIP_ROUTINE);
+        memory.write(Address::from(0x300), &[0xFF]);
+        return memory;
+    }
+
+    fn sequence(random: &mut Random, memory: &Memory) -> Vec<u8> {
+        return (0..64).map(|_| random.next_byte(memory)).collect();
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_sequence() {
+        let memory = code_page();
+        for generator in [Generator::Xorshift, Generator::Vip] {
+            let (mut a, mut b) = (Random::new(generator, 1234), Random::new(generator, 1234));
+            assert_eq!(sequence(&mut a, &memory), sequence(&mut b, &memory));
+            a.tick();
+            b.tick();
+            assert_eq!(sequence(&mut a, &memory), sequence(&mut b, &memory));
+            assert_ne!(sequence(&mut Random::new(generator, 1234), &memory),
+                       sequence(&mut Random::new(generator, 1235), &memory));
+            assert_eq!(a.clone().next_byte(&memory), a.next_byte(&memory));
+        }
+    }
+
+    #[test]
+    fn only_the_vip_generator_depends_on_time() {
+        let memory = code_page();
+        let mut ticked = Random::new(Generator::Xorshift, 0);
+        ticked.tick();
+        assert_eq!(sequence(&mut ticked, &memory), sequence(&mut Random::new(Generator::Xorshift, 0), &memory));
+
+        let mut ticked = Random::new(Generator::Vip, 0);
+        ticked.tick();
+        assert_ne!(sequence(&mut ticked, &memory), sequence(&mut Random::new(Generator::Vip, 0), &memory));
+        assert_eq!(ticked.seed(), 0);
+    }
+
+    #[test]
+    fn the_vip_generator_gives_what_the_vip_routine_does() {
+        let mut memory = code_page();
+        let mut random = Random::new(Generator::Vip, 0x12F0);
+        let mut cpu = Cdp1802::new();
+        cpu.set_register(0x9, 0x12F0);
+
+        for _ in 0..600 {
+            cpu.set_register(0x3, ROUTINE_ADDR as u16);
+            cpu.set_register(0x5, 0x300);
+            cpu.set_register(0x6, 0x310);
+            cpu.set_p(0x3);
+            while cpu.get_p() == 0x3 {
+                cpu.step(&mut memory);
+            }
+            let expected = memory.read_byte(Address::from(0x310));
+            assert_eq!(random.next_byte(&memory), expected);
+        }
+    }
+}
@@ -1,10 +1,11 @@
 // ----- Imports ----- //
 
+use crate::error::ErrorKind;
 use crate::memory::address::{MAX_ADDRESS, Address};
 
 // ----- Consts ----- //
 
-const VARIABLE_COUNT: usize = 0x10;
+pub const VARIABLE_COUNT: usize = 0x10;
 const FLAG_INDEX: usize = 0xF;
 const INITIAL_PC: usize = 0x200;
 
@@ -61,30 +62,31 @@ impl PC {
     }
 
     pub fn get(&self) -> Address {
-        return self.value.clone();
+        return self.value;
     }
 
     pub fn set(&mut self, value: Address) {
         self.value = value;
     }
 
-    pub fn increment(&mut self) {
+    pub fn increment(&mut self) -> Result<(), ErrorKind> {
         let current = self.value.get();
         let new = current + 2;
 
         if new >= MAX_ADDRESS {
-            panic!("Reached end of memory");
+            return Err(ErrorKind::EndOfMemory);
         }
         self.value = Address::from(new);
+        return Ok(());
     }
 
-    pub fn decrement(&mut self) {
+    pub fn decrement(&mut self) -> Result<(), ErrorKind> {
         let current = self.value.get();
-        let new = current - 2;
-
-        if new >= MAX_ADDRESS {
-            panic!("Reached end of memory");
+        if current < 2 {
+            return Err(ErrorKind::EndOfMemory);
         }
-        self.value = Address::from(new);
+
+        self.value = Address::from(current - 2);
+        return Ok(());
     }
 }
@@ -0,0 +1,50 @@
+// ----- Modules ----- //
+
+pub mod database;
+
+// ----- Imports ----- //
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::{fs, io};
+use sha1_smol::Sha1;
+
+use crate::rom::database::RomInfo;
+
+// ----- Structs ----- //
+
+/// A CHIP-8 program, as loaded from disk.
+pub struct RomImage {
+    data: Vec<u8>,
+    sha1: String,
+}
+
+impl RomImage {
+    #[cfg(feature = "std")]
+    pub fn load(file_path: &str) -> io::Result<Self> {
+        let data = fs::read(file_path)?;
+        return Ok(RomImage::from(data));
+    }
+
+    pub fn data(&self) -> &[u8] {
+        return &self.data;
+    }
+
+    /// Get the SHA-1 digest of the program, as a lowercase hex string.
+    pub fn sha1(&self) -> &str {
+        return &self.sha1;
+    }
+
+    /// Look the program up in the embedded ROM database.
+    pub fn info(&self) -> Option<&'static RomInfo> {
+        return database::lookup(&self.sha1);
+    }
+}
+
+impl From<Vec<u8>> for RomImage {
+    fn from(data: Vec<u8>) -> Self {
+        let sha1 = Sha1::from(&data).digest().to_string();
+        return RomImage { data, sha1 };
+    }
+}
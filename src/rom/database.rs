@@ -0,0 +1,115 @@
+// ----- Imports ----- //
+
+use crate::keyboard::Keymap;
+use crate::platform::{Platform, Quirks};
+
+// ----- Structs ----- //
+
+/// What is known about a specific ROM. Entries follow the layout of the
+/// community CHIP-8 database, where programs are keyed by their SHA-1 digest.
+pub struct RomInfo {
+    pub title: &'static str,
+    pub author: Option<&'static str>,
+    pub platform: Platform,
+    /// Quirks that differ from the platform's defaults, if any.
+    pub quirks: Option<Quirks>,
+    pub keymap: Option<Keymap>,
+    /// Recommended speed, in instructions per frame.
+    pub speed: Option<u32>,
+}
+
+// ----- Consts ----- //
+
+/// Keys typed as the hexadecimal digit of the CHIP-8 key they press.
+const HEX_KEYS: Keymap = Keymap::new([
+    '0', '1', '2', '3',
+    '4', '5', '6', '7',
+    '8', '9', 'a', 'b',
+    'c', 'd', 'e', 'f',
+]);
+
+/// Entries for the ROMs in `test_roms`, with the settings they were written
+/// for. Entries of the community database go in as they are, by SHA-1.
+const DATABASE: &[(&str, RomInfo)] = &[
+    ("1ba58656810b67fd131eb9af3e3987863bf26c90", RomInfo {
+        title: "IBM Logo",
+        author: None,
+        platform: Platform::CosmacVip,
+        quirks: None,
+        keymap: None,
+        speed: None,
+    }),
+    ("f1cfcffe1937ed6dd6eeed1a7f85dfc777bda700", RomInfo {
+        title: "Chip-8 Test Rom",
+        author: Some("corax89"),
+        platform: Platform::Chip8,
+        quirks: None,
+        keymap: None,
+        speed: None,
+    }),
+    ("5657672aa2a67c4f77cb4b8000165c65c00ee226", RomInfo {
+        title: "Count",
+        author: None,
+        platform: Platform::Chip8,
+        quirks: None,
+        keymap: None,
+        speed: None,
+    }),
+    ("ba603bde1d8596c575e81096fff3cea40173d7e3", RomInfo {
+        title: "Delay Timer Test",
+        author: None,
+        platform: Platform::Chip8,
+        quirks: None,
+        keymap: None,
+        speed: None,
+    }),
+    ("8f19d92d5eb05045a29989df71f396914028d790", RomInfo {
+        title: "Random Number",
+        author: None,
+        platform: Platform::Chip8,
+        quirks: None,
+        keymap: None,
+        speed: None,
+    }),
+    ("b7b46ad49871e54302496c95c41be842e4a4abdf", RomInfo {
+        title: "Random Number Test",
+        author: None,
+        platform: Platform::Chip8,
+        quirks: None,
+        keymap: None,
+        speed: None,
+    }),
+    ("882b4739afb3e4593afc821748ffade79ddd6d23", RomInfo {
+        title: "Keyboard Test",
+        author: None,
+        platform: Platform::Chip8,
+        quirks: None,
+        keymap: Some(HEX_KEYS),
+        speed: None,
+    }),
+    ("1b4578e40e60b6b8e7f545088f8f4689077817b8", RomInfo {
+        title: "LDM Test",
+        author: None,
+        platform: Platform::Chip8,
+        quirks: None,
+        keymap: None,
+        speed: None,
+    }),
+    ("ecb220ff1c7b3941f6a06c516c72600491d09471", RomInfo {
+        title: "STM Test",
+        author: None,
+        platform: Platform::Chip8,
+        quirks: None,
+        keymap: None,
+        speed: None,
+    }),
+];
+
+// ----- Functions ----- //
+
+/// Find the database entry for the ROM with the given SHA-1 digest.
+pub fn lookup(sha1: &str) -> Option<&'static RomInfo> {
+    let sha1 = sha1.to_lowercase();
+    let (_, info) = DATABASE.iter().find(|(digest, _)| *digest == sha1)?;
+    return Some(info);
+}
@@ -0,0 +1,125 @@
+// ----- Imports ----- //
+
+use core::time::Duration;
+
+// ----- Consts ----- //
+
+/// Duration of a single 60Hz frame, at normal speed.
+pub const FRAME_DURATION: Duration = Duration::from_micros(16_667);
+
+pub const DEFAULT_FAST_FORWARD: u32 = 4;
+pub const DEFAULT_SLOW_MOTION: u32 = 4;
+
+const MAX_SPEED: u32 = 1000;
+
+// ----- Structs ----- //
+
+/// How fast the program runs. The speed is given in instructions per emulated
+/// frame, and fast-forward and slow-motion scale how long an emulated frame
+/// takes in real time. Timers are ticked once per emulated frame, so they stay
+/// in step with the program in every mode.
+pub struct Speed {
+    ipf: u32,
+    fast_forward: u32,
+    slow_motion: u32,
+    mode: Mode,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Mode {
+    Normal,
+    FastForward,
+    SlowMotion,
+}
+
+impl Speed {
+    pub fn new(ipf: u32, fast_forward: u32, slow_motion: u32) -> Self {
+        assert!(ipf > 0 && fast_forward > 0 && slow_motion > 0);
+
+        return Speed {
+            ipf,
+            fast_forward,
+            slow_motion,
+            mode: Mode::Normal,
+        };
+    }
+
+    /// Get the amount of instructions to run in each frame.
+    pub fn instructions_per_frame(&self) -> u32 {
+        return self.ipf;
+    }
+
+    /// Scale the emulated `duration` to real time, according to the current
+    /// mode.
+    pub fn scale(&self, duration: Duration) -> Duration {
+        match self.mode {
+            Mode::Normal => { duration }
+            Mode::FastForward => { duration / self.fast_forward }
+            Mode::SlowMotion => { duration * self.slow_motion }
+        }
+    }
+
+    /// Get how long a frame takes in real time.
+    pub fn frame_duration(&self) -> Duration {
+        return self.scale(FRAME_DURATION);
+    }
+
+    /// Run one more instruction in each frame, up to `MAX_SPEED`. Faster
+    /// speeds, given on the command line, are left as they are.
+    pub fn raise(&mut self) {
+        if self.ipf < MAX_SPEED {
+            self.ipf += 1;
+        }
+    }
+
+    /// Run one less instruction in each frame.
+    pub fn lower(&mut self) {
+        self.ipf = (self.ipf - 1).max(1);
+    }
+
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+    }
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speed_steps_by_one_within_bounds() {
+        let mut speed = Speed::new(2, 4, 4);
+        speed.raise();
+        assert_eq!(speed.instructions_per_frame(), 3);
+        speed.lower();
+        speed.lower();
+        speed.lower();
+        assert_eq!(speed.instructions_per_frame(), 1);
+
+        let mut speed = Speed::new(MAX_SPEED, 4, 4);
+        speed.raise();
+        assert_eq!(speed.instructions_per_frame(), MAX_SPEED);
+    }
+
+    #[test]
+    fn raising_never_slows_faster_speeds_down() {
+        let mut speed = Speed::new(MAX_SPEED * 2, 4, 4);
+        speed.raise();
+        assert_eq!(speed.instructions_per_frame(), MAX_SPEED * 2);
+        speed.lower();
+        assert_eq!(speed.instructions_per_frame(), MAX_SPEED * 2 - 1);
+    }
+
+    #[test]
+    fn modes_only_scale_real_time() {
+        let mut speed = Speed::new(12, 4, 3);
+        assert_eq!(speed.frame_duration(), FRAME_DURATION);
+        speed.set_mode(Mode::FastForward);
+        assert_eq!(speed.frame_duration(), FRAME_DURATION / 4);
+        speed.set_mode(Mode::SlowMotion);
+        assert_eq!(speed.frame_duration(), FRAME_DURATION * 3);
+        assert_eq!(speed.instructions_per_frame(), 12);
+    }
+}
@@ -1,57 +1,165 @@
 // ----- Imports ----- //
 
-use std::cell::RefCell;
-use std::rc::Rc;
+use alloc::vec::Vec;
+use crate::error::ErrorKind;
 use crate::memory::address::Address;
 use crate::memory::Memory;
+use crate::platform::StackLayout;
 
 // ----- Consts ----- //
 
-const STACK_BASE: usize = 0;
-const STACK_SIZE: usize = 0x20;
 const ADDRESS_SIZE: usize = 2;
 
 // ----- Structs ----- //
 
 pub struct Stack {
+    layout: StackLayout,
     size: usize,
-    memory: Rc<RefCell<Memory>>,
+    frames: Vec<Address>,  // Only used when the stack is outside of memory
+}
+
+/// Iterator over the return addresses on the call stack, starting with the
+/// innermost call.
+pub struct Frames<'a> {
+    stack: &'a Stack,
+    memory: &'a Memory,
+    index: usize,
 }
 
 impl Stack {
-    pub fn new(memory: Rc<RefCell<Memory>>) -> Self {
+    pub fn new(layout: StackLayout) -> Self {
         return Stack {
+            layout,
             size: 0,
-            memory,
+            frames: Vec::new(),
         };
     }
 
-    pub fn push(&mut self, address: Address) {
-        if self.size >= STACK_SIZE {
-            panic!("Stack overflow! Call stack is full.");
+    /// Push the return `address`, in `memory` if the layout keeps the stack
+    /// there.
+    pub fn push(&mut self, memory: &mut Memory, address: Address) -> Result<(), ErrorKind> {
+        if self.size >= self.layout.depth {
+            return Err(ErrorKind::StackOverflow);
         }
 
-        let write_addr = Address::from(STACK_BASE + (self.size * ADDRESS_SIZE));
-        let address_value = address.get();
-        let address_data = [
-            (address_value & 0xFF) as u8,
-            ((address_value >> 8) & 0xFF) as u8,
-        ];
-        self.memory.borrow_mut().write(write_addr, &address_data);
+        match self.layout.base {
+            Some(base) => {
+                // Addresses are kept big-endian, like the VIP interpreter did.
+                let write_addr = Address::from(base + (self.size * ADDRESS_SIZE));
+                let address_value = address.get();
+                let address_data = [
+                    ((address_value >> 8) & 0xFF) as u8,
+                    (address_value & 0xFF) as u8,
+                ];
+                memory.write(write_addr, &address_data);
+            }
+            None => { self.frames.push(address); }
+        }
         self.size += 1;
+
+        return Ok(());
     }
 
-    pub fn pop(&mut self) -> Address {
+    pub fn pop(&mut self, memory: &Memory) -> Result<Address, ErrorKind> {
         if self.size == 0 {
-            panic!("Call stack is empty.");
+            return Err(ErrorKind::StackUnderflow);
         }
 
+        let address = self.read(memory, self.size - 1);
+        self.frames.pop();
         self.size -= 1;
-        let read_address = Address::from(STACK_BASE + (self.size * ADDRESS_SIZE));
-        let memory = self.memory.borrow();
-        let address_data = memory.read(read_address, ADDRESS_SIZE);
-        let output_value = (address_data[0] as usize) | ((address_data[1] as usize) << 8);
 
-        return Address::from(output_value);
+        return Ok(address);
+    }
+
+    /// Amount of calls currently on the stack.
+    pub fn depth(&self) -> usize {
+        return self.size;
+    }
+
+    pub fn frames<'a>(&'a self, memory: &'a Memory) -> Frames<'a> {
+        return Frames { stack: self, memory, index: self.size };
+    }
+
+    /// Get the return address at the given `index`, counting from the bottom
+    /// of the stack.
+    fn read(&self, memory: &Memory, index: usize) -> Address {
+        assert!(index < self.size);
+
+        match self.layout.base {
+            Some(base) => {
+                let read_address = Address::from(base + (index * ADDRESS_SIZE));
+                let address_data = memory.read(read_address, ADDRESS_SIZE);
+                let output_value = ((address_data[0] as usize) << 8) | (address_data[1] as usize);
+
+                return Address::from(output_value);
+            }
+            None => { return self.frames[index]; }
+        }
+    }
+}
+
+impl Iterator for Frames<'_> {
+    type Item = Address;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == 0 {
+            return None;
+        }
+
+        self.index -= 1;
+        return Some(self.stack.read(self.memory, self.index));
+    }
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(stack: &Stack, memory: &Memory) -> Vec<usize> {
+        return stack.frames(memory).map(|address| address.get()).collect();
+    }
+
+    #[test]
+    fn frames_start_with_the_innermost_call() {
+        for base in [None, Some(0xEA0)] {
+            let mut memory = Memory::new(&[]);
+            let mut stack = Stack::new(StackLayout { depth: 12, base });
+            for address in [0x202, 0x314, 0x426] {
+                assert!(stack.push(&mut memory, Address::from(address)).is_ok());
+            }
+            assert_eq!(frames(&stack, &memory), vec![0x426, 0x314, 0x202]);
+
+            assert_eq!(stack.pop(&memory).ok().map(|address| address.get()), Some(0x426));
+            assert_eq!(frames(&stack, &memory), vec![0x314, 0x202]);
+            assert_eq!(stack.depth(), 2);
+        }
+    }
+
+    #[test]
+    fn stacks_in_memory_are_big_endian() {
+        let mut memory = Memory::new(&[]);
+        let mut stack = Stack::new(StackLayout { depth: 12, base: Some(0xEA0) });
+        assert!(stack.push(&mut memory, Address::from(0x202)).is_ok());
+        assert!(stack.push(&mut memory, Address::from(0xABC)).is_ok());
+        assert_eq!(memory.read(Address::from(0xEA0), 4), vec![0x02, 0x02, 0x0A, 0xBC]);
+
+        // Programs poking at the stack change where calls return to.
+        memory.write(Address::from(0xEA2), &[0x03, 0x48]);
+        assert_eq!(stack.pop(&memory).ok().map(|address| address.get()), Some(0x348));
+    }
+
+    #[test]
+    fn depth_is_limited_by_the_layout() {
+        let mut memory = Memory::new(&[]);
+        let mut stack = Stack::new(StackLayout { depth: 2, base: None });
+        assert!(stack.push(&mut memory, Address::from(0x200)).is_ok());
+        assert!(stack.push(&mut memory, Address::from(0x200)).is_ok());
+        assert!(matches!(stack.push(&mut memory, Address::from(0x200)), Err(ErrorKind::StackOverflow)));
+        assert!(stack.pop(&memory).is_ok());
+        assert!(stack.pop(&memory).is_ok());
+        assert!(matches!(stack.pop(&memory), Err(ErrorKind::StackUnderflow)));
     }
 }
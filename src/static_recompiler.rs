@@ -0,0 +1,319 @@
+// ----- Imports ----- //
+
+use std::collections::BTreeSet;
+use std::fs;
+
+use crate::config::Config;
+use crate::cpu::instructions::Instruction;
+use crate::platform::Quirks;
+use crate::rom::RomImage;
+
+// ----- Consts ----- //
+
+/// Programs are loaded at this address.
+const ORIGIN: usize = 0x200;
+
+// ----- Functions ----- //
+
+/// Find every instruction that can be reached from 0x200, following jumps,
+/// calls and both ways out of skips. Computed jumps (`JMPO`) can't be
+/// followed, and neither can code outside of the `rom`.
+///
+/// returns The addresses of the instructions.
+pub fn reachable(rom: &[u8]) -> BTreeSet<usize> {
+    let mut result = BTreeSet::new();
+    let mut pending = vec![ORIGIN];
+
+    while let Some(address) = pending.pop() {
+        if result.contains(&address) {
+            continue;
+        }
+        let Some(instruction) = decode(rom, address) else {
+            continue;
+        };
+
+        result.insert(address);
+        match instruction {
+            Instruction::JUMP { address } => { pending.push(address.get()); }
+            Instruction::CALL { address: target } => {
+                pending.push(target.get());
+                pending.push(address + 2);
+            }
+            Instruction::SEQ { .. } | Instruction::SNE { .. } | Instruction::SRE { .. } | Instruction::SRNE { .. }
+            | Instruction::SKE { .. } | Instruction::SKN { .. } => {
+                pending.push(address + 2);
+                pending.push(address + 4);
+            }
+            Instruction::RET {} | Instruction::JMPO { .. } | Instruction::INVALID { .. } => {}
+            _ => { pending.push(address + 2); }
+        }
+    }
+
+    return result;
+}
+
+/// Generate a Rust module running the `rom` without decoding it, for a
+/// machine with the given `quirks`. Each reachable instruction becomes a
+/// function, and each run of consecutive ones a block: a table of them that
+/// can be entered at any instruction.
+///
+/// Blocks aren't generated as straight-line bodies, because the machine has to
+/// be able to stop between any two instructions: once the frame's cycle budget
+/// is spent, and before any instruction the program wrote over since, which
+/// the table lets it check against the opcode each function came from.
+pub fn generate(rom: &RomImage, quirks: &Quirks) -> String {
+    let addresses = reachable(rom.data());
+
+    // Split the instructions into runs of consecutive ones.
+    let mut blocks: Vec<Vec<usize>> = Vec::new();
+    for &address in addresses.iter() {
+        match blocks.last_mut() {
+            Some(block) if block.last() == Some(&(address - 2)) => { block.push(address); }
+            _ => { blocks.push(vec![address]); }
+        }
+    }
+
+    let mut result = format!("\
+// Generated by `chip-8 recompile` from the ROM with SHA-1 {}. Install it on a
+// machine running that ROM, with the same quirks, with
+// `machine.set_static_code(<module>::block)`.
+
+#![allow(clippy::needless_return, unused_imports)]
+
+use chip_8::cpu::CPU;
+use chip_8::cpu::instructions::Instruction;
+use chip_8::cpu::recompiler::StaticOp;
+use chip_8::error::ErrorKind;
+use chip_8::memory::{{FONT_ADDR, FONT_HEIGHT}};
+use chip_8::memory::address::Address;
+
+/// Get the recompiled code starting at `address`, if there is some.
+pub fn block(address: usize) -> Option<&'static [StaticOp]> {{
+    match address {{
+", rom.sha1());
+
+    for block in blocks.iter() {
+        for (i, address) in block.iter().enumerate() {
+            result += &format!("        0x{:03x} => {{ Some(&BLOCK_{:03x}[{}..]) }}\n", address, block[0], i);
+        }
+    }
+    result += "        _ => { None }\n    }\n}\n";
+
+    for block in blocks.iter() {
+        result += &format!("\nstatic BLOCK_{:03x}: [StaticOp; {}] = [\n", block[0], block.len());
+        for &address in block.iter() {
+            let opcode = u16::from(decode(rom.data(), address).expect("Reachable instructions decode"));
+            result += &format!("    StaticOp {{ address: 0x{:03x}, opcode: 0x{:04x}, run: op_{:03x} }},\n",
+                               address, opcode, address);
+        }
+        result += "];\n";
+    }
+
+    for &address in addresses.iter() {
+        let instruction = decode(rom.data(), address).expect("Reachable instructions decode");
+        result += &format!("\n// {:03x}: {}\nfn op_{:03x}(cpu: &mut CPU) -> Result<(), ErrorKind> {{\n",
+                           address, instruction, address);
+        for line in translate(&instruction, quirks) {
+            result += &format!("    {}\n", line);
+        }
+        result += "}\n";
+    }
+
+    return result;
+}
+
+/// Decode the instruction at `address`, if the `rom` holds all of it.
+fn decode(rom: &[u8], address: usize) -> Option<Instruction> {
+    let index = address.checked_sub(ORIGIN)?;
+    let bytes = rom.get(index..index + 2)?;
+    return Some(Instruction::from(((bytes[0] as u16) << 8) | bytes[1] as u16));
+}
+
+/// Translate the `instruction` to lines of Rust, with the given `quirks`.
+/// Anything with more going on than registers and the program counter is
+/// left to the interpreter's implementation.
+fn translate(instruction: &Instruction, quirks: &Quirks) -> Vec<String> {
+    let registers = "let registers = cpu.registers_mut();".to_string();
+    let mut lines = match *instruction {
+        Instruction::SETI { reg, imm8 } => {
+            vec![format!("cpu.registers_mut().set_variable(0x{:x}, 0x{:02x});", reg, imm8)]
+        }
+        Instruction::ADDI { reg, imm8 } => {
+            vec![
+                registers,
+                format!("let value = registers.get_variable(0x{:x}).wrapping_add(0x{:02x});", reg, imm8),
+                format!("registers.set_variable(0x{:x}, value);", reg),
+            ]
+        }
+        Instruction::SET { reg_x, reg_y } => {
+            vec![
+                registers,
+                format!("let value = registers.get_variable(0x{:x});", reg_y),
+                format!("registers.set_variable(0x{:x}, value);", reg_x),
+            ]
+        }
+        Instruction::OR { reg_x, reg_y } => { logic(reg_x, reg_y, "|", quirks.logic) }
+        Instruction::AND { reg_x, reg_y } => { logic(reg_x, reg_y, "&", quirks.logic) }
+        Instruction::XOR { reg_x, reg_y } => { logic(reg_x, reg_y, "^", quirks.logic) }
+        Instruction::ADD { reg_x, reg_y } => {
+            vec![
+                registers,
+                format!("let (value, carry) = registers.get_variable(0x{:x}).overflowing_add(registers.get_variable(0x{:x}));",
+                        reg_x, reg_y),
+                "registers.set_flag(carry);".to_string(),
+                format!("registers.set_variable(0x{:x}, value);", reg_x),
+            ]
+        }
+        Instruction::SUB { reg_x, reg_y } => { subtract(reg_x, reg_x, reg_y) }
+        Instruction::NSUB { reg_x, reg_y } => { subtract(reg_x, reg_y, reg_x) }
+        Instruction::SHR { reg_x, reg_y } => {
+            let source = if quirks.shift { reg_x } else { reg_y };
+            vec![
+                registers,
+                format!("let value = registers.get_variable(0x{:x});", source),
+                "registers.set_flag(value & 0x01 > 0);".to_string(),
+                format!("registers.set_variable(0x{:x}, value >> 1);", reg_x),
+            ]
+        }
+        Instruction::SHL { reg_x, reg_y } => {
+            let source = if quirks.shift { reg_x } else { reg_y };
+            vec![
+                registers,
+                format!("let value = registers.get_variable(0x{:x});", source),
+                "registers.set_flag(value & 0x80 > 0);".to_string(),
+                format!("registers.set_variable(0x{:x}, value << 1);", reg_x),
+            ]
+        }
+        Instruction::SETN { address } => {
+            vec![format!("cpu.registers_mut().set_index(Address::from(0x{:03x}));", address.get())]
+        }
+        Instruction::ADDN { reg } => {
+            vec![
+                registers,
+                format!("let index = registers.get_index().get() + registers.get_variable(0x{:x}) as usize;", reg),
+                "registers.set_index(Address::from(index));".to_string(),
+            ]
+        }
+        Instruction::FONT { reg } => {
+            vec![
+                registers,
+                format!("let digit = (registers.get_variable(0x{:x}) & 0x0F) as usize;", reg),
+                "registers.set_index(Address::from(FONT_ADDR + FONT_HEIGHT * digit));".to_string(),
+            ]
+        }
+        Instruction::SEQ { reg, imm8 } => {
+            skip(format!("cpu.registers_mut().get_variable(0x{:x}) == 0x{:02x}", reg, imm8))
+        }
+        Instruction::SNE { reg, imm8 } => {
+            skip(format!("cpu.registers_mut().get_variable(0x{:x}) != 0x{:02x}", reg, imm8))
+        }
+        Instruction::SRE { reg_x, reg_y } => {
+            skip(format!("cpu.registers_mut().get_variable(0x{:x}) == cpu.registers_mut().get_variable(0x{:x})",
+                         reg_x, reg_y))
+        }
+        Instruction::SRNE { reg_x, reg_y } => {
+            skip(format!("cpu.registers_mut().get_variable(0x{:x}) != cpu.registers_mut().get_variable(0x{:x})",
+                         reg_x, reg_y))
+        }
+        Instruction::JMPO { address } => {
+            let target = address.get();
+            let reg = if quirks.jump { (target >> 8) & 0x0F } else { 0 };
+            vec![
+                format!("let offset = cpu.registers_mut().get_variable(0x{:x}) as usize;", reg),
+                format!("cpu.pc_mut().set(Address::from(0x{:03x} + offset));", target),
+            ]
+        }
+        _ => {
+            return vec![format!("return cpu.execute(Instruction::from(0x{:04x}));", u16::from(instruction))];
+        }
+    };
+
+    lines.push("return Ok(());".to_string());
+    return lines;
+}
+
+fn logic(reg_x: usize, reg_y: usize, operator: &str, reset_flag: bool) -> Vec<String> {
+    let mut lines = vec![
+        "let registers = cpu.registers_mut();".to_string(),
+        format!("let value = registers.get_variable(0x{:x}) {} registers.get_variable(0x{:x});", reg_x, operator, reg_y),
+        format!("registers.set_variable(0x{:x}, value);", reg_x),
+    ];
+    if reset_flag {
+        lines.push("registers.set_flag(false);".to_string());
+    }
+    return lines;
+}
+
+/// Store `minuend - subtrahend` in `reg`, with the flag set when there is no
+/// borrow.
+fn subtract(reg: usize, minuend: usize, subtrahend: usize) -> Vec<String> {
+    return vec![
+        "let registers = cpu.registers_mut();".to_string(),
+        format!("let (a, b) = (registers.get_variable(0x{:x}), registers.get_variable(0x{:x}));", minuend, subtrahend),
+        "registers.set_flag(a >= b);".to_string(),
+        format!("registers.set_variable(0x{:x}, a.wrapping_sub(b));", reg),
+    ];
+}
+
+fn skip(condition: String) -> Vec<String> {
+    return vec![
+        format!("if {} {{", condition),
+        "    cpu.pc_mut().increment()?;".to_string(),
+        "}".to_string(),
+    ];
+}
+
+/// Entry point of the `recompile` tool, given the arguments after its name:
+/// the file to write the module to, followed by the usual options and ROM.
+///
+/// returns The exit code.
+pub fn command(args: &[String]) -> i32 {
+    let Some(output_path) = args.first() else {
+        eprintln!("Usage: recompile OUTPUT [OPTIONS] ROM");
+        return 1;
+    };
+    let config = match Config::from_args(&args[1..]) {
+        Ok(config) => { config }
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    };
+    let rom = match RomImage::load(&config.rom_path) {
+        Ok(rom) => { rom }
+        Err(error) => {
+            eprintln!("Failed to read {}: {}", config.rom_path, error);
+            return 1;
+        }
+    };
+
+    let source = generate(&rom, &config.settings(&rom).quirks);
+    if let Err(error) = fs::write(output_path, source) {
+        eprintln!("Failed to write {}: {}", output_path, error);
+        return 1;
+    }
+    return 0;
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler;
+
+    #[test]
+    fn reachable_code_skips_data() {
+        let source = "
+                   SEQ(v0, 00)
+                   CALL(sub)
+                   JUMP(end)
+            sub:   RET()
+                   BYTE(ff, ff)
+            end:   JUMP(end)
+        ";
+        let rom = assembler::assemble(source, None).unwrap();
+        let expected: BTreeSet<usize> = [0x200, 0x202, 0x204, 0x206, 0x20a].into_iter().collect();
+        assert_eq!(reachable(&rom), expected);
+    }
+}
@@ -0,0 +1,224 @@
+// ----- Imports ----- //
+
+use std::io::{stdout, Write};
+use std::time::{Duration, Instant};
+use crossterm::{cursor, queue, style, terminal};
+use device_query::{DeviceQuery, DeviceState, Keycode};
+use spin_sleep::sleep;
+
+use crate::cpu::Status;
+use crate::display::{Display, DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use crate::error::Error;
+use crate::keyboard::{Keymap, Keypad};
+use crate::machine::Machine;
+use crate::speed::{Mode, Speed};
+
+// ----- Consts ----- //
+
+const RAISE_KEY: Keycode = Keycode::Equal;
+const LOWER_KEY: Keycode = Keycode::Minus;
+const FAST_FORWARD_KEY: Keycode = Keycode::Tab;
+const SLOW_MOTION_KEY: Keycode = Keycode::Grave;
+const CONTINUE_KEY: Keycode = Keycode::F5;
+
+/// How often the keyboard is read while paused.
+const PAUSE_POLL: Duration = Duration::from_millis(10);
+
+/// Columns taken by the display and the border of the console pane.
+const PANE_COLUMN: usize = DISPLAY_WIDTH + 2;
+
+// ----- Structs ----- //
+
+/// Frontend drawing the display in the terminal, and reading the keypad from
+/// the host keyboard.
+pub struct Terminal {
+    shown: [[bool; DISPLAY_WIDTH]; DISPLAY_HEIGHT],  // Pixels currently on screen
+    keymap: Keymap,
+    held: Vec<Keycode>,  // Hotkeys held during the last update
+    console: Vec<String>,  // Last lines of debug console output, the last one unfinished
+}
+
+impl Terminal {
+    pub fn new(title: &str, keymap: Keymap) -> Self {
+        let mut out = stdout();
+        let _ = queue!(out, terminal::SetTitle(title), terminal::Clear(terminal::ClearType::All));
+        let _ = out.flush();
+
+        return Terminal {
+            shown: [[false; DISPLAY_WIDTH]; DISPLAY_HEIGHT],
+            keymap,
+            held: Vec::new(),
+            console: vec![String::new()],
+        };
+    }
+
+    /// Set the state of the `keypad` from the host `keys` currently held.
+    pub fn update_keypad(&self, keypad: &mut Keypad, keys: &[Keycode]) {
+        let typed: Vec<char> = keys.iter().filter_map(typed_char).collect();
+        keypad.update(&self.keymap, &typed);
+    }
+
+    /// Handle the speed hotkeys, given the host `keys` currently held: `=` and
+    /// `-` raise and lower the speed, and while `Tab` (fast-forward) or `` ` ``
+    /// (slow-motion) are held the emulation runs faster or slower.
+    pub fn update_speed(&mut self, speed: &mut Speed, keys: &[Keycode]) {
+        let pressed = |key: Keycode| keys.contains(&key) && !self.held.contains(&key);
+
+        if pressed(RAISE_KEY) {
+            speed.raise();
+        }
+        if pressed(LOWER_KEY) {
+            speed.lower();
+        }
+
+        speed.set_mode(if keys.contains(&FAST_FORWARD_KEY) {
+            Mode::FastForward
+        } else if keys.contains(&SLOW_MOTION_KEY) {
+            Mode::SlowMotion
+        } else {
+            Mode::Normal
+        });
+
+        self.held = keys.to_vec();
+    }
+
+    /// Draw the pixels of `display` that changed since the last frame.
+    pub fn present(&mut self, display: &Display) {
+        let mut out = stdout();
+
+        for row in 0..DISPLAY_HEIGHT {
+            for column in 0..DISPLAY_WIDTH {
+                let pixel = display.get_pixel(column, row);
+                if pixel == self.shown[row][column] {
+                    continue;
+                }
+
+                self.shown[row][column] = pixel;
+                let s = if pixel { "█" } else { " " };
+                let _ = queue!(out, cursor::MoveTo(column as u16, row as u16), style::Print(s));
+            }
+        }
+        let _ = out.flush();
+    }
+
+    /// Add the `output` of the debug console to the pane next to the display,
+    /// which shows its last lines.
+    pub fn log(&mut self, output: &str) {
+        if output.is_empty() {
+            return;
+        }
+
+        let mut pieces = output.split('\n');
+        if let (Some(last), Some(piece)) = (self.console.last_mut(), pieces.next()) {
+            last.push_str(piece);
+        }
+        self.console.extend(pieces.map(String::from));
+        let excess = self.console.len().saturating_sub(DISPLAY_HEIGHT);
+        self.console.drain(..excess);
+
+        let columns = terminal::size().map_or(0, |(columns, _)| columns as usize);
+        let width = columns.saturating_sub(PANE_COLUMN);
+        let mut out = stdout();
+        for row in 0..DISPLAY_HEIGHT {
+            let line = self.console.get(row).map_or("", String::as_str);
+            let text: String = line.chars().take(width).collect();
+            let _ = queue!(out,
+                           cursor::MoveTo(DISPLAY_WIDTH as u16, row as u16),
+                           style::Print("│ "),
+                           style::Print(text),
+                           terminal::Clear(terminal::ClearType::UntilNewLine));
+        }
+        let _ = out.flush();
+    }
+
+    /// Wait until `F5` is pressed, to resume after a breakpoint.
+    pub fn pause(&mut self) {
+        loop {
+            let keys = host_keys();
+            let pressed = keys.contains(&CONTINUE_KEY) && !self.held.contains(&CONTINUE_KEY);
+            self.held = keys;
+            if pressed {
+                return;
+            }
+            sleep(PAUSE_POLL);
+        }
+    }
+
+    /// Run the `machine` one frame at a time: read the keyboard, run the
+    /// frame, show it and wait for the next one. Stop after `frames` frames, or
+    /// once the program halts if `stop_on_halt` is set. Breakpoints pause the
+    /// run, showing the registers next to the display.
+    ///
+    /// returns The status of the last instruction to run.
+    pub fn run(&mut self, machine: &mut Machine, frames: Option<u64>, stop_on_halt: bool) -> Result<Status, Error> {
+        let mut deadline = Instant::now();
+        let mut count = 0;
+
+        loop {
+            if frames.is_some_and(|frames| count >= frames) {
+                return Ok(Status::Running);
+            }
+
+            let keys = host_keys();
+            self.update_keypad(machine.keypad_mut(), &keys);
+            self.update_speed(machine.speed_mut(), &keys);
+
+            let status = machine.run_frame();
+            self.log(&machine.take_console());
+            let status = status?;
+            self.present(&machine.display());
+            if status == Status::Breakpoint {
+                self.log(&format!("breakpoint at {}, F5 to continue:\n{}", machine.location(), machine.dump_registers()));
+                self.pause();
+                deadline = Instant::now();
+                continue;
+            }
+            if stop_on_halt && status == Status::Halted {
+                return Ok(status);
+            }
+            count += 1;
+
+            deadline += machine.speed().frame_duration();
+            wait_until(&mut deadline);
+        }
+    }
+
+    /// Move the cursor below the display, so that anything printed after the
+    /// emulation stops doesn't overwrite it.
+    pub fn finish(&self) {
+        let mut out = stdout();
+        let _ = queue!(out, cursor::MoveTo(0, DISPLAY_HEIGHT as u16), style::Print("\n"));
+        let _ = out.flush();
+    }
+}
+
+// ----- Functions ----- //
+
+/// Get the host keys currently held.
+pub fn host_keys() -> Vec<Keycode> {
+    let ds = DeviceState::new();
+    return ds.get_keys();
+}
+
+/// Get the lowercase character a host key types, for looking it up in a
+/// keymap. Only letters and digits have one.
+fn typed_char(code: &Keycode) -> Option<char> {
+    let name = code.to_string();
+    let name = name.strip_prefix("Key").unwrap_or(&name);
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => { Some(c.to_ascii_lowercase()) }
+        _ => { None }
+    }
+}
+
+/// Sleep until `deadline`. If it already passed, move it to now, so that a
+/// slow frame doesn't make the following ones rush to catch up.
+pub fn wait_until(deadline: &mut Instant) {
+    let now = Instant::now();
+    if *deadline > now {
+        sleep(*deadline - now);
+    } else {
+        *deadline = now;
+    }
+}
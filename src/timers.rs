@@ -1,47 +1,29 @@
-// ----- Imports ----- //
-
-use std::{thread, time};
-use std::sync::{Arc, Mutex};
-use spin_sleep::sleep;
-
-// ----- Consts ----- //
-
-const DURATION: time::Duration = time::Duration::from_millis(16);
-
 // ----- Structs ----- //
 
+/// A CHIP-8 timer, counting down to 0 at 60Hz. The timer is ticked by the
+/// emulation loop once per emulated frame, so it keeps pace with the program
+/// whatever the emulation speed.
 pub struct Timer {
-    value: Arc<Mutex<u8>>,
+    value: u8,
 }
 
 impl Timer {
     pub fn new() -> Self {
-        let value = Arc::new(Mutex::new(0));
-        let other = value.clone();
-        thread::spawn(|| count(other));
-        return Timer { value };
+        return Timer { value: 0 };
     }
 
     pub fn get(&self) -> u8 {
-        return *self.value.lock().unwrap();
+        return self.value;
     }
 
     pub fn set(&mut self, value: u8) {
-        let _ = value;
-        let mut current = self.value.lock().unwrap();
-        *current = value;
+        self.value = value;
     }
-}
-
-fn count(m: Arc<Mutex<u8>>) {
-    loop {
-        sleep(DURATION);
 
-        {
-            let mut value = m.lock().unwrap();
-            if *value > 0 {
-                *value -= 1;
-            }
+    /// Count down by one, stopping at 0.
+    pub fn tick(&mut self) {
+        if self.value > 0 {
+            self.value -= 1;
         }
     }
 }
@@ -0,0 +1,148 @@
+// ----- Imports ----- //
+
+use crate::cpu::instructions::Instruction;
+use crate::registers::Registers;
+
+// ----- Consts ----- //
+
+/// Machine cycles in a 60Hz frame, with 8 clock cycles per machine cycle at
+/// 1.7609 MHz.
+pub const CYCLES_PER_FRAME: u32 = 3668;
+
+/// Machine cycles of each frame lost to the vblank interrupt: the display DMA
+/// (128 lines of 8 bytes) and the interrupt routine, which updates the timers.
+pub const VBLANK_CYCLES: u32 = 1024 + 56;
+
+/// Extra machine cycles taken by a skip instruction when it skips.
+pub const SKIP_CYCLES: u32 = 4;
+
+/// Machine cycles the VIP interpreter spends fetching and dispatching every
+/// instruction, on top of the instruction's own routine.
+const DISPATCH_CYCLES: u32 = 40;
+
+// ----- Structs ----- //
+
+/// How long each instruction takes to run.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Timing {
+    /// Every instruction takes the same time, set by the speed.
+    Fixed,
+    /// Every instruction takes as long as it did on the COSMAC VIP.
+    Vip,
+}
+
+impl Timing {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "fixed" => { Some(Timing::Fixed) }
+            "vip" => { Some(Timing::Vip) }
+            _ => { None }
+        }
+    }
+}
+
+// ----- Functions ----- //
+
+/// Get the cost of `instruction` on the VIP, in machine cycles, given the
+/// `registers` right before it runs. The costs are approximations, counted
+/// from the routines of the original interpreter. Skips that are taken cost
+/// `SKIP_CYCLES` more, and machine code routines cost whatever they ran for.
+pub fn vip_cycles(instruction: &Instruction, registers: &Registers) -> u32 {
+    let cost = match instruction {
+        Instruction::CLS {} => { 24 + 1536 }
+        Instruction::SYS { .. } => { 0 }
+        Instruction::RET {} => { 10 }
+        Instruction::JUMP { .. } => { 12 }
+        Instruction::CALL { .. } => { 26 }
+        Instruction::JMPO { .. } => { 22 }
+        Instruction::SETN { .. } => { 12 }
+        Instruction::ADDN { .. } => { 16 }
+        Instruction::SEQ { .. } | Instruction::SNE { .. } => { 10 }
+        Instruction::SRE { .. } | Instruction::SRNE { .. } => { 14 }
+        Instruction::SETI { .. } => { 6 }
+        Instruction::ADDI { .. } => { 10 }
+        Instruction::SET { .. } => { 12 }
+        Instruction::OR { .. } | Instruction::AND { .. } | Instruction::XOR { .. } => { 44 }
+        Instruction::ADD { .. } | Instruction::SUB { .. } | Instruction::NSUB { .. } => { 44 }
+        Instruction::SHL { .. } | Instruction::SHR { .. } => { 44 }
+        Instruction::STD { .. } | Instruction::RDD { .. } | Instruction::STS { .. } => { 10 }
+        Instruction::RAND { .. } => { 36 }
+        Instruction::DRAW { reg_x, imm4, .. } => {
+            // Sprites that aren't byte-aligned are shifted into place one bit
+            // at a time, and spill into a second byte.
+            let shift = registers.get_variable(*reg_x) as u32 % 8;
+            let row = if shift == 0 { 22 } else { 34 + 2 * shift };
+            26 + row * (*imm4 as u32)
+        }
+        Instruction::SKE { .. } | Instruction::SKN { .. } => { 14 }
+        Instruction::GTK { .. } => { 8 }
+        Instruction::FONT { .. } => { 16 }
+        Instruction::BCD { reg } => {
+            // Each digit is found by repeated subtraction.
+            let value = registers.get_variable(*reg) as u32;
+            let digits = value / 100 + (value / 10) % 10 + value % 10;
+            84 + 16 * digits
+        }
+        Instruction::STM { reg } | Instruction::LDM { reg } => { 14 + 14 * (*reg as u32 + 1) }
+        Instruction::INVALID { .. } => { 0 }
+    };
+
+    return DISPATCH_CYCLES + cost;
+}
+
+/// Get the extra cost of `instruction` on the VIP, given whether it `skipped`
+/// the next instruction. Only skip instructions cost more, when they skip;
+/// jumps that happen to land past the next instruction don't.
+pub fn skip_cycles(instruction: &Instruction, skipped: bool) -> u32 {
+    let skip = matches!(instruction, Instruction::SEQ { .. } | Instruction::SNE { .. }
+                        | Instruction::SRE { .. } | Instruction::SRNE { .. }
+                        | Instruction::SKE { .. } | Instruction::SKN { .. });
+    return if skip && skipped { SKIP_CYCLES } else { 0 };
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cycles(opcode: u16, registers: &Registers) -> u32 {
+        return vip_cycles(&Instruction::from(opcode), registers);
+    }
+
+    #[test]
+    fn instructions_cost_their_routine_and_dispatch() {
+        let registers = Registers::new();
+        assert_eq!(cycles(0x6012, &registers), DISPATCH_CYCLES + 6);
+        assert_eq!(cycles(0x00E0, &registers), DISPATCH_CYCLES + 24 + 1536);
+        assert_eq!(cycles(0x8124, &registers), DISPATCH_CYCLES + 44);
+        assert_eq!(cycles(0xF355, &registers), DISPATCH_CYCLES + 14 + 14 * 4);
+        // Routines are counted as they run.
+        assert_eq!(cycles(0x0234, &registers), DISPATCH_CYCLES);
+    }
+
+    #[test]
+    fn costs_depend_on_the_registers() {
+        let mut registers = Registers::new();
+
+        // Byte-aligned sprites are cheaper than those that need shifting.
+        registers.set_variable(1, 8);
+        assert_eq!(cycles(0xD125, &registers), DISPATCH_CYCLES + 26 + 22 * 5);
+        registers.set_variable(1, 11);
+        assert_eq!(cycles(0xD125, &registers), DISPATCH_CYCLES + 26 + 40 * 5);
+
+        // BCD subtracts once per unit of each digit.
+        registers.set_variable(2, 0);
+        assert_eq!(cycles(0xF233, &registers), DISPATCH_CYCLES + 84);
+        registers.set_variable(2, 199);
+        assert_eq!(cycles(0xF233, &registers), DISPATCH_CYCLES + 84 + 16 * 19);
+    }
+
+    #[test]
+    fn only_taken_skips_cost_more() {
+        assert_eq!(skip_cycles(&Instruction::from(0x3000), true), SKIP_CYCLES);
+        assert_eq!(skip_cycles(&Instruction::from(0xE09E), true), SKIP_CYCLES);
+        assert_eq!(skip_cycles(&Instruction::from(0x3000), false), 0);
+        assert_eq!(skip_cycles(&Instruction::from(0x1204), true), 0);
+    }
+}
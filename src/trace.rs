@@ -0,0 +1,269 @@
+// ----- Imports ----- //
+
+#[cfg(feature = "std")]
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+#[cfg(feature = "std")]
+use std::io::Write;
+
+use crate::cpu::instructions::Instruction;
+use crate::registers::VARIABLE_COUNT;
+
+// ----- Structs ----- //
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum TraceFormat {
+    /// One aligned line of text per instruction.
+    Human,
+    /// One JSON object per line.
+    Json,
+}
+
+/// Which instructions make it into the trace.
+#[derive(Default)]
+pub struct TraceFilter {
+    /// Only instructions at these addresses, if set.
+    pub range: Option<RangeInclusive<usize>>,
+    /// Only instructions with these names (like `DRAW`), if not empty.
+    pub kinds: Vec<String>,
+}
+
+/// A single executed instruction, along with the state it left behind.
+pub struct TraceEntry {
+    /// Operation cycles run before this one.
+    pub cycle: u64,
+    pub pc: usize,
+    pub opcode: u16,
+    pub instruction: String,
+    /// Where the instruction is in the source, as `file:line`, when the debug
+    /// info knows.
+    pub source: Option<String>,
+    pub variables: [u8; VARIABLE_COUNT],
+    pub index: usize,
+    pub delay: u8,
+    pub sound: u8,
+    /// The whole memory, when the tracer asks for it.
+    pub memory: Option<Vec<u8>>,
+}
+
+/// Records the instructions that pass the filter, as they run.
+pub struct Tracer {
+    filter: TraceFilter,
+    memory: bool,
+    output: Output,
+}
+
+enum Output {
+    #[cfg(feature = "std")]
+    Writer { format: TraceFormat, out: Box<dyn Write + Send> },
+    /// Kept until taken, for tools that look at the trace while it runs.
+    Buffer(Vec<TraceEntry>),
+}
+
+impl TraceFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "human" | "text" => { Some(TraceFormat::Human) }
+            "json" | "jsonl" => { Some(TraceFormat::Json) }
+            _ => { None }
+        }
+    }
+}
+
+impl TraceFilter {
+    /// Check whether the `instruction` at `pc` should be traced.
+    pub fn accepts(&self, pc: usize, instruction: &Instruction) -> bool {
+        if let Some(range) = &self.range {
+            if !range.contains(&pc) {
+                return false;
+            }
+        }
+        return self.kinds.is_empty() || self.kinds.iter().any(|kind| kind == instruction.name());
+    }
+}
+
+impl Tracer {
+    /// Create a tracer writing every entry to `out`, in the given `format`.
+    #[cfg(feature = "std")]
+    pub fn new(format: TraceFormat, filter: TraceFilter, out: Box<dyn Write + Send>) -> Self {
+        return Tracer {
+            filter,
+            memory: false,
+            output: Output::Writer { format, out },
+        };
+    }
+
+    /// Create a tracer keeping the entries until they are taken.
+    pub fn buffered(filter: TraceFilter) -> Self {
+        return Tracer {
+            filter,
+            memory: false,
+            output: Output::Buffer(Vec::new()),
+        };
+    }
+
+    /// Include the whole memory in each entry.
+    pub fn with_memory(mut self) -> Self {
+        self.memory = true;
+        return self;
+    }
+
+    pub fn filter(&self) -> &TraceFilter {
+        return &self.filter;
+    }
+
+    pub fn wants_memory(&self) -> bool {
+        return self.memory;
+    }
+
+    /// Start the trace with the `seed` of the run, so that it can be replayed:
+    /// a `#` comment for people, or an object without a `pc` for programs.
+    #[cfg(feature = "std")]
+    pub fn header(&mut self, seed: u64) {
+        if let Output::Writer { format, out } = &mut self.output {
+            let _ = match format {
+                TraceFormat::Human => { writeln!(out, "# seed {:#x}", seed) }
+                TraceFormat::Json => { writeln!(out, "{}", serde_json::json!({ "seed": seed })) }
+            };
+        }
+    }
+
+    /// Record the `entry`. Tracing is best effort, so a failed write doesn't
+    /// stop the program.
+    pub fn record(&mut self, entry: TraceEntry) {
+        match &mut self.output {
+            #[cfg(feature = "std")]
+            Output::Writer { format, out } => {
+                let line = match format {
+                    TraceFormat::Human => { format_human(&entry) }
+                    TraceFormat::Json => { format_json(&entry) }
+                };
+                let _ = writeln!(out, "{}", line);
+            }
+            Output::Buffer(entries) => { entries.push(entry); }
+        }
+    }
+
+    /// Take the entries recorded since the last call, for buffered tracers.
+    pub fn take(&mut self) -> Vec<TraceEntry> {
+        match &mut self.output {
+            #[cfg(feature = "std")]
+            Output::Writer { .. } => { Vec::new() }
+            Output::Buffer(entries) => { core::mem::take(entries) }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for Tracer {
+    fn drop(&mut self) {
+        if let Output::Writer { out, .. } = &mut self.output {
+            let _ = out.flush();
+        }
+    }
+}
+
+// ----- Functions ----- //
+
+/// Parse an address range given as `START-END`, both inclusive and in
+/// hexadecimal.
+pub fn parse_range(range: &str) -> Option<RangeInclusive<usize>> {
+    let (start, end) = range.split_once('-')?;
+    let start = usize::from_str_radix(start.trim_start_matches("0x"), 16).ok()?;
+    let end = usize::from_str_radix(end.trim_start_matches("0x"), 16).ok()?;
+    if start > end {
+        return None;
+    }
+    return Some(start..=end);
+}
+
+/// Describe the `entry` as a single line of text.
+pub fn format_human(entry: &TraceEntry) -> String {
+    let variables: Vec<String> = entry.variables.iter().map(|value| format!("{:02x}", value)).collect();
+    let mut result = format!("{:>10} {:03x}: {:04x} {:<18} v={} I={:03x} DT={:02x} ST={:02x}",
+                             entry.cycle, entry.pc, entry.opcode, entry.instruction,
+                             variables.join(" "), entry.index, entry.delay, entry.sound);
+    if let Some(source) = &entry.source {
+        result += &format!("  {}", source);
+    }
+    return result;
+}
+
+/// Describe the `entry` as a JSON object, in the format `trace-diff` reads
+/// reference traces in.
+#[cfg(feature = "std")]
+pub fn format_json(entry: &TraceEntry) -> String {
+    let mut object = serde_json::json!({
+        "cycle": entry.cycle,
+        "pc": entry.pc,
+        "opcode": entry.opcode,
+        "instruction": entry.instruction,
+        "v": entry.variables,
+        "i": entry.index,
+        "dt": entry.delay,
+        "st": entry.sound,
+    });
+    if let Some(source) = &entry.source {
+        object["source"] = source.as_str().into();
+    }
+    if let Some(memory) = &entry.memory {
+        object["memory"] = to_hex(memory).into();
+    }
+    return object.to_string();
+}
+
+#[cfg(feature = "std")]
+fn to_hex(data: &[u8]) -> String {
+    return data.iter().map(|byte| format!("{:02x}", byte)).collect();
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn filters_check_addresses_and_names() {
+        let draw = Instruction::from(0xD125);
+        let filter = TraceFilter { range: parse_range("200-2ff"), kinds: Vec::new() };
+        assert!(filter.accepts(0x200, &draw));
+        assert!(filter.accepts(0x2FF, &draw));
+        assert!(!filter.accepts(0x300, &draw));
+
+        let filter = TraceFilter { range: parse_range("0x300-0x310"), kinds: vec![String::from("DRAW")] };
+        assert!(filter.accepts(0x304, &draw));
+        assert!(!filter.accepts(0x304, &Instruction::from(0x00E0)));
+        assert!(!filter.accepts(0x200, &draw));
+
+        assert!(TraceFilter::default().accepts(0x000, &Instruction::from(0x0000)));
+        assert!(parse_range("300-200").is_none());
+        assert!(parse_range("300").is_none());
+    }
+
+    #[test]
+    fn buffered_tracers_keep_entries_until_taken() {
+        let mut tracer = Tracer::buffered(TraceFilter::default());
+        assert!(tracer.take().is_empty());
+        for cycle in 0..3 {
+            tracer.record(TraceEntry {
+                cycle,
+                pc: 0x200,
+                opcode: 0x00E0,
+                instruction: String::from("CLS()"),
+                source: None,
+                variables: [0; VARIABLE_COUNT],
+                index: 0,
+                delay: 0,
+                sound: 0,
+                memory: None,
+            });
+        }
+        assert_eq!(tracer.take().iter().map(|entry| entry.cycle).collect::<Vec<u64>>(), vec![0, 1, 2]);
+        assert!(tracer.take().is_empty());
+    }
+}
@@ -0,0 +1,436 @@
+// ----- Imports ----- //
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::config::{Config, Settings};
+use crate::cpu::CPU;
+use crate::cpu::instructions::Instruction;
+use crate::debug::DebugInfo;
+use crate::display::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use crate::memory::address::{Address, MAX_ADDRESS};
+use crate::platform::Platform;
+use crate::registers::VARIABLE_COUNT;
+use crate::rom::RomImage;
+
+// ----- Consts ----- //
+
+/// Instructions a subroutine may run before it is considered stuck, when the
+/// test doesn't say.
+const DEFAULT_LIMIT: u64 = 100_000;
+
+// ----- Structs ----- //
+
+/// Tests of the subroutines of a ROM, as read from a spec file.
+///
+/// Specs are text, one directive per line. They start by naming the ROM
+/// (relative to the spec) and optionally its platform, followed by the tests.
+/// Each test calls a subroutine, after setting up registers and memory, and
+/// checks the state it leaves behind once it returns. Numbers, pixel
+/// coordinates included, are in hexadecimal, and pixel rows use `#` for lit
+/// pixels and `.` for dark ones. Empty lines and lines starting with `#` are
+/// ignored.
+///
+/// ```text
+/// rom delay_timer_test.ch8
+/// platform chip8
+///
+/// test prints_42
+/// set v4=00 v6=2a
+/// call _p_print
+/// expect v0=00 v1=04 v2=02
+/// expect memory 23a 00 04 02
+/// expect pixels a,0 #### ...# #### #... ####
+/// ```
+///
+/// Subroutines are called by address, or by label when the ROM has debug
+/// info: the one given with `--debug`, or else the sidecar the assembler
+/// writes next to the ROM. Tests can also `memory ADDRESS BYTES..` to fill
+/// memory before the call, and set the `limit` of instructions the subroutine
+/// may run for.
+pub struct Spec {
+    rom: String,
+    platform: Option<Platform>,
+    tests: Vec<Test>,
+}
+
+struct Test {
+    name: String,
+    /// Label or address of the subroutine to call.
+    call: Option<String>,
+    setup: Vec<Value>,
+    checks: Vec<Check>,
+    limit: u64,
+}
+
+/// A piece of machine state, to set before a test or check after it.
+enum Value {
+    Variable(usize, u8),
+    Index(usize),
+    Memory(usize, Vec<u8>),
+}
+
+enum Check {
+    Value(Value),
+    /// Rows of pixels, from the top left corner at `x`, `y`.
+    Pixels { x: usize, y: usize, rows: Vec<Vec<bool>> },
+}
+
+impl Spec {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("Failed to read {}: {}", path, error))?;
+        return Spec::parse(&text);
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut rom: Option<String> = None;
+        let mut platform: Option<Platform> = None;
+        let mut tests: Vec<Test> = Vec::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = || format!("Invalid spec on line {}: '{}'", number + 1, line);
+            let mut words = line.split_whitespace();
+            let directive = words.next().ok_or_else(error)?;
+            let words: Vec<&str> = words.collect();
+
+            if directive == "test" {
+                let [name] = words.as_slice() else {
+                    return Err(error());
+                };
+                tests.push(Test {
+                    name: name.to_string(),
+                    call: None,
+                    setup: Vec::new(),
+                    checks: Vec::new(),
+                    limit: DEFAULT_LIMIT,
+                });
+                continue;
+            }
+
+            let Some(test) = tests.last_mut() else {
+                match (directive, words.as_slice()) {
+                    ("rom", [path]) => { rom = Some(path.to_string()); }
+                    ("platform", [name]) => {
+                        platform = Some(Platform::from_name(name).ok_or(format!("Unknown platform '{}'", name))?);
+                    }
+                    _ => { return Err(error()); }
+                }
+                continue;
+            };
+            match (directive, words.as_slice()) {
+                ("call", [target]) => { test.call = Some(target.to_string()); }
+                ("limit", [limit]) => { test.limit = limit.parse().map_err(|_| error())?; }
+                ("set", assignments) => {
+                    for assignment in assignments {
+                        test.setup.push(parse_assignment(assignment).ok_or_else(error)?);
+                    }
+                }
+                ("memory", [address, bytes @ ..]) => {
+                    test.setup.push(parse_memory(address, bytes).ok_or_else(error)?);
+                }
+                ("expect", ["memory", address, bytes @ ..]) => {
+                    test.checks.push(Check::Value(parse_memory(address, bytes).ok_or_else(error)?));
+                }
+                ("expect", ["pixels", corner, rows @ ..]) => {
+                    let (x, y) = corner.split_once(',').ok_or_else(error)?;
+                    let x = parse_hex(x).ok_or_else(error)?;
+                    let y = parse_hex(y).ok_or_else(error)?;
+                    let rows = rows.iter()
+                        .map(|row| row.chars().map(|c| c == '#').collect())
+                        .collect();
+                    test.checks.push(Check::Pixels { x, y, rows });
+                }
+                ("expect", assignments) => {
+                    for assignment in assignments {
+                        test.checks.push(Check::Value(parse_assignment(assignment).ok_or_else(error)?));
+                    }
+                }
+                _ => { return Err(error()); }
+            }
+        }
+
+        let rom = rom.ok_or("The spec doesn't name a ROM")?;
+        if let Some(test) = tests.iter().find(|test| test.call.is_none()) {
+            return Err(format!("Test {} doesn't call anything", test.name));
+        }
+        return Ok(Spec { rom, platform, tests });
+    }
+}
+
+impl Value {
+    /// Put the value in place on the `cpu`.
+    fn apply(&self, cpu: &mut CPU) {
+        match self {
+            Value::Variable(index, value) => { cpu.registers_mut().set_variable(*index, *value); }
+            Value::Index(address) => { cpu.registers_mut().set_index(Address::from(*address)); }
+            Value::Memory(address, data) => { cpu.write_memory(Address::from(*address), data); }
+        }
+    }
+
+    /// Compare the value with what the `cpu` holds.
+    ///
+    /// returns What differs, if anything.
+    fn compare(&self, cpu: &CPU) -> Option<String> {
+        match self {
+            Value::Variable(index, expected) => {
+                let actual = cpu.registers().get_variable(*index);
+                if actual == *expected {
+                    return None;
+                }
+                return Some(format!("v{:x} is {:02x}, expected {:02x}", index, actual, expected));
+            }
+            Value::Index(expected) => {
+                let actual = cpu.registers().get_index().get();
+                if actual == *expected {
+                    return None;
+                }
+                return Some(format!("I is {:03x}, expected {:03x}", actual, expected));
+            }
+            Value::Memory(address, expected) => {
+                let actual = cpu.memory().read(Address::from(*address), expected.len());
+                if actual == *expected {
+                    return None;
+                }
+                return Some(format!("memory at {:03x} is {}, expected {}", address, hex_bytes(&actual), hex_bytes(expected)));
+            }
+        }
+    }
+}
+
+impl Check {
+    fn compare(&self, cpu: &CPU) -> Option<String> {
+        match self {
+            Check::Value(value) => { return value.compare(cpu); }
+            Check::Pixels { x, y, rows } => {
+                let display = cpu.display();
+                for (row, pixels) in rows.iter().enumerate() {
+                    for (column, &expected) in pixels.iter().enumerate() {
+                        let (px, py) = (x + column, y + row);
+                        if px >= DISPLAY_WIDTH || py >= DISPLAY_HEIGHT {
+                            return Some(format!("pixel at {},{} is off the display", px, py));
+                        }
+                        let actual = display.get_pixel(px, py);
+                        if actual != expected {
+                            return Some(format!("pixel at {},{} is {}, expected {}",
+                                                px, py, on_off(actual), on_off(expected)));
+                        }
+                    }
+                }
+                return None;
+            }
+        }
+    }
+}
+
+impl Test {
+    /// Call the subroutine on a fresh machine, and run it until it returns,
+    /// checking the assertions in `debug` along the way. Breakpoints are
+    /// ignored. The VIP random number generator reads `rng_code`, if given.
+    ///
+    /// returns What went wrong, if anything.
+    fn run(&self, rom: &RomImage, settings: &Settings, debug: Option<&Arc<DebugInfo>>, rng_code: Option<&[u8]>)
+           -> Vec<String> {
+        let mut cpu = CPU::new(rom, settings);
+        if let Some(code) = rng_code {
+            cpu.load_rng_code(code);
+        }
+        if let Some(debug) = debug {
+            cpu.set_debug(Arc::clone(debug));
+        }
+        for value in self.setup.iter() {
+            value.apply(&mut cpu);
+        }
+
+        // Labels take precedence, as names that are also hexadecimal numbers
+        // (like `add`) are more likely to be meant as names.
+        let call = self.call.as_deref().unwrap_or_default();
+        let label = debug.and_then(|debug| debug.address_of(call));
+        let Some(address) = label.or_else(|| parse_hex(call)).map(Address::from) else {
+            return vec![format!("'{}' is neither a label nor an address", call)];
+        };
+        if let Err(error) = cpu.execute(Instruction::CALL { address }) {
+            return vec![error.to_string()];
+        }
+        let mut count = 0;
+        while cpu.depth() > 0 {
+            if count >= self.limit {
+                return vec![format!("still running after {} instructions, at {}", count, cpu.pc_mut().get())];
+            }
+            if let Err(error) = cpu.cycle() {
+                return vec![error.to_string()];
+            }
+            count += 1;
+        }
+
+        return self.checks.iter().filter_map(|check| check.compare(&cpu)).collect();
+    }
+}
+
+// ----- Functions ----- //
+
+fn parse_hex(text: &str) -> Option<usize> {
+    return usize::from_str_radix(text.trim_start_matches("0x"), 16).ok();
+}
+
+/// Parse `vX=NN` or `i=NNN`.
+fn parse_assignment(text: &str) -> Option<Value> {
+    let (name, value) = text.split_once('=')?;
+    let value = parse_hex(value)?;
+    let name = name.to_lowercase();
+    if name == "i" {
+        return Some(Value::Index(value)).filter(|_| value <= MAX_ADDRESS);
+    }
+
+    let index = parse_hex(name.strip_prefix('v')?).filter(|&index| index < VARIABLE_COUNT)?;
+    return Some(Value::Variable(index, u8::try_from(value).ok()?));
+}
+
+fn parse_memory(address: &str, bytes: &[&str]) -> Option<Value> {
+    let address = parse_hex(address)?;
+    let data = bytes.iter()
+        .map(|byte| u8::from_str_radix(byte, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    return Some(Value::Memory(address, data));
+}
+
+fn hex_bytes(data: &[u8]) -> String {
+    let bytes: Vec<String> = data.iter().map(|byte| format!("{:02x}", byte)).collect();
+    return bytes.join(" ");
+}
+
+fn on_off(pixel: bool) -> &'static str {
+    return if pixel { "on" } else { "off" };
+}
+
+/// Run the tests in `spec`, with the ROM found relative to `directory`,
+/// printing the results the way `cargo test` does.
+///
+/// returns Whether every test passed.
+pub fn run(spec: &Spec, directory: &Path, config: &Config) -> Result<bool, String> {
+    let path = directory.join(&spec.rom);
+    let rom = RomImage::load(&path.to_string_lossy())
+        .map_err(|error| format!("Failed to read {}: {}", path.display(), error))?;
+    let settings = config.settings(&rom);
+    let sidecar = format!("{}.debug", path.display());
+    let debug = match &config.debug {
+        Some(path) => { Some(Arc::new(DebugInfo::load(path)?)) }
+        None if Path::new(&sidecar).exists() => { Some(Arc::new(DebugInfo::load(&sidecar)?)) }
+        None => { None }
+    };
+    let rng_code = config.load_rng_code()?;
+
+    println!("running {} tests", spec.tests.len());
+    let mut failures = Vec::new();
+    for test in spec.tests.iter() {
+        let problems = test.run(&rom, &settings, debug.as_ref(), rng_code.as_deref());
+        println!("test {} ... {}", test.name, if problems.is_empty() { "ok" } else { "FAILED" });
+        if !problems.is_empty() {
+            failures.push((&test.name, problems));
+        }
+    }
+
+    if !failures.is_empty() {
+        println!("\nfailures:");
+        for (name, problems) in failures.iter() {
+            println!("\n---- {} ----", name);
+            for problem in problems {
+                println!("{}", problem);
+            }
+        }
+        println!("\nfailures:");
+        for (name, _) in failures.iter() {
+            println!("    {}", name);
+        }
+    }
+
+    let passed = spec.tests.len() - failures.len();
+    println!("\ntest result: {}. {} passed; {} failed",
+             if failures.is_empty() { "ok" } else { "FAILED" }, passed, failures.len());
+    return Ok(failures.is_empty());
+}
+
+/// Entry point of the `test` tool, given the arguments after its name: the
+/// usual options, and the spec to run.
+///
+/// returns The exit code: 0 when every test passed, 1 otherwise.
+pub fn command(args: &[String]) -> i32 {
+    let mut config = match Config::from_args(args) {
+        Ok(config) => { config }
+        Err(_) if args.is_empty() => {
+            eprintln!("Usage: test [OPTIONS] SPEC");
+            return 1;
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    };
+    let spec = match Spec::load(&config.rom_path) {
+        Ok(spec) => { spec }
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    };
+
+    // Tests should play out the same every time.
+    config.platform = config.platform.or(spec.platform);
+    config.seed = config.seed.or(Some(0));
+
+    let directory = Path::new(&config.rom_path).parent().unwrap_or(Path::new("."));
+    match run(&spec, directory, &config) {
+        Ok(passed) => { return if passed { 0 } else { 1 }; }
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    }
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subroutines_are_checked_once_they_return() {
+        let directory = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/test_roms"));
+        let mut spec = Spec::load(&directory.join("delay_timer_test.spec").to_string_lossy()).unwrap();
+        let rom = RomImage::load(&directory.join(&spec.rom).to_string_lossy()).unwrap();
+        let config = Config::from_args(&[spec.rom.clone(), String::from("--seed"), String::from("0")]).unwrap();
+        let settings = config.settings(&rom);
+        let debug = Arc::new(DebugInfo::load(&directory.join("delay_timer_test.ch8.debug").to_string_lossy()).unwrap());
+        for test in spec.tests.iter() {
+            assert!(test.run(&rom, &settings, Some(&debug), None).is_empty(), "{}", test.name);
+        }
+
+        spec.tests[0].checks.push(Check::Value(Value::Variable(0, 1)));
+        assert_eq!(spec.tests[0].run(&rom, &settings, Some(&debug), None), vec![String::from("v0 is 00, expected 01")]);
+    }
+
+    #[test]
+    fn subroutines_can_be_called_by_label() {
+        let spec = Spec::parse("rom test.ch8\ntest label\ncall add\nexpect v0=01\ntest address\ncall 200\nexpect v0=02\n").unwrap();
+        let rom = RomImage::from(vec![0x60, 0x02, 0x00, 0xEE, 0x60, 0x01, 0x00, 0xEE]);
+        let config = Config::from_args(&[String::from("test.ch8"), String::from("--seed"), String::from("0")]).unwrap();
+        let settings = config.settings(&rom);
+        let mut debug = DebugInfo::new();
+        debug.add_label(0x204, "add");
+        let debug = Arc::new(debug);
+
+        assert!(spec.tests[0].run(&rom, &settings, Some(&debug), None).is_empty());
+        assert!(spec.tests[1].run(&rom, &settings, Some(&debug), None).is_empty());
+
+        // Without the label, `add` is an address, and there is nothing there.
+        let problems = spec.tests[0].run(&rom, &settings, None, None);
+        assert!(problems[0].starts_with("Ran into empty memory"), "{:?}", problems);
+    }
+}
@@ -0,0 +1,214 @@
+// ----- Imports ----- //
+
+use std::time::Instant;
+
+use crate::cdp1802::{Bus, Cdp1802};
+use crate::display::{Display, Framebuffer};
+use crate::keyboard::{Key, Keypad};
+use crate::memory::address::Address;
+use crate::memory::Memory;
+use crate::rom::RomImage;
+use crate::speed::FRAME_DURATION;
+use crate::terminal;
+use crate::terminal::Terminal;
+
+// ----- Consts ----- //
+
+/// The monitor ROM appears at 0x8000, mirrored over the upper half of the
+/// address space.
+const MONITOR_FLAG: u16 = 0x8000;
+const MONITOR_SIZE: usize = 0x200;
+
+/// Page the CHIP-8 interpreter keeps its framebuffer in, on a 4K machine.
+const DISPLAY_PAGE: usize = 0xF00;
+
+// CDP1861 video timing, in 1802 machine cycles and scan lines.
+const CYCLES_PER_LINE: i32 = 14;
+const LINES_PER_FRAME: u32 = 262;
+const INTERRUPT_LINE: u32 = 62;
+const DISPLAY_START: u32 = 64;
+const DISPLAY_END: u32 = 192;
+const DMA_PER_LINE: i32 = 8;
+/// EF1 is raised for the 4 lines before and the 4 last lines of the display.
+const EF1_LINES: [(u32, u32); 2] = [(DISPLAY_START - 4, DISPLAY_START), (DISPLAY_END - 4, DISPLAY_END)];
+
+// ----- Structs ----- //
+
+/// A complete COSMAC VIP, running the original CHIP-8 interpreter on its
+/// CDP1802 rather than interpreting the program directly.
+pub struct Vip {
+    cpu: Cdp1802,
+    bus: VipBus,
+    framebuffer: Framebuffer,
+}
+
+/// The VIP's memory and I/O devices, as seen by the processor.
+struct VipBus {
+    memory: Memory,
+    monitor: Vec<u8>,
+    keypad: Keypad,
+    key_latch: u8,  // Key selected by `OUT 2`
+    display_on: bool,
+    line: u32,
+}
+
+impl Vip {
+    /// Create a VIP with the CHIP-8 `interpreter` at 0x000 and the `rom` at
+    /// 0x200. The `monitor` ROM is only needed by interpreters that use its
+    /// routines (like the original one, for the font).
+    pub fn new(interpreter: &[u8], monitor: Option<Vec<u8>>, rom: &RomImage) -> Self {
+        let mut memory = Memory::new(rom.data());
+        memory.write(Address::from(0), interpreter);
+
+        // The monitor leaves the last RAM page in R1 before starting the
+        // program, and the interpreter sizes memory based on it.
+        let mut cpu = Cdp1802::new();
+        cpu.set_register(0x1, DISPLAY_PAGE as u16);
+
+        return Vip {
+            cpu,
+            bus: VipBus {
+                memory,
+                monitor: monitor.unwrap_or_default(),
+                keypad: Keypad::new(),
+                key_latch: 0,
+                display_on: false,
+                line: 0,
+            },
+            framebuffer: Framebuffer::new_mapped(DISPLAY_PAGE),
+        };
+    }
+
+    /// Run the machine forever, at the speed of the original, showing it on
+    /// the `terminal`.
+    pub fn run(&mut self, terminal: &mut Terminal) {
+        let mut deadline = Instant::now();
+
+        loop {
+            let keys = terminal::host_keys();
+            terminal.update_keypad(&mut self.bus.keypad, &keys);
+
+            self.frame();
+            terminal.present(&Display::new(&self.framebuffer, &self.bus.memory));
+
+            deadline += FRAME_DURATION;
+            terminal::wait_until(&mut deadline);
+        }
+    }
+
+    /// Run the processor for one video frame, one scan line at a time.
+    fn frame(&mut self) {
+        let mut budget = 0;
+
+        for line in 0..LINES_PER_FRAME {
+            self.bus.line = line;
+            budget += CYCLES_PER_LINE;
+
+            if line == INTERRUPT_LINE && self.bus.display_on {
+                self.cpu.interrupt();
+            }
+
+            if (DISPLAY_START..DISPLAY_END).contains(&line) && self.bus.display_on {
+                for _ in 0..DMA_PER_LINE {
+                    self.cpu.dma_out(&mut self.bus);
+                }
+                budget -= DMA_PER_LINE;
+            }
+
+            while budget > 0 {
+                budget -= self.cpu.step(&mut self.bus) as i32;
+            }
+        }
+    }
+}
+
+impl Bus for VipBus {
+    fn read(&mut self, address: u16) -> u8 {
+        if address & MONITOR_FLAG > 0 {
+            let offset = (address as usize) % MONITOR_SIZE;
+            return self.monitor.get(offset).copied().unwrap_or(0);
+        }
+        return self.memory.read_byte(Address::from(address as usize));
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        if address & MONITOR_FLAG == 0 {
+            self.memory.write(Address::from(address as usize), &[value]);
+        }
+    }
+
+    /// `INP 1` turns the CDP1861 on.
+    fn input(&mut self, port: u8) -> u8 {
+        if port == 1 {
+            self.display_on = true;
+        }
+        return 0;
+    }
+
+    /// `OUT 1` turns the CDP1861 off, and `OUT 2` selects the key EF3 reports.
+    fn output(&mut self, port: u8, value: u8) {
+        match port {
+            1 => { self.display_on = false; }
+            2 => { self.key_latch = value & 0x0F; }
+            _ => {}
+        }
+    }
+
+    fn flag(&mut self, flag: u8) -> bool {
+        match flag {
+            1 => { EF1_LINES.iter().any(|(start, end)| (*start..*end).contains(&self.line)) }
+            3 => { self.keypad.is_pressed(&Key::from(self.key_latch)) }
+            _ => { false }
+        }
+    }
+}
+
+// ----- Tests ----- //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bus(monitor: Vec<u8>) -> VipBus {
+        return VipBus {
+            memory: Memory::new(&[]),
+            monitor,
+            keypad: Keypad::new(),
+            key_latch: 0,
+            display_on: false,
+            line: 0,
+        };
+    }
+
+    #[test]
+    fn the_monitor_is_mirrored_and_read_only() {
+        let mut bus = bus(vec![0x12; MONITOR_SIZE]);
+        bus.write(0x8010, 0x34);
+        assert_eq!(bus.read(0x8010), 0x12);
+        assert_eq!(bus.read(0xC010), 0x12);
+        bus.write(0x0010, 0x34);
+        assert_eq!(bus.read(0x0010), 0x34);
+    }
+
+    #[test]
+    fn ports_drive_the_display_and_keypad() {
+        let mut bus = bus(Vec::new());
+        bus.input(1);
+        assert!(bus.display_on);
+        bus.output(1, 0);
+        assert!(!bus.display_on);
+
+        let mut pressed = [false; 0x10];
+        pressed[0xA] = true;
+        bus.keypad.set(pressed);
+        bus.output(2, 0x0A);
+        assert!(bus.flag(3));
+        bus.output(2, 0x0B);
+        assert!(!bus.flag(3));
+
+        bus.line = DISPLAY_START - 1;
+        assert!(bus.flag(1));
+        bus.line = DISPLAY_START;
+        assert!(!bus.flag(1));
+    }
+}
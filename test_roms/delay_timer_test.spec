@@ -0,0 +1,19 @@
+# Tests for the subroutines of delay_timer_test.ch8.txt.
+rom delay_timer_test.ch8
+platform chip8
+
+# _p_print clears the screen and shows v6 in decimal, at the top left.
+test prints_42
+set v4=00 v6=2a
+call _p_print
+expect v0=00 v1=04 v2=02 v3=0a
+expect memory 23a 00 04 02
+expect pixels 0,0 ####. #..#. #..#. #..#. ####.
+expect pixels 5,0 #..#. #..#. ####. ...#. ...#.
+expect pixels a,0 ####. ...#. ####. #.... ####.
+
+test prints_255_lower
+set v4=08 v6=ff
+call _p_print
+expect v0=02 v1=05 v2=05
+expect pixels 0,7 ..... ####. ...#. ####. #.... ####. .....
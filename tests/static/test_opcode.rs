@@ -0,0 +1,1673 @@
+// Generated by `chip-8 recompile` from the ROM with SHA-1 f1cfcffe1937ed6dd6eeed1a7f85dfc777bda700. Install it on a
+// machine running that ROM, with the same quirks, with
+// `machine.set_static_code(<module>::block)`.
+
+#![allow(clippy::needless_return, unused_imports)]
+
+use chip_8::cpu::CPU;
+use chip_8::cpu::instructions::Instruction;
+use chip_8::cpu::recompiler::StaticOp;
+use chip_8::error::ErrorKind;
+use chip_8::memory::{FONT_ADDR, FONT_HEIGHT};
+use chip_8::memory::address::Address;
+
+/// Get the recompiled code starting at `address`, if there is some.
+pub fn block(address: usize) -> Option<&'static [StaticOp]> {
+    match address {
+        0x200 => { Some(&BLOCK_200[0..]) }
+        0x242 => { Some(&BLOCK_242[0..]) }
+        0x244 => { Some(&BLOCK_242[1..]) }
+        0x246 => { Some(&BLOCK_242[2..]) }
+        0x248 => { Some(&BLOCK_242[3..]) }
+        0x24a => { Some(&BLOCK_242[4..]) }
+        0x24c => { Some(&BLOCK_242[5..]) }
+        0x24e => { Some(&BLOCK_242[6..]) }
+        0x250 => { Some(&BLOCK_242[7..]) }
+        0x252 => { Some(&BLOCK_242[8..]) }
+        0x254 => { Some(&BLOCK_242[9..]) }
+        0x256 => { Some(&BLOCK_242[10..]) }
+        0x258 => { Some(&BLOCK_242[11..]) }
+        0x25a => { Some(&BLOCK_242[12..]) }
+        0x25c => { Some(&BLOCK_242[13..]) }
+        0x25e => { Some(&BLOCK_242[14..]) }
+        0x260 => { Some(&BLOCK_242[15..]) }
+        0x262 => { Some(&BLOCK_242[16..]) }
+        0x264 => { Some(&BLOCK_242[17..]) }
+        0x266 => { Some(&BLOCK_242[18..]) }
+        0x268 => { Some(&BLOCK_242[19..]) }
+        0x26a => { Some(&BLOCK_242[20..]) }
+        0x26c => { Some(&BLOCK_242[21..]) }
+        0x26e => { Some(&BLOCK_242[22..]) }
+        0x270 => { Some(&BLOCK_242[23..]) }
+        0x272 => { Some(&BLOCK_242[24..]) }
+        0x274 => { Some(&BLOCK_242[25..]) }
+        0x276 => { Some(&BLOCK_242[26..]) }
+        0x278 => { Some(&BLOCK_242[27..]) }
+        0x27a => { Some(&BLOCK_242[28..]) }
+        0x27c => { Some(&BLOCK_242[29..]) }
+        0x27e => { Some(&BLOCK_242[30..]) }
+        0x280 => { Some(&BLOCK_242[31..]) }
+        0x282 => { Some(&BLOCK_242[32..]) }
+        0x284 => { Some(&BLOCK_242[33..]) }
+        0x286 => { Some(&BLOCK_242[34..]) }
+        0x288 => { Some(&BLOCK_242[35..]) }
+        0x28a => { Some(&BLOCK_242[36..]) }
+        0x28c => { Some(&BLOCK_242[37..]) }
+        0x28e => { Some(&BLOCK_242[38..]) }
+        0x290 => { Some(&BLOCK_242[39..]) }
+        0x292 => { Some(&BLOCK_242[40..]) }
+        0x294 => { Some(&BLOCK_242[41..]) }
+        0x296 => { Some(&BLOCK_242[42..]) }
+        0x298 => { Some(&BLOCK_242[43..]) }
+        0x29a => { Some(&BLOCK_242[44..]) }
+        0x29c => { Some(&BLOCK_242[45..]) }
+        0x29e => { Some(&BLOCK_242[46..]) }
+        0x2a0 => { Some(&BLOCK_242[47..]) }
+        0x2a2 => { Some(&BLOCK_242[48..]) }
+        0x2a4 => { Some(&BLOCK_242[49..]) }
+        0x2a6 => { Some(&BLOCK_242[50..]) }
+        0x2a8 => { Some(&BLOCK_242[51..]) }
+        0x2aa => { Some(&BLOCK_242[52..]) }
+        0x2ac => { Some(&BLOCK_242[53..]) }
+        0x2ae => { Some(&BLOCK_242[54..]) }
+        0x2b0 => { Some(&BLOCK_242[55..]) }
+        0x2b2 => { Some(&BLOCK_242[56..]) }
+        0x2b4 => { Some(&BLOCK_242[57..]) }
+        0x2b6 => { Some(&BLOCK_242[58..]) }
+        0x2b8 => { Some(&BLOCK_242[59..]) }
+        0x2ba => { Some(&BLOCK_242[60..]) }
+        0x2bc => { Some(&BLOCK_242[61..]) }
+        0x2be => { Some(&BLOCK_242[62..]) }
+        0x2c0 => { Some(&BLOCK_242[63..]) }
+        0x2c2 => { Some(&BLOCK_242[64..]) }
+        0x2c4 => { Some(&BLOCK_242[65..]) }
+        0x2c6 => { Some(&BLOCK_242[66..]) }
+        0x2c8 => { Some(&BLOCK_242[67..]) }
+        0x2ca => { Some(&BLOCK_242[68..]) }
+        0x2cc => { Some(&BLOCK_242[69..]) }
+        0x2ce => { Some(&BLOCK_242[70..]) }
+        0x2d0 => { Some(&BLOCK_242[71..]) }
+        0x2d2 => { Some(&BLOCK_242[72..]) }
+        0x2d4 => { Some(&BLOCK_242[73..]) }
+        0x2d6 => { Some(&BLOCK_242[74..]) }
+        0x2d8 => { Some(&BLOCK_242[75..]) }
+        0x2da => { Some(&BLOCK_242[76..]) }
+        0x2dc => { Some(&BLOCK_242[77..]) }
+        0x2de => { Some(&BLOCK_242[78..]) }
+        0x2e0 => { Some(&BLOCK_242[79..]) }
+        0x2e2 => { Some(&BLOCK_242[80..]) }
+        0x2e4 => { Some(&BLOCK_242[81..]) }
+        0x2e6 => { Some(&BLOCK_242[82..]) }
+        0x2e8 => { Some(&BLOCK_242[83..]) }
+        0x2ea => { Some(&BLOCK_242[84..]) }
+        0x2ec => { Some(&BLOCK_242[85..]) }
+        0x2ee => { Some(&BLOCK_242[86..]) }
+        0x2f0 => { Some(&BLOCK_242[87..]) }
+        0x2f2 => { Some(&BLOCK_242[88..]) }
+        0x2f4 => { Some(&BLOCK_242[89..]) }
+        0x2f6 => { Some(&BLOCK_242[90..]) }
+        0x2f8 => { Some(&BLOCK_242[91..]) }
+        0x2fa => { Some(&BLOCK_242[92..]) }
+        0x2fc => { Some(&BLOCK_242[93..]) }
+        0x2fe => { Some(&BLOCK_242[94..]) }
+        0x300 => { Some(&BLOCK_242[95..]) }
+        0x302 => { Some(&BLOCK_242[96..]) }
+        0x304 => { Some(&BLOCK_242[97..]) }
+        0x306 => { Some(&BLOCK_242[98..]) }
+        0x308 => { Some(&BLOCK_242[99..]) }
+        0x30a => { Some(&BLOCK_242[100..]) }
+        0x30c => { Some(&BLOCK_242[101..]) }
+        0x30e => { Some(&BLOCK_242[102..]) }
+        0x310 => { Some(&BLOCK_242[103..]) }
+        0x312 => { Some(&BLOCK_242[104..]) }
+        0x314 => { Some(&BLOCK_242[105..]) }
+        0x316 => { Some(&BLOCK_242[106..]) }
+        0x318 => { Some(&BLOCK_242[107..]) }
+        0x31a => { Some(&BLOCK_242[108..]) }
+        0x31c => { Some(&BLOCK_242[109..]) }
+        0x31e => { Some(&BLOCK_242[110..]) }
+        0x320 => { Some(&BLOCK_242[111..]) }
+        0x322 => { Some(&BLOCK_242[112..]) }
+        0x324 => { Some(&BLOCK_242[113..]) }
+        0x326 => { Some(&BLOCK_242[114..]) }
+        0x328 => { Some(&BLOCK_242[115..]) }
+        0x32a => { Some(&BLOCK_242[116..]) }
+        0x32c => { Some(&BLOCK_242[117..]) }
+        0x32e => { Some(&BLOCK_242[118..]) }
+        0x330 => { Some(&BLOCK_242[119..]) }
+        0x332 => { Some(&BLOCK_242[120..]) }
+        0x334 => { Some(&BLOCK_242[121..]) }
+        0x336 => { Some(&BLOCK_242[122..]) }
+        0x338 => { Some(&BLOCK_242[123..]) }
+        0x33a => { Some(&BLOCK_242[124..]) }
+        0x33c => { Some(&BLOCK_242[125..]) }
+        0x33e => { Some(&BLOCK_242[126..]) }
+        0x340 => { Some(&BLOCK_242[127..]) }
+        0x342 => { Some(&BLOCK_242[128..]) }
+        0x344 => { Some(&BLOCK_242[129..]) }
+        0x346 => { Some(&BLOCK_242[130..]) }
+        0x348 => { Some(&BLOCK_242[131..]) }
+        0x34a => { Some(&BLOCK_242[132..]) }
+        0x34c => { Some(&BLOCK_242[133..]) }
+        0x34e => { Some(&BLOCK_242[134..]) }
+        0x350 => { Some(&BLOCK_242[135..]) }
+        0x352 => { Some(&BLOCK_242[136..]) }
+        0x354 => { Some(&BLOCK_242[137..]) }
+        0x356 => { Some(&BLOCK_242[138..]) }
+        0x358 => { Some(&BLOCK_242[139..]) }
+        0x35a => { Some(&BLOCK_242[140..]) }
+        0x35c => { Some(&BLOCK_242[141..]) }
+        0x35e => { Some(&BLOCK_242[142..]) }
+        0x360 => { Some(&BLOCK_242[143..]) }
+        0x362 => { Some(&BLOCK_242[144..]) }
+        0x364 => { Some(&BLOCK_242[145..]) }
+        0x366 => { Some(&BLOCK_242[146..]) }
+        0x368 => { Some(&BLOCK_242[147..]) }
+        0x36a => { Some(&BLOCK_242[148..]) }
+        0x36c => { Some(&BLOCK_242[149..]) }
+        0x36e => { Some(&BLOCK_242[150..]) }
+        0x370 => { Some(&BLOCK_242[151..]) }
+        0x372 => { Some(&BLOCK_242[152..]) }
+        0x374 => { Some(&BLOCK_242[153..]) }
+        0x376 => { Some(&BLOCK_242[154..]) }
+        0x378 => { Some(&BLOCK_242[155..]) }
+        0x37a => { Some(&BLOCK_242[156..]) }
+        0x37c => { Some(&BLOCK_242[157..]) }
+        0x37e => { Some(&BLOCK_242[158..]) }
+        0x380 => { Some(&BLOCK_242[159..]) }
+        0x382 => { Some(&BLOCK_242[160..]) }
+        0x384 => { Some(&BLOCK_242[161..]) }
+        0x386 => { Some(&BLOCK_242[162..]) }
+        0x388 => { Some(&BLOCK_242[163..]) }
+        0x38a => { Some(&BLOCK_242[164..]) }
+        0x38c => { Some(&BLOCK_242[165..]) }
+        0x38e => { Some(&BLOCK_242[166..]) }
+        0x390 => { Some(&BLOCK_242[167..]) }
+        0x392 => { Some(&BLOCK_242[168..]) }
+        0x394 => { Some(&BLOCK_242[169..]) }
+        0x396 => { Some(&BLOCK_242[170..]) }
+        0x398 => { Some(&BLOCK_242[171..]) }
+        0x39a => { Some(&BLOCK_242[172..]) }
+        0x39c => { Some(&BLOCK_242[173..]) }
+        0x39e => { Some(&BLOCK_242[174..]) }
+        0x3a0 => { Some(&BLOCK_242[175..]) }
+        0x3a2 => { Some(&BLOCK_242[176..]) }
+        0x3a4 => { Some(&BLOCK_242[177..]) }
+        0x3a6 => { Some(&BLOCK_242[178..]) }
+        0x3a8 => { Some(&BLOCK_242[179..]) }
+        0x3aa => { Some(&BLOCK_242[180..]) }
+        0x3ac => { Some(&BLOCK_242[181..]) }
+        0x3ae => { Some(&BLOCK_242[182..]) }
+        0x3b0 => { Some(&BLOCK_242[183..]) }
+        0x3b2 => { Some(&BLOCK_242[184..]) }
+        0x3b4 => { Some(&BLOCK_242[185..]) }
+        0x3b6 => { Some(&BLOCK_242[186..]) }
+        0x3b8 => { Some(&BLOCK_242[187..]) }
+        0x3ba => { Some(&BLOCK_242[188..]) }
+        0x3bc => { Some(&BLOCK_242[189..]) }
+        0x3be => { Some(&BLOCK_242[190..]) }
+        0x3c0 => { Some(&BLOCK_242[191..]) }
+        0x3c2 => { Some(&BLOCK_242[192..]) }
+        0x3c4 => { Some(&BLOCK_242[193..]) }
+        0x3c6 => { Some(&BLOCK_242[194..]) }
+        0x3c8 => { Some(&BLOCK_242[195..]) }
+        0x3ca => { Some(&BLOCK_242[196..]) }
+        0x3cc => { Some(&BLOCK_242[197..]) }
+        0x3ce => { Some(&BLOCK_242[198..]) }
+        0x3d0 => { Some(&BLOCK_242[199..]) }
+        0x3d2 => { Some(&BLOCK_242[200..]) }
+        0x3d4 => { Some(&BLOCK_242[201..]) }
+        0x3d6 => { Some(&BLOCK_242[202..]) }
+        0x3d8 => { Some(&BLOCK_242[203..]) }
+        0x3da => { Some(&BLOCK_242[204..]) }
+        0x3dc => { Some(&BLOCK_242[205..]) }
+        _ => { None }
+    }
+}
+
+static BLOCK_200: [StaticOp; 1] = [
+    StaticOp { address: 0x200, opcode: 0x124e, run: op_200 },
+];
+
+static BLOCK_242: [StaticOp; 206] = [
+    StaticOp { address: 0x242, opcode: 0xa202, run: op_242 },
+    StaticOp { address: 0x244, opcode: 0xdab4, run: op_244 },
+    StaticOp { address: 0x246, opcode: 0x00ee, run: op_246 },
+    StaticOp { address: 0x248, opcode: 0xa202, run: op_248 },
+    StaticOp { address: 0x24a, opcode: 0xdab4, run: op_24a },
+    StaticOp { address: 0x24c, opcode: 0x13dc, run: op_24c },
+    StaticOp { address: 0x24e, opcode: 0x6801, run: op_24e },
+    StaticOp { address: 0x250, opcode: 0x6905, run: op_250 },
+    StaticOp { address: 0x252, opcode: 0x6a0a, run: op_252 },
+    StaticOp { address: 0x254, opcode: 0x6b01, run: op_254 },
+    StaticOp { address: 0x256, opcode: 0x652a, run: op_256 },
+    StaticOp { address: 0x258, opcode: 0x662b, run: op_258 },
+    StaticOp { address: 0x25a, opcode: 0xa216, run: op_25a },
+    StaticOp { address: 0x25c, opcode: 0xd8b4, run: op_25c },
+    StaticOp { address: 0x25e, opcode: 0xa23e, run: op_25e },
+    StaticOp { address: 0x260, opcode: 0xd9b4, run: op_260 },
+    StaticOp { address: 0x262, opcode: 0xa202, run: op_262 },
+    StaticOp { address: 0x264, opcode: 0x362b, run: op_264 },
+    StaticOp { address: 0x266, opcode: 0xa206, run: op_266 },
+    StaticOp { address: 0x268, opcode: 0xdab4, run: op_268 },
+    StaticOp { address: 0x26a, opcode: 0x6b06, run: op_26a },
+    StaticOp { address: 0x26c, opcode: 0xa21a, run: op_26c },
+    StaticOp { address: 0x26e, opcode: 0xd8b4, run: op_26e },
+    StaticOp { address: 0x270, opcode: 0xa23e, run: op_270 },
+    StaticOp { address: 0x272, opcode: 0xd9b4, run: op_272 },
+    StaticOp { address: 0x274, opcode: 0xa206, run: op_274 },
+    StaticOp { address: 0x276, opcode: 0x452a, run: op_276 },
+    StaticOp { address: 0x278, opcode: 0xa202, run: op_278 },
+    StaticOp { address: 0x27a, opcode: 0xdab4, run: op_27a },
+    StaticOp { address: 0x27c, opcode: 0x6b0b, run: op_27c },
+    StaticOp { address: 0x27e, opcode: 0xa21e, run: op_27e },
+    StaticOp { address: 0x280, opcode: 0xd8b4, run: op_280 },
+    StaticOp { address: 0x282, opcode: 0xa23e, run: op_282 },
+    StaticOp { address: 0x284, opcode: 0xd9b4, run: op_284 },
+    StaticOp { address: 0x286, opcode: 0xa206, run: op_286 },
+    StaticOp { address: 0x288, opcode: 0x5560, run: op_288 },
+    StaticOp { address: 0x28a, opcode: 0xa202, run: op_28a },
+    StaticOp { address: 0x28c, opcode: 0xdab4, run: op_28c },
+    StaticOp { address: 0x28e, opcode: 0x6b10, run: op_28e },
+    StaticOp { address: 0x290, opcode: 0xa226, run: op_290 },
+    StaticOp { address: 0x292, opcode: 0xd8b4, run: op_292 },
+    StaticOp { address: 0x294, opcode: 0xa23e, run: op_294 },
+    StaticOp { address: 0x296, opcode: 0xd9b4, run: op_296 },
+    StaticOp { address: 0x298, opcode: 0xa206, run: op_298 },
+    StaticOp { address: 0x29a, opcode: 0x76ff, run: op_29a },
+    StaticOp { address: 0x29c, opcode: 0x462a, run: op_29c },
+    StaticOp { address: 0x29e, opcode: 0xa202, run: op_29e },
+    StaticOp { address: 0x2a0, opcode: 0xdab4, run: op_2a0 },
+    StaticOp { address: 0x2a2, opcode: 0x6b15, run: op_2a2 },
+    StaticOp { address: 0x2a4, opcode: 0xa22e, run: op_2a4 },
+    StaticOp { address: 0x2a6, opcode: 0xd8b4, run: op_2a6 },
+    StaticOp { address: 0x2a8, opcode: 0xa23e, run: op_2a8 },
+    StaticOp { address: 0x2aa, opcode: 0xd9b4, run: op_2aa },
+    StaticOp { address: 0x2ac, opcode: 0xa206, run: op_2ac },
+    StaticOp { address: 0x2ae, opcode: 0x9560, run: op_2ae },
+    StaticOp { address: 0x2b0, opcode: 0xa202, run: op_2b0 },
+    StaticOp { address: 0x2b2, opcode: 0xdab4, run: op_2b2 },
+    StaticOp { address: 0x2b4, opcode: 0x6b1a, run: op_2b4 },
+    StaticOp { address: 0x2b6, opcode: 0xa232, run: op_2b6 },
+    StaticOp { address: 0x2b8, opcode: 0xd8b4, run: op_2b8 },
+    StaticOp { address: 0x2ba, opcode: 0xa23e, run: op_2ba },
+    StaticOp { address: 0x2bc, opcode: 0xd9b4, run: op_2bc },
+    StaticOp { address: 0x2be, opcode: 0x2242, run: op_2be },
+    StaticOp { address: 0x2c0, opcode: 0x6817, run: op_2c0 },
+    StaticOp { address: 0x2c2, opcode: 0x691b, run: op_2c2 },
+    StaticOp { address: 0x2c4, opcode: 0x6a20, run: op_2c4 },
+    StaticOp { address: 0x2c6, opcode: 0x6b01, run: op_2c6 },
+    StaticOp { address: 0x2c8, opcode: 0xa20a, run: op_2c8 },
+    StaticOp { address: 0x2ca, opcode: 0xd8b4, run: op_2ca },
+    StaticOp { address: 0x2cc, opcode: 0xa236, run: op_2cc },
+    StaticOp { address: 0x2ce, opcode: 0xd9b4, run: op_2ce },
+    StaticOp { address: 0x2d0, opcode: 0xa202, run: op_2d0 },
+    StaticOp { address: 0x2d2, opcode: 0xdab4, run: op_2d2 },
+    StaticOp { address: 0x2d4, opcode: 0x6b06, run: op_2d4 },
+    StaticOp { address: 0x2d6, opcode: 0xa22a, run: op_2d6 },
+    StaticOp { address: 0x2d8, opcode: 0xd8b4, run: op_2d8 },
+    StaticOp { address: 0x2da, opcode: 0xa20a, run: op_2da },
+    StaticOp { address: 0x2dc, opcode: 0xd9b4, run: op_2dc },
+    StaticOp { address: 0x2de, opcode: 0xa206, run: op_2de },
+    StaticOp { address: 0x2e0, opcode: 0x8750, run: op_2e0 },
+    StaticOp { address: 0x2e2, opcode: 0x472a, run: op_2e2 },
+    StaticOp { address: 0x2e4, opcode: 0xa202, run: op_2e4 },
+    StaticOp { address: 0x2e6, opcode: 0xdab4, run: op_2e6 },
+    StaticOp { address: 0x2e8, opcode: 0x6b0b, run: op_2e8 },
+    StaticOp { address: 0x2ea, opcode: 0xa22a, run: op_2ea },
+    StaticOp { address: 0x2ec, opcode: 0xd8b4, run: op_2ec },
+    StaticOp { address: 0x2ee, opcode: 0xa20e, run: op_2ee },
+    StaticOp { address: 0x2f0, opcode: 0xd9b4, run: op_2f0 },
+    StaticOp { address: 0x2f2, opcode: 0xa206, run: op_2f2 },
+    StaticOp { address: 0x2f4, opcode: 0x672a, run: op_2f4 },
+    StaticOp { address: 0x2f6, opcode: 0x87b1, run: op_2f6 },
+    StaticOp { address: 0x2f8, opcode: 0x472b, run: op_2f8 },
+    StaticOp { address: 0x2fa, opcode: 0xa202, run: op_2fa },
+    StaticOp { address: 0x2fc, opcode: 0xdab4, run: op_2fc },
+    StaticOp { address: 0x2fe, opcode: 0x6b10, run: op_2fe },
+    StaticOp { address: 0x300, opcode: 0xa22a, run: op_300 },
+    StaticOp { address: 0x302, opcode: 0xd8b4, run: op_302 },
+    StaticOp { address: 0x304, opcode: 0xa212, run: op_304 },
+    StaticOp { address: 0x306, opcode: 0xd9b4, run: op_306 },
+    StaticOp { address: 0x308, opcode: 0xa206, run: op_308 },
+    StaticOp { address: 0x30a, opcode: 0x6678, run: op_30a },
+    StaticOp { address: 0x30c, opcode: 0x671f, run: op_30c },
+    StaticOp { address: 0x30e, opcode: 0x8762, run: op_30e },
+    StaticOp { address: 0x310, opcode: 0x4718, run: op_310 },
+    StaticOp { address: 0x312, opcode: 0xa202, run: op_312 },
+    StaticOp { address: 0x314, opcode: 0xdab4, run: op_314 },
+    StaticOp { address: 0x316, opcode: 0x6b15, run: op_316 },
+    StaticOp { address: 0x318, opcode: 0xa22a, run: op_318 },
+    StaticOp { address: 0x31a, opcode: 0xd8b4, run: op_31a },
+    StaticOp { address: 0x31c, opcode: 0xa216, run: op_31c },
+    StaticOp { address: 0x31e, opcode: 0xd9b4, run: op_31e },
+    StaticOp { address: 0x320, opcode: 0xa206, run: op_320 },
+    StaticOp { address: 0x322, opcode: 0x6678, run: op_322 },
+    StaticOp { address: 0x324, opcode: 0x671f, run: op_324 },
+    StaticOp { address: 0x326, opcode: 0x8763, run: op_326 },
+    StaticOp { address: 0x328, opcode: 0x4767, run: op_328 },
+    StaticOp { address: 0x32a, opcode: 0xa202, run: op_32a },
+    StaticOp { address: 0x32c, opcode: 0xdab4, run: op_32c },
+    StaticOp { address: 0x32e, opcode: 0x6b1a, run: op_32e },
+    StaticOp { address: 0x330, opcode: 0xa22a, run: op_330 },
+    StaticOp { address: 0x332, opcode: 0xd8b4, run: op_332 },
+    StaticOp { address: 0x334, opcode: 0xa21a, run: op_334 },
+    StaticOp { address: 0x336, opcode: 0xd9b4, run: op_336 },
+    StaticOp { address: 0x338, opcode: 0xa206, run: op_338 },
+    StaticOp { address: 0x33a, opcode: 0x668c, run: op_33a },
+    StaticOp { address: 0x33c, opcode: 0x678c, run: op_33c },
+    StaticOp { address: 0x33e, opcode: 0x8764, run: op_33e },
+    StaticOp { address: 0x340, opcode: 0x4718, run: op_340 },
+    StaticOp { address: 0x342, opcode: 0xa202, run: op_342 },
+    StaticOp { address: 0x344, opcode: 0xdab4, run: op_344 },
+    StaticOp { address: 0x346, opcode: 0x682c, run: op_346 },
+    StaticOp { address: 0x348, opcode: 0x6930, run: op_348 },
+    StaticOp { address: 0x34a, opcode: 0x6a34, run: op_34a },
+    StaticOp { address: 0x34c, opcode: 0x6b01, run: op_34c },
+    StaticOp { address: 0x34e, opcode: 0xa22a, run: op_34e },
+    StaticOp { address: 0x350, opcode: 0xd8b4, run: op_350 },
+    StaticOp { address: 0x352, opcode: 0xa21e, run: op_352 },
+    StaticOp { address: 0x354, opcode: 0xd9b4, run: op_354 },
+    StaticOp { address: 0x356, opcode: 0xa206, run: op_356 },
+    StaticOp { address: 0x358, opcode: 0x668c, run: op_358 },
+    StaticOp { address: 0x35a, opcode: 0x6778, run: op_35a },
+    StaticOp { address: 0x35c, opcode: 0x8765, run: op_35c },
+    StaticOp { address: 0x35e, opcode: 0x47ec, run: op_35e },
+    StaticOp { address: 0x360, opcode: 0xa202, run: op_360 },
+    StaticOp { address: 0x362, opcode: 0xdab4, run: op_362 },
+    StaticOp { address: 0x364, opcode: 0x6b06, run: op_364 },
+    StaticOp { address: 0x366, opcode: 0xa22a, run: op_366 },
+    StaticOp { address: 0x368, opcode: 0xd8b4, run: op_368 },
+    StaticOp { address: 0x36a, opcode: 0xa222, run: op_36a },
+    StaticOp { address: 0x36c, opcode: 0xd9b4, run: op_36c },
+    StaticOp { address: 0x36e, opcode: 0xa206, run: op_36e },
+    StaticOp { address: 0x370, opcode: 0x66e0, run: op_370 },
+    StaticOp { address: 0x372, opcode: 0x866e, run: op_372 },
+    StaticOp { address: 0x374, opcode: 0x46c0, run: op_374 },
+    StaticOp { address: 0x376, opcode: 0xa202, run: op_376 },
+    StaticOp { address: 0x378, opcode: 0xdab4, run: op_378 },
+    StaticOp { address: 0x37a, opcode: 0x6b0b, run: op_37a },
+    StaticOp { address: 0x37c, opcode: 0xa22a, run: op_37c },
+    StaticOp { address: 0x37e, opcode: 0xd8b4, run: op_37e },
+    StaticOp { address: 0x380, opcode: 0xa236, run: op_380 },
+    StaticOp { address: 0x382, opcode: 0xd9b4, run: op_382 },
+    StaticOp { address: 0x384, opcode: 0xa206, run: op_384 },
+    StaticOp { address: 0x386, opcode: 0x660f, run: op_386 },
+    StaticOp { address: 0x388, opcode: 0x8666, run: op_388 },
+    StaticOp { address: 0x38a, opcode: 0x4607, run: op_38a },
+    StaticOp { address: 0x38c, opcode: 0xa202, run: op_38c },
+    StaticOp { address: 0x38e, opcode: 0xdab4, run: op_38e },
+    StaticOp { address: 0x390, opcode: 0x6b10, run: op_390 },
+    StaticOp { address: 0x392, opcode: 0xa23a, run: op_392 },
+    StaticOp { address: 0x394, opcode: 0xd8b4, run: op_394 },
+    StaticOp { address: 0x396, opcode: 0xa21e, run: op_396 },
+    StaticOp { address: 0x398, opcode: 0xd9b4, run: op_398 },
+    StaticOp { address: 0x39a, opcode: 0xa3e8, run: op_39a },
+    StaticOp { address: 0x39c, opcode: 0x6000, run: op_39c },
+    StaticOp { address: 0x39e, opcode: 0x6130, run: op_39e },
+    StaticOp { address: 0x3a0, opcode: 0xf155, run: op_3a0 },
+    StaticOp { address: 0x3a2, opcode: 0xa3e9, run: op_3a2 },
+    StaticOp { address: 0x3a4, opcode: 0xf065, run: op_3a4 },
+    StaticOp { address: 0x3a6, opcode: 0xa206, run: op_3a6 },
+    StaticOp { address: 0x3a8, opcode: 0x4030, run: op_3a8 },
+    StaticOp { address: 0x3aa, opcode: 0xa202, run: op_3aa },
+    StaticOp { address: 0x3ac, opcode: 0xdab4, run: op_3ac },
+    StaticOp { address: 0x3ae, opcode: 0x6b15, run: op_3ae },
+    StaticOp { address: 0x3b0, opcode: 0xa23a, run: op_3b0 },
+    StaticOp { address: 0x3b2, opcode: 0xd8b4, run: op_3b2 },
+    StaticOp { address: 0x3b4, opcode: 0xa216, run: op_3b4 },
+    StaticOp { address: 0x3b6, opcode: 0xd9b4, run: op_3b6 },
+    StaticOp { address: 0x3b8, opcode: 0xa3e8, run: op_3b8 },
+    StaticOp { address: 0x3ba, opcode: 0x6689, run: op_3ba },
+    StaticOp { address: 0x3bc, opcode: 0xf633, run: op_3bc },
+    StaticOp { address: 0x3be, opcode: 0xf265, run: op_3be },
+    StaticOp { address: 0x3c0, opcode: 0xa202, run: op_3c0 },
+    StaticOp { address: 0x3c2, opcode: 0x3001, run: op_3c2 },
+    StaticOp { address: 0x3c4, opcode: 0xa206, run: op_3c4 },
+    StaticOp { address: 0x3c6, opcode: 0x3103, run: op_3c6 },
+    StaticOp { address: 0x3c8, opcode: 0xa206, run: op_3c8 },
+    StaticOp { address: 0x3ca, opcode: 0x3207, run: op_3ca },
+    StaticOp { address: 0x3cc, opcode: 0xa206, run: op_3cc },
+    StaticOp { address: 0x3ce, opcode: 0xdab4, run: op_3ce },
+    StaticOp { address: 0x3d0, opcode: 0x6b1a, run: op_3d0 },
+    StaticOp { address: 0x3d2, opcode: 0xa20e, run: op_3d2 },
+    StaticOp { address: 0x3d4, opcode: 0xd8b4, run: op_3d4 },
+    StaticOp { address: 0x3d6, opcode: 0xa23e, run: op_3d6 },
+    StaticOp { address: 0x3d8, opcode: 0xd9b4, run: op_3d8 },
+    StaticOp { address: 0x3da, opcode: 0x1248, run: op_3da },
+    StaticOp { address: 0x3dc, opcode: 0x13dc, run: op_3dc },
+];
+
+// 200: JUMP(24e)
+fn op_200(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0x124e));
+}
+
+// 242: SETN(202)
+fn op_242(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 244: DRAW(va, vb, 4)
+fn op_244(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 246: RET()
+fn op_246(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0x00ee));
+}
+
+// 248: SETN(202)
+fn op_248(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 24a: DRAW(va, vb, 4)
+fn op_24a(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 24c: JUMP(3dc)
+fn op_24c(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0x13dc));
+}
+
+// 24e: SETI(v8, 01)
+fn op_24e(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x8, 0x01);
+    return Ok(());
+}
+
+// 250: SETI(v9, 05)
+fn op_250(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x9, 0x05);
+    return Ok(());
+}
+
+// 252: SETI(va, 0a)
+fn op_252(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xa, 0x0a);
+    return Ok(());
+}
+
+// 254: SETI(vb, 01)
+fn op_254(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x01);
+    return Ok(());
+}
+
+// 256: SETI(v5, 2a)
+fn op_256(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x5, 0x2a);
+    return Ok(());
+}
+
+// 258: SETI(v6, 2b)
+fn op_258(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x6, 0x2b);
+    return Ok(());
+}
+
+// 25a: SETN(216)
+fn op_25a(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x216));
+    return Ok(());
+}
+
+// 25c: DRAW(v8, vb, 4)
+fn op_25c(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 25e: SETN(23e)
+fn op_25e(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x23e));
+    return Ok(());
+}
+
+// 260: DRAW(v9, vb, 4)
+fn op_260(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 262: SETN(202)
+fn op_262(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 264: SEQ(v6, 2b)
+fn op_264(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x6) == 0x2b {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 266: SETN(206)
+fn op_266(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 268: DRAW(va, vb, 4)
+fn op_268(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 26a: SETI(vb, 06)
+fn op_26a(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x06);
+    return Ok(());
+}
+
+// 26c: SETN(21a)
+fn op_26c(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x21a));
+    return Ok(());
+}
+
+// 26e: DRAW(v8, vb, 4)
+fn op_26e(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 270: SETN(23e)
+fn op_270(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x23e));
+    return Ok(());
+}
+
+// 272: DRAW(v9, vb, 4)
+fn op_272(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 274: SETN(206)
+fn op_274(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 276: SNE(v5, 2a)
+fn op_276(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x5) != 0x2a {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 278: SETN(202)
+fn op_278(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 27a: DRAW(va, vb, 4)
+fn op_27a(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 27c: SETI(vb, 0b)
+fn op_27c(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x0b);
+    return Ok(());
+}
+
+// 27e: SETN(21e)
+fn op_27e(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x21e));
+    return Ok(());
+}
+
+// 280: DRAW(v8, vb, 4)
+fn op_280(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 282: SETN(23e)
+fn op_282(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x23e));
+    return Ok(());
+}
+
+// 284: DRAW(v9, vb, 4)
+fn op_284(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 286: SETN(206)
+fn op_286(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 288: SRE(v5, v6)
+fn op_288(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x5) == cpu.registers_mut().get_variable(0x6) {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 28a: SETN(202)
+fn op_28a(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 28c: DRAW(va, vb, 4)
+fn op_28c(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 28e: SETI(vb, 10)
+fn op_28e(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x10);
+    return Ok(());
+}
+
+// 290: SETN(226)
+fn op_290(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x226));
+    return Ok(());
+}
+
+// 292: DRAW(v8, vb, 4)
+fn op_292(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 294: SETN(23e)
+fn op_294(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x23e));
+    return Ok(());
+}
+
+// 296: DRAW(v9, vb, 4)
+fn op_296(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 298: SETN(206)
+fn op_298(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 29a: ADDI(v6, ff)
+fn op_29a(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    let registers = cpu.registers_mut();
+    let value = registers.get_variable(0x6).wrapping_add(0xff);
+    registers.set_variable(0x6, value);
+    return Ok(());
+}
+
+// 29c: SNE(v6, 2a)
+fn op_29c(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x6) != 0x2a {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 29e: SETN(202)
+fn op_29e(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 2a0: DRAW(va, vb, 4)
+fn op_2a0(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 2a2: SETI(vb, 15)
+fn op_2a2(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x15);
+    return Ok(());
+}
+
+// 2a4: SETN(22e)
+fn op_2a4(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x22e));
+    return Ok(());
+}
+
+// 2a6: DRAW(v8, vb, 4)
+fn op_2a6(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 2a8: SETN(23e)
+fn op_2a8(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x23e));
+    return Ok(());
+}
+
+// 2aa: DRAW(v9, vb, 4)
+fn op_2aa(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 2ac: SETN(206)
+fn op_2ac(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 2ae: SRNE(v5, v6)
+fn op_2ae(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x5) != cpu.registers_mut().get_variable(0x6) {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 2b0: SETN(202)
+fn op_2b0(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 2b2: DRAW(va, vb, 4)
+fn op_2b2(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 2b4: SETI(vb, 1a)
+fn op_2b4(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x1a);
+    return Ok(());
+}
+
+// 2b6: SETN(232)
+fn op_2b6(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x232));
+    return Ok(());
+}
+
+// 2b8: DRAW(v8, vb, 4)
+fn op_2b8(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 2ba: SETN(23e)
+fn op_2ba(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x23e));
+    return Ok(());
+}
+
+// 2bc: DRAW(v9, vb, 4)
+fn op_2bc(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 2be: CALL(242)
+fn op_2be(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0x2242));
+}
+
+// 2c0: SETI(v8, 17)
+fn op_2c0(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x8, 0x17);
+    return Ok(());
+}
+
+// 2c2: SETI(v9, 1b)
+fn op_2c2(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x9, 0x1b);
+    return Ok(());
+}
+
+// 2c4: SETI(va, 20)
+fn op_2c4(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xa, 0x20);
+    return Ok(());
+}
+
+// 2c6: SETI(vb, 01)
+fn op_2c6(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x01);
+    return Ok(());
+}
+
+// 2c8: SETN(20a)
+fn op_2c8(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x20a));
+    return Ok(());
+}
+
+// 2ca: DRAW(v8, vb, 4)
+fn op_2ca(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 2cc: SETN(236)
+fn op_2cc(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x236));
+    return Ok(());
+}
+
+// 2ce: DRAW(v9, vb, 4)
+fn op_2ce(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 2d0: SETN(202)
+fn op_2d0(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 2d2: DRAW(va, vb, 4)
+fn op_2d2(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 2d4: SETI(vb, 06)
+fn op_2d4(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x06);
+    return Ok(());
+}
+
+// 2d6: SETN(22a)
+fn op_2d6(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x22a));
+    return Ok(());
+}
+
+// 2d8: DRAW(v8, vb, 4)
+fn op_2d8(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 2da: SETN(20a)
+fn op_2da(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x20a));
+    return Ok(());
+}
+
+// 2dc: DRAW(v9, vb, 4)
+fn op_2dc(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 2de: SETN(206)
+fn op_2de(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 2e0: SET(v7, v5)
+fn op_2e0(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    let registers = cpu.registers_mut();
+    let value = registers.get_variable(0x5);
+    registers.set_variable(0x7, value);
+    return Ok(());
+}
+
+// 2e2: SNE(v7, 2a)
+fn op_2e2(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x7) != 0x2a {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 2e4: SETN(202)
+fn op_2e4(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 2e6: DRAW(va, vb, 4)
+fn op_2e6(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 2e8: SETI(vb, 0b)
+fn op_2e8(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x0b);
+    return Ok(());
+}
+
+// 2ea: SETN(22a)
+fn op_2ea(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x22a));
+    return Ok(());
+}
+
+// 2ec: DRAW(v8, vb, 4)
+fn op_2ec(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 2ee: SETN(20e)
+fn op_2ee(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x20e));
+    return Ok(());
+}
+
+// 2f0: DRAW(v9, vb, 4)
+fn op_2f0(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 2f2: SETN(206)
+fn op_2f2(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 2f4: SETI(v7, 2a)
+fn op_2f4(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x7, 0x2a);
+    return Ok(());
+}
+
+// 2f6: OR(v7, vb)
+fn op_2f6(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    let registers = cpu.registers_mut();
+    let value = registers.get_variable(0x7) | registers.get_variable(0xb);
+    registers.set_variable(0x7, value);
+    return Ok(());
+}
+
+// 2f8: SNE(v7, 2b)
+fn op_2f8(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x7) != 0x2b {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 2fa: SETN(202)
+fn op_2fa(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 2fc: DRAW(va, vb, 4)
+fn op_2fc(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 2fe: SETI(vb, 10)
+fn op_2fe(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x10);
+    return Ok(());
+}
+
+// 300: SETN(22a)
+fn op_300(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x22a));
+    return Ok(());
+}
+
+// 302: DRAW(v8, vb, 4)
+fn op_302(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 304: SETN(212)
+fn op_304(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x212));
+    return Ok(());
+}
+
+// 306: DRAW(v9, vb, 4)
+fn op_306(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 308: SETN(206)
+fn op_308(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 30a: SETI(v6, 78)
+fn op_30a(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x6, 0x78);
+    return Ok(());
+}
+
+// 30c: SETI(v7, 1f)
+fn op_30c(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x7, 0x1f);
+    return Ok(());
+}
+
+// 30e: AND(v7, v6)
+fn op_30e(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    let registers = cpu.registers_mut();
+    let value = registers.get_variable(0x7) & registers.get_variable(0x6);
+    registers.set_variable(0x7, value);
+    return Ok(());
+}
+
+// 310: SNE(v7, 18)
+fn op_310(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x7) != 0x18 {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 312: SETN(202)
+fn op_312(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 314: DRAW(va, vb, 4)
+fn op_314(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 316: SETI(vb, 15)
+fn op_316(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x15);
+    return Ok(());
+}
+
+// 318: SETN(22a)
+fn op_318(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x22a));
+    return Ok(());
+}
+
+// 31a: DRAW(v8, vb, 4)
+fn op_31a(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 31c: SETN(216)
+fn op_31c(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x216));
+    return Ok(());
+}
+
+// 31e: DRAW(v9, vb, 4)
+fn op_31e(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 320: SETN(206)
+fn op_320(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 322: SETI(v6, 78)
+fn op_322(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x6, 0x78);
+    return Ok(());
+}
+
+// 324: SETI(v7, 1f)
+fn op_324(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x7, 0x1f);
+    return Ok(());
+}
+
+// 326: XOR(v7, v6)
+fn op_326(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    let registers = cpu.registers_mut();
+    let value = registers.get_variable(0x7) ^ registers.get_variable(0x6);
+    registers.set_variable(0x7, value);
+    return Ok(());
+}
+
+// 328: SNE(v7, 67)
+fn op_328(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x7) != 0x67 {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 32a: SETN(202)
+fn op_32a(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 32c: DRAW(va, vb, 4)
+fn op_32c(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 32e: SETI(vb, 1a)
+fn op_32e(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x1a);
+    return Ok(());
+}
+
+// 330: SETN(22a)
+fn op_330(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x22a));
+    return Ok(());
+}
+
+// 332: DRAW(v8, vb, 4)
+fn op_332(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 334: SETN(21a)
+fn op_334(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x21a));
+    return Ok(());
+}
+
+// 336: DRAW(v9, vb, 4)
+fn op_336(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 338: SETN(206)
+fn op_338(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 33a: SETI(v6, 8c)
+fn op_33a(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x6, 0x8c);
+    return Ok(());
+}
+
+// 33c: SETI(v7, 8c)
+fn op_33c(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x7, 0x8c);
+    return Ok(());
+}
+
+// 33e: ADD(v7, v6)
+fn op_33e(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    let registers = cpu.registers_mut();
+    let (value, carry) = registers.get_variable(0x7).overflowing_add(registers.get_variable(0x6));
+    registers.set_flag(carry);
+    registers.set_variable(0x7, value);
+    return Ok(());
+}
+
+// 340: SNE(v7, 18)
+fn op_340(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x7) != 0x18 {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 342: SETN(202)
+fn op_342(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 344: DRAW(va, vb, 4)
+fn op_344(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 346: SETI(v8, 2c)
+fn op_346(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x8, 0x2c);
+    return Ok(());
+}
+
+// 348: SETI(v9, 30)
+fn op_348(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x9, 0x30);
+    return Ok(());
+}
+
+// 34a: SETI(va, 34)
+fn op_34a(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xa, 0x34);
+    return Ok(());
+}
+
+// 34c: SETI(vb, 01)
+fn op_34c(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x01);
+    return Ok(());
+}
+
+// 34e: SETN(22a)
+fn op_34e(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x22a));
+    return Ok(());
+}
+
+// 350: DRAW(v8, vb, 4)
+fn op_350(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 352: SETN(21e)
+fn op_352(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x21e));
+    return Ok(());
+}
+
+// 354: DRAW(v9, vb, 4)
+fn op_354(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 356: SETN(206)
+fn op_356(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 358: SETI(v6, 8c)
+fn op_358(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x6, 0x8c);
+    return Ok(());
+}
+
+// 35a: SETI(v7, 78)
+fn op_35a(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x7, 0x78);
+    return Ok(());
+}
+
+// 35c: SUB(v7, v6)
+fn op_35c(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    let registers = cpu.registers_mut();
+    let (a, b) = (registers.get_variable(0x7), registers.get_variable(0x6));
+    registers.set_flag(a >= b);
+    registers.set_variable(0x7, a.wrapping_sub(b));
+    return Ok(());
+}
+
+// 35e: SNE(v7, ec)
+fn op_35e(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x7) != 0xec {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 360: SETN(202)
+fn op_360(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 362: DRAW(va, vb, 4)
+fn op_362(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 364: SETI(vb, 06)
+fn op_364(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x06);
+    return Ok(());
+}
+
+// 366: SETN(22a)
+fn op_366(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x22a));
+    return Ok(());
+}
+
+// 368: DRAW(v8, vb, 4)
+fn op_368(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 36a: SETN(222)
+fn op_36a(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x222));
+    return Ok(());
+}
+
+// 36c: DRAW(v9, vb, 4)
+fn op_36c(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 36e: SETN(206)
+fn op_36e(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 370: SETI(v6, e0)
+fn op_370(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x6, 0xe0);
+    return Ok(());
+}
+
+// 372: SHL(v6, v6)
+fn op_372(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    let registers = cpu.registers_mut();
+    let value = registers.get_variable(0x6);
+    registers.set_flag(value & 0x80 > 0);
+    registers.set_variable(0x6, value << 1);
+    return Ok(());
+}
+
+// 374: SNE(v6, c0)
+fn op_374(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x6) != 0xc0 {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 376: SETN(202)
+fn op_376(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 378: DRAW(va, vb, 4)
+fn op_378(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 37a: SETI(vb, 0b)
+fn op_37a(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x0b);
+    return Ok(());
+}
+
+// 37c: SETN(22a)
+fn op_37c(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x22a));
+    return Ok(());
+}
+
+// 37e: DRAW(v8, vb, 4)
+fn op_37e(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 380: SETN(236)
+fn op_380(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x236));
+    return Ok(());
+}
+
+// 382: DRAW(v9, vb, 4)
+fn op_382(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 384: SETN(206)
+fn op_384(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 386: SETI(v6, 0f)
+fn op_386(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x6, 0x0f);
+    return Ok(());
+}
+
+// 388: SHR(v6, v6)
+fn op_388(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    let registers = cpu.registers_mut();
+    let value = registers.get_variable(0x6);
+    registers.set_flag(value & 0x01 > 0);
+    registers.set_variable(0x6, value >> 1);
+    return Ok(());
+}
+
+// 38a: SNE(v6, 07)
+fn op_38a(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x6) != 0x07 {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 38c: SETN(202)
+fn op_38c(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 38e: DRAW(va, vb, 4)
+fn op_38e(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 390: SETI(vb, 10)
+fn op_390(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x10);
+    return Ok(());
+}
+
+// 392: SETN(23a)
+fn op_392(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x23a));
+    return Ok(());
+}
+
+// 394: DRAW(v8, vb, 4)
+fn op_394(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 396: SETN(21e)
+fn op_396(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x21e));
+    return Ok(());
+}
+
+// 398: DRAW(v9, vb, 4)
+fn op_398(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 39a: SETN(3e8)
+fn op_39a(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x3e8));
+    return Ok(());
+}
+
+// 39c: SETI(v0, 00)
+fn op_39c(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x0, 0x00);
+    return Ok(());
+}
+
+// 39e: SETI(v1, 30)
+fn op_39e(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x1, 0x30);
+    return Ok(());
+}
+
+// 3a0: STM(v1)
+fn op_3a0(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xf155));
+}
+
+// 3a2: SETN(3e9)
+fn op_3a2(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x3e9));
+    return Ok(());
+}
+
+// 3a4: LDM(v0)
+fn op_3a4(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xf065));
+}
+
+// 3a6: SETN(206)
+fn op_3a6(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 3a8: SNE(v0, 30)
+fn op_3a8(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x0) != 0x30 {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 3aa: SETN(202)
+fn op_3aa(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 3ac: DRAW(va, vb, 4)
+fn op_3ac(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 3ae: SETI(vb, 15)
+fn op_3ae(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x15);
+    return Ok(());
+}
+
+// 3b0: SETN(23a)
+fn op_3b0(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x23a));
+    return Ok(());
+}
+
+// 3b2: DRAW(v8, vb, 4)
+fn op_3b2(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 3b4: SETN(216)
+fn op_3b4(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x216));
+    return Ok(());
+}
+
+// 3b6: DRAW(v9, vb, 4)
+fn op_3b6(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 3b8: SETN(3e8)
+fn op_3b8(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x3e8));
+    return Ok(());
+}
+
+// 3ba: SETI(v6, 89)
+fn op_3ba(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0x6, 0x89);
+    return Ok(());
+}
+
+// 3bc: BCD(v6)
+fn op_3bc(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xf633));
+}
+
+// 3be: LDM(v2)
+fn op_3be(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xf265));
+}
+
+// 3c0: SETN(202)
+fn op_3c0(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x202));
+    return Ok(());
+}
+
+// 3c2: SEQ(v0, 01)
+fn op_3c2(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x0) == 0x01 {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 3c4: SETN(206)
+fn op_3c4(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 3c6: SEQ(v1, 03)
+fn op_3c6(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x1) == 0x03 {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 3c8: SETN(206)
+fn op_3c8(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 3ca: SEQ(v2, 07)
+fn op_3ca(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    if cpu.registers_mut().get_variable(0x2) == 0x07 {
+        cpu.pc_mut().increment()?;
+    }
+    return Ok(());
+}
+
+// 3cc: SETN(206)
+fn op_3cc(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x206));
+    return Ok(());
+}
+
+// 3ce: DRAW(va, vb, 4)
+fn op_3ce(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xdab4));
+}
+
+// 3d0: SETI(vb, 1a)
+fn op_3d0(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_variable(0xb, 0x1a);
+    return Ok(());
+}
+
+// 3d2: SETN(20e)
+fn op_3d2(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x20e));
+    return Ok(());
+}
+
+// 3d4: DRAW(v8, vb, 4)
+fn op_3d4(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd8b4));
+}
+
+// 3d6: SETN(23e)
+fn op_3d6(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    cpu.registers_mut().set_index(Address::from(0x23e));
+    return Ok(());
+}
+
+// 3d8: DRAW(v9, vb, 4)
+fn op_3d8(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0xd9b4));
+}
+
+// 3da: JUMP(248)
+fn op_3da(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0x1248));
+}
+
+// 3dc: JUMP(3dc)
+fn op_3dc(cpu: &mut CPU) -> Result<(), ErrorKind> {
+    return cpu.execute(Instruction::from(0x13dc));
+}
@@ -0,0 +1,69 @@
+#![allow(clippy::needless_return)]
+
+// ----- Modules ----- //
+
+/// `chip-8 recompile tests/static/test_opcode.rs test_roms/test_opcode.ch8`
+#[path = "static/test_opcode.rs"]
+mod test_opcode;
+
+// ----- Imports ----- //
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use chip_8::config::Config;
+use chip_8::cpu::recompiler::StaticOp;
+use chip_8::machine::Machine;
+use chip_8::rom::RomImage;
+use chip_8::static_recompiler;
+
+// ----- Consts ----- //
+
+const ROM: &str = "test_roms/test_opcode.ch8";
+const GENERATED: &str = include_str!("static/test_opcode.rs");
+const FRAMES: usize = 60;
+
+/// Lookups that found recompiled code.
+static FOUND: AtomicUsize = AtomicUsize::new(0);
+
+// ----- Functions ----- //
+
+fn counted_block(address: usize) -> Option<&'static [StaticOp]> {
+    let result = test_opcode::block(address);
+    if result.is_some() {
+        FOUND.fetch_add(1, Ordering::Relaxed);
+    }
+    return result;
+}
+
+fn setup() -> (RomImage, Machine) {
+    let config = Config::from_args(&[ROM.to_string(), "--seed".to_string(), "0".to_string()]).unwrap();
+    let rom = RomImage::load(ROM).unwrap();
+    let machine = Machine::new(&rom, &config.settings(&rom));
+    return (rom, machine);
+}
+
+// ----- Tests ----- //
+
+#[test]
+fn recompiled_code_is_up_to_date() {
+    let (rom, _) = setup();
+    let config = Config::from_args(&[ROM.to_string()]).unwrap();
+    assert!(static_recompiler::generate(&rom, &config.settings(&rom).quirks) == GENERATED,
+            "Regenerate tests/static/test_opcode.rs with `chip-8 recompile`");
+}
+
+#[test]
+fn recompiled_code_runs_like_the_interpreter() {
+    let (_, mut interpreted) = setup();
+    let (_, mut recompiled) = setup();
+    recompiled.set_static_code(counted_block);
+
+    for frame in 0..FRAMES {
+        assert!(interpreted.run_frame().is_ok());
+        assert!(recompiled.run_frame().is_ok());
+        assert_eq!(recompiled.dump_registers(), interpreted.dump_registers(), "Registers differ in frame {}", frame);
+        assert_eq!(recompiled.instructions(), interpreted.instructions(), "Instructions differ in frame {}", frame);
+    }
+    assert_eq!(recompiled.display().dump(), interpreted.display().dump());
+    assert!(FOUND.load(Ordering::Relaxed) > 0);
+}